@@ -1,8 +1,121 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use std::mem::size_of;
 
-fn criterion_bechmark(_c: &mut Criterion) {
-    // Your benchmark code here
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rustydb::domain::entity::{Column, DataType, Row, Table, Value};
+use rustydb::domain::repository::{FilterCondition, FilterOperator};
+use rustydb::infrastructure::storage::MemoryStorage;
+
+/// 計測する行数
+const ROW_COUNTS: [usize; 2] = [1_000, 10_000];
+
+/// カテゴリの数（category = 'c3' の条件は行の1/10を選ぶ）
+const CATEGORIES: usize = 10;
+
+fn schema() -> Table {
+    Table::new("items")
+        .with_column(Column::new("id", DataType::Integer).primary_key()).unwrap()
+        .with_column(Column::new("name", DataType::Text)).unwrap()
+        .with_column(Column::new("category", DataType::Text)).unwrap()
+        .with_column(Column::new("price", DataType::Float)).unwrap()
+        .with_column(Column::new("stock", DataType::Integer)).unwrap()
+}
+
+fn sample_rows(count: usize) -> Vec<Row> {
+    (0..count).map(|i| {
+        let mut row = Row::new();
+        row.set("id", Value::Integer(i as i64));
+        row.set("name", Value::Text(format!("item{}", i)));
+        row.set("category", Value::Text(format!("c{}", i % CATEGORIES)));
+        row.set("price", Value::Float(i as f64 * 1.5));
+        row.set("stock", Value::Integer((i % 100) as i64));
+        row
+    }).collect()
+}
+
+/// カラム名で値を持つ行をスキーマの順に並べた値にする
+fn positional_rows(table: &Table, rows: &[Row]) -> Vec<Vec<Value>> {
+    rows.iter()
+        .map(|row| table.columns.iter().map(|c| row.get(&c.name).cloned().unwrap_or(Value::Null)).collect())
+        .collect()
+}
+
+fn category_filter() -> FilterCondition {
+    FilterCondition::Simple {
+        column: "category".to_string(),
+        operator: FilterOperator::Equal,
+        value: Value::Text("c3".to_string()),
+    }
+}
+
+/// 値の文字列を除いた、行の格納に使うヒープの概算（バイト）
+///
+/// HashMap の行はエントリごとにカラム名の String と制御バイトを持ち、位置の行は値だけを持つ。
+fn estimated_bytes(map_rows: &[Row], positional: &[Vec<Value>]) -> (usize, usize) {
+    let map_bytes = map_rows.iter().map(|row| {
+        let entries = row.values.capacity() * (size_of::<(String, Value)>() + 1);
+        let keys: usize = row.values.keys().map(String::capacity).sum();
+        size_of::<Row>() + entries + keys
+    }).sum();
+    let positional_bytes = positional.iter()
+        .map(|values| size_of::<Vec<Value>>() + values.capacity() * size_of::<Value>())
+        .sum();
+    (map_bytes, positional_bytes)
+}
+
+/// 行の格納方法ごとのメモリ使用量の概算を表示する
+fn report_memory() {
+    let table = schema();
+    for count in ROW_COUNTS {
+        let rows = sample_rows(count);
+        let positional = positional_rows(&table, &rows);
+        let (map_bytes, positional_bytes) = estimated_bytes(&rows, &positional);
+        println!(
+            "row layout memory ({} rows): HashMap {} KiB, positional {} KiB ({:.1}x)",
+            count,
+            map_bytes / 1024,
+            positional_bytes / 1024,
+            map_bytes as f64 / positional_bytes as f64,
+        );
+    }
+}
+
+/// WHERE category = 'c3' の全件走査を、行の格納方法ごとに比較する
+fn scan_benchmark(c: &mut Criterion) {
+    report_memory();
+
+    let table = schema();
+    let filter = category_filter();
+    let bound = filter.bind(&table).unwrap();
+
+    let mut group = c.benchmark_group("scan");
+    for count in ROW_COUNTS {
+        let rows = sample_rows(count);
+        let positional = positional_rows(&table, &rows);
+
+        // 行ごとにカラム名をハッシュして値を引く（以前の格納方法）
+        group.bench_with_input(BenchmarkId::new("hashmap", count), &rows, |b, rows| {
+            b.iter(|| rows.iter().filter(|row| filter.matches(*row)).count())
+        });
+
+        // カラムを位置に解決済みの条件で、スキーマの順に並べた値を引く
+        group.bench_with_input(BenchmarkId::new("positional", count), &positional, |b, rows| {
+            b.iter(|| rows.iter().filter(|values| bound.matches(values.as_slice())).count())
+        });
+
+        // ストレージ経由の検索（条件を満たした行だけをカラム名の行にして返す）
+        //
+        // 走査は位置の行で行うが、結果は API の境界でカラム名の Row に組み立て直すため、
+        // 時間の大半は選ばれた行ごとの HashMap の構築が占める。格納した Row を複製していた
+        // 以前の検索とほぼ同じ時間で、上の hashmap（行を組み立てない走査のみ）より遅い。
+        let storage = MemoryStorage::new();
+        storage.create_table(table.clone(), false).unwrap();
+        storage.insert_rows("items", rows.clone()).unwrap();
+        group.bench_with_input(BenchmarkId::new("storage_select", count), &storage, |b, storage| {
+            b.iter(|| storage.select_rows("items", None, Some(black_box(&filter))).unwrap().1.len())
+        });
+    }
+    group.finish();
 }
 
-criterion_group!(benches, criterion_bechmark);
-criterion_main!(benches);
\ No newline at end of file
+criterion_group!(benches, scan_benchmark);
+criterion_main!(benches);
@@ -38,11 +38,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let parsed = parser.parse(insert_sql)?;
     if let Some(rustydb::infrastructure::parser::ParsedStatement::Insert(stmt)) = parsed.first() {
-        for values in &stmt.values {
+        let rustydb::infrastructure::parser::InsertSource::Values(rows) = &stmt.source else {
+            return Err("INSERT ... SELECT is not used in this example".into());
+        };
+        for values in rows {
             let mut row = Row::new();
-            for (i, value) in values.iter().enumerate() {
+            for (i, expr) in values.iter().enumerate() {
                 if i < stmt.columns.len() {
-                    row.set(stmt.columns[i].clone(), value.clone());
+                    row.set(stmt.columns[i].clone(), expr.evaluate(&Row::new())?);
                 }
             }
             repository.insert(&stmt.table_name, &row).await?;
@@ -57,7 +60,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let parsed = parser.parse(select_sql)?;
     if let Some(rustydb::infrastructure::parser::ParsedStatement::Select(stmt)) = parsed.first() {
-        let cols = stmt.columns.as_ref().map_or(Vec::new(), |c| c.clone());
+        let cols = stmt.column_names().unwrap_or_default();
         let result = repository.select(&stmt.table_name, &cols, stmt.filter.as_ref()).await?;
         
         // 結果の表示
@@ -88,8 +91,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let parsed = parser.parse(update_sql)?;
     if let Some(rustydb::infrastructure::parser::ParsedStatement::Update(stmt)) = parsed.first() {
-        let count = repository.update(&stmt.table_name, &stmt.updates, stmt.filter.as_ref()).await?;
-        println!("{}行更新しました\n", count);
+        let updated = repository.update(&stmt.table_name, &stmt.updates, stmt.filter.as_ref()).await?;
+        println!("{}行更新しました\n", updated.len());
     }
     
     // 更新後のデータを表示
@@ -99,7 +102,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let parsed = parser.parse(select_sql)?;
     if let Some(rustydb::infrastructure::parser::ParsedStatement::Select(stmt)) = parsed.first() {
-        let cols = stmt.columns.as_ref().map_or(Vec::new(), |c| c.clone());
+        let cols = stmt.column_names().unwrap_or_default();
         let result = repository.select(&stmt.table_name, &cols, stmt.filter.as_ref()).await?;
         
         // 結果の表示
@@ -130,8 +133,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let parsed = parser.parse(delete_sql)?;
     if let Some(rustydb::infrastructure::parser::ParsedStatement::Delete(stmt)) = parsed.first() {
-        let count = repository.delete(&stmt.table_name, stmt.filter.as_ref()).await?;
-        println!("{}行削除しました\n", count);
+        let deleted = repository.delete(&stmt.table_name, stmt.filter.as_ref()).await?;
+        println!("{}行削除しました\n", deleted.len());
     }
     
     // 削除後のデータを表示
@@ -141,7 +144,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let parsed = parser.parse(select_sql)?;
     if let Some(rustydb::infrastructure::parser::ParsedStatement::Select(stmt)) = parsed.first() {
-        let cols = stmt.columns.as_ref().map_or(Vec::new(), |c| c.clone());
+        let cols = stmt.column_names().unwrap_or_default();
         let result = repository.select(&stmt.table_name, &cols, stmt.filter.as_ref()).await?;
         
         // 結果の表示
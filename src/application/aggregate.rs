@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+
+use rust_decimal::Decimal;
+
+use crate::domain::entity::{DataType, Value};
+use crate::domain::expression::{AggregateFunction, ExpressionError};
+use crate::domain::function::{AggregateState, UserAggregateRef};
+
+/// 集約関数の途中状態を保持するアキュムレーター
+#[derive(Debug)]
+pub enum Accumulator {
+    Count(i64),
+    Sum(Option<Value>),
+    /// 10進数の平均は10進数で合計する（それ以外は浮動小数点数）
+    Avg { sum: Option<Value>, count: i64 },
+    Min(Option<Value>),
+    Max(Option<Value>),
+    User { function: UserAggregateRef, state: AggregateState },
+}
+
+impl Accumulator {
+    pub fn new(function: AggregateFunction) -> Self {
+        match function {
+            AggregateFunction::Count => Accumulator::Count(0),
+            AggregateFunction::Sum => Accumulator::Sum(None),
+            AggregateFunction::Avg => Accumulator::Avg { sum: None, count: 0 },
+            AggregateFunction::Min => Accumulator::Min(None),
+            AggregateFunction::Max => Accumulator::Max(None),
+        }
+    }
+
+    /// 利用者定義の集約関数のアキュムレーターを作成する
+    pub fn user(function: &UserAggregateRef) -> Self {
+        Accumulator::User { function: function.clone(), state: function.init() }
+    }
+
+    /// 1行分の引数の値を取り込む（組み込みの集約関数は1番目の引数だけを使う）
+    pub fn update_args(&mut self, args: &[Value]) -> Result<(), ExpressionError> {
+        match self {
+            Accumulator::User { function, state } => function.update(state, args),
+            _ => self.update(args.first().unwrap_or(&Value::Null)),
+        }
+    }
+
+    /// 1行分の値を取り込む（COUNT(*) の場合は常に非NULLの値が渡される）
+    pub fn update(&mut self, value: &Value) -> Result<(), ExpressionError> {
+        if let Accumulator::User { function, state } = self {
+            return function.update(state, std::slice::from_ref(value));
+        }
+
+        // 組み込みの集約関数はNULLを無視する
+        if *value == Value::Null {
+            return Ok(());
+        }
+
+        match self {
+            Accumulator::Count(count) => *count += 1,
+            Accumulator::Sum(sum) => *sum = Some(add_numbers(sum.take(), value, "SUM")?),
+            Accumulator::Avg { sum, count } => {
+                // 整数の平均は浮動小数点数で合計する（オーバーフローを避けるため）
+                let value = match value {
+                    Value::Integer(i) => Value::Float(*i as f64),
+                    value => value.clone(),
+                };
+                *sum = Some(add_numbers(sum.take(), &value, "AVG")?);
+                *count += 1;
+            },
+            Accumulator::Min(current) => Self::keep_extreme(current, value, Ordering::Less)?,
+            Accumulator::Max(current) => Self::keep_extreme(current, value, Ordering::Greater)?,
+            Accumulator::User { .. } => unreachable!("user-defined aggregates are updated above"),
+        }
+
+        Ok(())
+    }
+
+    /// 同じ集約関数で別の行を集約したアキュムレーターの途中状態を取り込む
+    pub fn merge(&mut self, other: Accumulator) -> Result<(), ExpressionError> {
+        match (self, other) {
+            (Accumulator::Count(count), Accumulator::Count(other)) => *count += other,
+            (Accumulator::Sum(sum), Accumulator::Sum(other)) => {
+                if let Some(other) = other {
+                    *sum = Some(add_numbers(sum.take(), &other, "SUM")?);
+                }
+            },
+            (Accumulator::Avg { sum, count }, Accumulator::Avg { sum: other_sum, count: other_count }) => {
+                if let Some(other_sum) = other_sum {
+                    *sum = Some(add_numbers(sum.take(), &other_sum, "AVG")?);
+                }
+                *count += other_count;
+            },
+            (Accumulator::Min(current), Accumulator::Min(Some(other))) => Self::keep_extreme(current, &other, Ordering::Less)?,
+            (Accumulator::Max(current), Accumulator::Max(Some(other))) => Self::keep_extreme(current, &other, Ordering::Greater)?,
+            (Accumulator::Min(_), Accumulator::Min(None)) | (Accumulator::Max(_), Accumulator::Max(None)) => {},
+            (Accumulator::User { function, state }, Accumulator::User { state: other, .. }) => function.merge(state, other)?,
+            (accumulator, other) => return Err(ExpressionError::InvalidOperation(format!(
+                "Cannot merge aggregate states {:?} and {:?}", accumulator, other))),
+        }
+
+        Ok(())
+    }
+
+    /// 集約結果を取得する
+    pub fn finish(&self) -> Result<Value, ExpressionError> {
+        Ok(match self {
+            Accumulator::Count(count) => Value::Integer(*count),
+            Accumulator::Sum(sum) => sum.clone().unwrap_or(Value::Null),
+            Accumulator::Avg { sum: Some(Value::Decimal(sum)), count } => {
+                let scale = sum.scale().max(DataType::DECIMAL_DIVISION_SCALE as u32);
+                Value::Decimal((sum / Decimal::from(*count)).round_dp(scale))
+            },
+            Accumulator::Avg { sum: Some(Value::Float(sum)), count } => Value::Float(sum / *count as f64),
+            Accumulator::Avg { .. } => Value::Null,
+            Accumulator::Min(value) | Accumulator::Max(value) => value.clone().unwrap_or(Value::Null),
+            Accumulator::User { function, state } => function.finalize(state)?,
+        })
+    }
+
+    fn keep_extreme(current: &mut Option<Value>, value: &Value, wanted: Ordering) -> Result<(), ExpressionError> {
+        match current {
+            None => *current = Some(value.clone()),
+            Some(existing) => match value.compare(existing) {
+                Some(ordering) if ordering == wanted => *current = Some(value.clone()),
+                Some(_) => {},
+                None => return Err(ExpressionError::InvalidOperation(format!(
+                    "Cannot compare {} with {}", value.data_type(), existing.data_type()))),
+            },
+        }
+        Ok(())
+    }
+}
+
+/// SUM や AVG の途中の合計に値を加える
+///
+/// 整数同士と10進数（と整数）の合計は誤差なく求め、浮動小数点数を含む場合は浮動小数点数になる。
+fn add_numbers(sum: Option<Value>, value: &Value, name: &str) -> Result<Value, ExpressionError> {
+    let overflow = || ExpressionError::InvalidOperation(format!("{} overflow", name));
+    Ok(match (sum, value) {
+        (None, Value::Integer(_) | Value::Float(_) | Value::Decimal(_)) => value.clone(),
+        (Some(Value::Integer(a)), Value::Integer(b)) => Value::Integer(a.checked_add(*b).ok_or_else(overflow)?),
+        (Some(Value::Decimal(a)), Value::Decimal(b)) => Value::Decimal(a.checked_add(*b).ok_or_else(overflow)?),
+        (Some(Value::Decimal(a)), Value::Integer(b)) => Value::Decimal(a.checked_add(Decimal::from(*b)).ok_or_else(overflow)?),
+        (Some(Value::Integer(a)), Value::Decimal(b)) => Value::Decimal(b.checked_add(Decimal::from(a)).ok_or_else(overflow)?),
+        (Some(a @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_))), Value::Integer(_) | Value::Float(_) | Value::Decimal(_)) => {
+            Value::Float(as_f64(&a) + as_f64(value))
+        },
+        _ => return Err(ExpressionError::InvalidOperation(
+            format!("{} is not defined for {}", name, value.data_type()))),
+    })
+}
+
+fn as_f64(value: &Value) -> f64 {
+    match value.cast_to(&DataType::Float) {
+        Ok(Value::Float(f)) => f,
+        _ => f64::NAN,
+    }
+}
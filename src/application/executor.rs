@@ -0,0 +1,2529 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use chrono::{FixedOffset, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::application::aggregate::Accumulator;
+use crate::application::function::FunctionRegistry;
+use crate::domain::entity::{format_time_zone, Collation, Table, Column, DataType, Row, ResultSet, Value, ValueKey, View};
+use crate::domain::expression::{Expression, ExpressionError, OrderByExpr, compare_sort_keys, resolve_column};
+use crate::domain::function::{ScalarFunction, UserAggregate};
+use crate::domain::repository::{
+    TableRepository, RepositoryError, FilterCondition, OnConflict, ConflictAction, UpsertOutcome
+};
+use crate::infrastructure::parser::{
+    SqlParser, ParseError, ParsedStatement, SelectStatement, SelectItem, InsertSource, Distinct,
+    TableFunction, CreateViewStatement, DropViewStatement
+};
+
+mod cte;
+mod set_operation;
+mod subquery;
+mod window;
+
+#[cfg(test)]
+pub(crate) mod testing;
+
+use subquery::{map_filter, HIDDEN_COLUMN_PREFIX};
+
+/// ビュー展開の最大ネスト数（循環参照の検出用）
+const MAX_VIEW_DEPTH: usize = 32;
+
+/// GROUP BY の集約で、この行数ごとに部分的な集約を並行して行う
+const PARTIAL_AGGREGATE_ROWS: usize = 4096;
+
+/// クエリ実行エラー
+#[derive(Error, Debug)]
+pub enum ExecutorError {
+    #[error("Repository error: {0}")]
+    Repository(#[from] RepositoryError),
+
+    #[error("Parse error: {0}")]
+    Parse(#[from] ParseError),
+
+    #[error("Expression error: {0}")]
+    Expression(#[from] ExpressionError),
+
+    #[error("Execution error: {0}")]
+    Execution(String),
+}
+
+impl From<ExecutorError> for crate::Error {
+    fn from(err: ExecutorError) -> Self {
+        match err {
+            ExecutorError::Repository(e) => e.into(),
+            ExecutorError::Parse(e) => crate::Error::Parse(e.to_string()),
+            ExecutorError::Expression(e) => crate::Error::Execution(e.to_string()),
+            ExecutorError::Execution(msg) => crate::Error::Execution(msg),
+        }
+    }
+}
+
+/// SQL文の実行結果
+#[derive(Debug, Clone)]
+pub struct ExecutionResult {
+    /// 文の種類（"SELECT", "INSERT" など）
+    pub statement_type: String,
+
+    /// 行を返す文の結果セット
+    pub result_set: Option<ResultSet>,
+
+    /// 影響を受けた行数
+    pub affected_rows: Option<usize>,
+
+    /// UPSERTで新たに挿入された行数
+    pub inserted_rows: Option<usize>,
+
+    /// UPSERTで既存の行を更新した行数
+    pub updated_rows: Option<usize>,
+
+    /// 文を実行した時点のセッションのタイムゾーン（タイムゾーン付きの日時を表示する際に使う）
+    pub time_zone: FixedOffset,
+}
+
+impl ExecutionResult {
+    fn rows(statement_type: &str, result_set: ResultSet) -> Self {
+        Self {
+            statement_type: statement_type.to_string(),
+            result_set: Some(result_set),
+            affected_rows: None,
+            inserted_rows: None,
+            updated_rows: None,
+            time_zone: Utc.fix(),
+        }
+    }
+
+    fn affected(statement_type: &str, affected_rows: Option<usize>) -> Self {
+        Self {
+            statement_type: statement_type.to_string(),
+            result_set: None,
+            affected_rows,
+            inserted_rows: None,
+            updated_rows: None,
+            time_zone: Utc.fix(),
+        }
+    }
+
+    /// RETURNING句の結果を付け加える
+    fn with_returning(mut self, result_set: Option<ResultSet>) -> Self {
+        self.result_set = result_set;
+        self
+    }
+
+    fn upserted(inserted: usize, updated: usize) -> Self {
+        Self {
+            statement_type: "INSERT".to_string(),
+            result_set: None,
+            affected_rows: Some(inserted + updated),
+            inserted_rows: Some(inserted),
+            updated_rows: Some(updated),
+            time_zone: Utc.fix(),
+        }
+    }
+}
+
+/// スクリプト中の文がエラーになった場合の扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnError {
+    /// 以降の文を実行しない
+    #[default]
+    Stop,
+    /// 以降の文の実行を続ける
+    Continue,
+}
+
+/// 複数の文を実行する際のオプション
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScriptOptions {
+    pub on_error: OnError,
+
+    /// スクリプト全体を1つのトランザクションとして実行する
+    /// （エラーが発生した時点で停止し、すべての変更を取り消す。OnError::Continue とは組み合わせられない）
+    pub transaction: bool,
+}
+
+/// 複数の文の実行結果
+#[derive(Debug)]
+pub struct ScriptResult {
+    /// 実行した文ごとの結果（実行されなかった文は含まない）
+    pub results: Vec<Result<ExecutionResult, ExecutorError>>,
+
+    /// トランザクションをロールバックしたかどうか
+    pub rolled_back: bool,
+}
+
+type SelectFuture<'a> = Pin<Box<dyn Future<Output = Result<ResultSet, ExecutorError>> + Send + 'a>>;
+
+/// 差分更新の対象となるマテリアライズドビューとその定義クエリ
+type DependentView = (View, SelectStatement);
+
+/// グループキーと、そのグループの集約関数ごとのアキュムレーター（集約関数でない項目はNone）
+type AccumulatedGroup = (ValueKey, Vec<Option<Accumulator>>);
+
+/// SELECT文を実行するときの文脈（ネストの深さと参照できる共通テーブル式）
+#[derive(Debug, Clone, Default)]
+struct QueryContext {
+    depth: usize,
+    ctes: HashMap<String, Arc<ResultSet>>,
+}
+
+impl QueryContext {
+    /// サブクエリなど、共通テーブル式を引き継ぐ内側のクエリの文脈
+    fn nested(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ctes: self.ctes.clone(),
+        }
+    }
+
+    /// ビュー定義の文脈（呼び出し側の共通テーブル式は見えない）
+    fn view(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ctes: HashMap::new(),
+        }
+    }
+}
+
+/// 解析済みのSQL文をリポジトリに対して実行する
+///
+/// 実行器は1つのセッションを表し、SET TIME ZONE の設定はその実行器で実行する文だけに適用される。
+/// 複数のクライアントから使う場合は `session` でクライアントごとの実行器を作る。
+pub struct QueryExecutor {
+    repository: Arc<dyn TableRepository>,
+    parser: SqlParser,
+    /// SQLから呼び出せるスカラー関数と利用者定義の集約関数
+    functions: Arc<FunctionRegistry>,
+    /// トランザクション中のスクリプトが他の文と混ざらないようにするためのロック（セッション間で共有する）
+    transaction_lock: Arc<RwLock<()>>,
+    /// セッションのタイムゾーン（SET TIME ZONE で変更する）
+    time_zone: std::sync::RwLock<FixedOffset>,
+}
+
+impl QueryExecutor {
+    pub fn new(repository: Arc<dyn TableRepository>) -> Self {
+        Self {
+            repository,
+            parser: SqlParser::new(),
+            functions: Arc::new(FunctionRegistry::with_builtins()),
+            transaction_lock: Arc::new(RwLock::new(())),
+            time_zone: std::sync::RwLock::new(Utc.fix()),
+        }
+    }
+
+    /// 同じリポジトリに対する新しいセッションの実行器を作る
+    ///
+    /// 登録済みの関数とトランザクションのロックを共有し、タイムゾーンはUTCから始まる。
+    pub fn session(&self) -> Self {
+        Self {
+            repository: self.repository.clone(),
+            parser: SqlParser::new(),
+            functions: self.functions.clone(),
+            transaction_lock: self.transaction_lock.clone(),
+            time_zone: std::sync::RwLock::new(Utc.fix()),
+        }
+    }
+
+    /// セッションのタイムゾーン
+    ///
+    /// タイムゾーン付きの日時のカラムに書き込む値のうち、タイムゾーンを持たないものはこのタイムゾーンの日時とみなす。
+    /// タイムゾーン付きの日時のCAST、EXTRACT、DATE_TRUNC もこのタイムゾーンの日時として計算する。
+    pub fn time_zone(&self) -> FixedOffset {
+        *self.time_zone.read().unwrap()
+    }
+
+    /// セッションのタイムゾーンを変更する
+    pub fn set_time_zone(&self, time_zone: FixedOffset) {
+        *self.time_zone.write().unwrap() = time_zone;
+    }
+
+    /// SQLから呼び出せるスカラー関数を登録する（同じ名前の関数があれば置き換える）
+    pub fn register_function(&mut self, function: impl ScalarFunction + 'static) {
+        Arc::make_mut(&mut self.functions).register(Arc::new(function));
+    }
+
+    /// SQLから呼び出せる利用者定義の集約関数を登録する（同じ名前の関数があれば置き換える）
+    pub fn register_aggregate(&mut self, function: impl UserAggregate + 'static) {
+        Arc::make_mut(&mut self.functions).register_aggregate(Arc::new(function));
+    }
+
+    /// 解析済みのSQL文を実行する
+    pub async fn execute(&self, stmt: &ParsedStatement) -> Result<ExecutionResult, ExecutorError> {
+        let _guard = self.transaction_lock.read().await;
+        self.execute_statement(stmt).await
+    }
+
+    /// 複数のSQL文を順に実行する
+    ///
+    /// トランザクションではエラーになった文の変更だけを取り消す手段がないため、
+    /// エラー後も実行を続ける指定との組み合わせはどの文も実行せずにエラーにする。
+    pub async fn execute_script(
+        &self,
+        statements: &[ParsedStatement],
+        options: ScriptOptions
+    ) -> Result<ScriptResult, ExecutorError> {
+        if options.transaction && options.on_error == OnError::Continue {
+            return Err(ExecutorError::Execution(
+                "on_error=continue cannot be used with transaction=true (a failing statement rolls back the whole script)".to_string()));
+        }
+
+        let mut results = Vec::new();
+
+        if !options.transaction {
+            for stmt in statements {
+                let result = self.execute(stmt).await;
+                let failed = result.is_err();
+                results.push(result);
+                if failed && options.on_error == OnError::Stop {
+                    break;
+                }
+            }
+            return Ok(ScriptResult { results, rolled_back: false });
+        }
+
+        // トランザクションの間は他の文を実行させない
+        let _guard = self.transaction_lock.write().await;
+        self.repository.begin_transaction().await?;
+
+        let mut failed = false;
+        for stmt in statements {
+            let result = self.execute_statement(stmt).await;
+            failed = result.is_err();
+            results.push(result);
+            if failed {
+                break;
+            }
+        }
+
+        if failed {
+            self.repository.rollback_transaction().await?;
+        } else {
+            self.repository.commit_transaction().await?;
+        }
+
+        Ok(ScriptResult { results, rolled_back: failed })
+    }
+
+    /// 文を実行し、結果に実行時点のセッションのタイムゾーンを記録する
+    async fn execute_statement(&self, stmt: &ParsedStatement) -> Result<ExecutionResult, ExecutorError> {
+        let mut result = self.run_statement(stmt).await?;
+        result.time_zone = self.time_zone();
+        Ok(result)
+    }
+
+    async fn run_statement(&self, stmt: &ParsedStatement) -> Result<ExecutionResult, ExecutorError> {
+        match stmt {
+            ParsedStatement::CreateTable(create_stmt) => {
+                if create_stmt.if_not_exists && self.repository.table_exists(&create_stmt.table_name).await? {
+                    return Ok(ExecutionResult::affected("CREATE_TABLE", Some(0)));
+                }
+
+                if let Some(query) = &create_stmt.query {
+                    let inserted = self.create_table_as_select(&create_stmt.table_name, query).await?;
+                    return Ok(ExecutionResult::affected("CREATE_TABLE", Some(inserted)));
+                }
+
+                let mut table = Table::new(&create_stmt.table_name);
+                for column in &create_stmt.columns {
+                    let data_type = self.resolve_column_type(&column.data_type).await?;
+                    table.add_column(Column { data_type, ..column.clone() })
+                        .map_err(|e| ExecutorError::Execution(e.to_string()))?;
+                }
+
+                self.repository.create_table(&table).await?;
+
+                Ok(ExecutionResult::affected("CREATE_TABLE", Some(0)))
+            },
+
+            ParsedStatement::Select(select_stmt) => {
+                let result = self.execute_select(select_stmt).await?;
+                Ok(ExecutionResult::rows("SELECT", result))
+            },
+
+            ParsedStatement::Insert(insert_stmt) => {
+                let table = self.repository.get_table(&insert_stmt.table_name).await?;
+
+                // カラムリストが省略された場合はテーブル定義の順序に従う
+                let columns = if insert_stmt.columns.is_empty() {
+                    table.column_names().into_iter().map(String::from).collect()
+                } else {
+                    insert_stmt.columns.clone()
+                };
+
+                let values = match &insert_stmt.source {
+                    InsertSource::Values(values) => values.iter()
+                        .map(|row| row.iter().map(|expr| {
+                            let expr = self.functions.resolve(expr, self.time_zone())?;
+                            if expr.contains_aggregate() {
+                                return Err(ExecutorError::Execution(
+                                    "Aggregate functions are not allowed in VALUES".to_string()));
+                            }
+                            Ok(expr.evaluate(&Row::new())?)
+                        }).collect::<Result<Vec<_>, ExecutorError>>())
+                        .collect::<Result<Vec<_>, _>>()?,
+                    InsertSource::Select(query) => {
+                        let result = self.execute_select(query).await?;
+                        result.rows.iter().map(|row| {
+                            result.columns.iter()
+                                .map(|c| row.get(&c.name).cloned().unwrap_or(Value::Null))
+                                .collect()
+                        }).collect()
+                    },
+                };
+
+                // RETURNING句の誤りは変更を加える前に検出する
+                self.returning(&insert_stmt.table_name, insert_stmt.returning.as_deref(), Vec::new()).await?;
+
+                let column_types: HashMap<&str, &DataType> = table.columns.iter()
+                    .map(|c| (c.name.as_str(), &c.data_type))
+                    .collect();
+                let time_zone = self.time_zone();
+
+                let mut rows = Vec::new();
+                for row_values in values {
+                    if row_values.len() > columns.len() {
+                        return Err(ExecutorError::Execution(
+                            "INSERT has more expressions than target columns".to_string()));
+                    }
+                    if matches!(insert_stmt.source, InsertSource::Select(_)) && row_values.len() < columns.len() {
+                        return Err(ExecutorError::Execution(
+                            "INSERT has more target columns than expressions".to_string()));
+                    }
+
+                    let mut row = Row::new();
+                    for (column, value) in columns.iter().zip(row_values) {
+                        let value = match column_types.get(column.as_str()) {
+                            Some(data_type) => localize(value, data_type, time_zone)?,
+                            None => value,
+                        };
+                        row.set(column.clone(), value);
+                    }
+                    rows.push(row);
+                }
+
+                if let Some(on_conflict) = &insert_stmt.on_conflict {
+                    let on_conflict = self.resolve_conflict_functions(on_conflict)?;
+                    let (inserted, updated, changed) = self.upsert_rows(&insert_stmt.table_name, rows, &on_conflict).await?;
+                    let returning = self.returning(&insert_stmt.table_name, insert_stmt.returning.as_deref(), changed).await?;
+                    return Ok(ExecutionResult::upserted(inserted, updated).with_returning(returning));
+                }
+
+                let inserted = self.insert_rows(&insert_stmt.table_name, rows).await?;
+                let affected = inserted.len();
+                let returning = self.returning(&insert_stmt.table_name, insert_stmt.returning.as_deref(), inserted).await?;
+
+                Ok(ExecutionResult::affected("INSERT", Some(affected)).with_returning(returning))
+            },
+
+            ParsedStatement::Update(update_stmt) => {
+                // RETURNING句の誤りは変更を加える前に検出する
+                self.returning(&update_stmt.table_name, update_stmt.returning.as_deref(), Vec::new()).await?;
+                let filter = self.resolve_filter(
+                    &update_stmt.table_name,
+                    update_stmt.table_alias.as_deref(),
+                    update_stmt.filter.as_ref()
+                ).await?;
+
+                let table = self.repository.get_table(&update_stmt.table_name).await?;
+                let time_zone = self.time_zone();
+                let updates = update_stmt.updates.iter().map(|(column, expr)| {
+                    let expr = match (expr, table.get_column(column)) {
+                        (Expression::Literal(value), Some(column)) =>
+                            Expression::Literal(localize(value.clone(), &column.data_type, time_zone)?),
+                        (expr, _) => self.functions.resolve(expr, time_zone)?,
+                    };
+                    Ok((column.clone(), expr))
+                }).collect::<Result<Vec<_>, ExecutorError>>()?;
+
+                let dependents = self.incremental_views_on(&update_stmt.table_name).await?;
+                let (old_rows, new_rows): (Vec<Row>, Vec<Row>) = self.repository.update(
+                    &update_stmt.table_name,
+                    &updates,
+                    filter.as_ref()
+                ).await?.into_iter().unzip();
+                let affected = new_rows.len();
+
+                self.apply_view_deltas(&dependents, &old_rows, &new_rows).await?;
+                let returning = self.returning(&update_stmt.table_name, update_stmt.returning.as_deref(), new_rows).await?;
+
+                Ok(ExecutionResult::affected("UPDATE", Some(affected)).with_returning(returning))
+            },
+
+            ParsedStatement::Delete(delete_stmt) => {
+                self.returning(&delete_stmt.table_name, delete_stmt.returning.as_deref(), Vec::new()).await?;
+                let filter = self.resolve_filter(
+                    &delete_stmt.table_name,
+                    delete_stmt.table_alias.as_deref(),
+                    delete_stmt.filter.as_ref()
+                ).await?;
+
+                let dependents = self.incremental_views_on(&delete_stmt.table_name).await?;
+                let removed = self.repository.delete(
+                    &delete_stmt.table_name,
+                    filter.as_ref()
+                ).await?;
+                let affected = removed.len();
+
+                self.apply_view_deltas(&dependents, &removed, &[]).await?;
+                let returning = self.returning(&delete_stmt.table_name, delete_stmt.returning.as_deref(), removed).await?;
+
+                Ok(ExecutionResult::affected("DELETE", Some(affected)).with_returning(returning))
+            },
+
+            ParsedStatement::DropTable(drop_stmt) => {
+                self.repository.drop_table(&drop_stmt.table_name).await?;
+
+                Ok(ExecutionResult::affected("DROP_TABLE", None))
+            },
+
+            ParsedStatement::CreateView(create_stmt) => {
+                if create_stmt.materialized {
+                    let rows = self.create_materialized_view(create_stmt).await?;
+                    return Ok(ExecutionResult::affected("CREATE_MATERIALIZED_VIEW", Some(rows)));
+                }
+
+                let view = View::new(&create_stmt.view_name, &create_stmt.query)
+                    .with_columns(create_stmt.columns.clone());
+
+                self.repository.create_view(&view, create_stmt.or_replace).await?;
+
+                Ok(ExecutionResult::affected("CREATE_VIEW", Some(0)))
+            },
+
+            ParsedStatement::DropView(drop_stmt) => {
+                self.drop_view(drop_stmt).await?;
+
+                let statement_type = if drop_stmt.materialized { "DROP_MATERIALIZED_VIEW" } else { "DROP_VIEW" };
+                Ok(ExecutionResult::affected(statement_type, None))
+            },
+
+            ParsedStatement::RefreshMaterializedView(refresh_stmt) => {
+                let rows = self.refresh_materialized_view(&refresh_stmt.view_name).await?;
+
+                Ok(ExecutionResult::affected("REFRESH_MATERIALIZED_VIEW", Some(rows)))
+            },
+
+            ParsedStatement::SetTimeZone(set_stmt) => {
+                self.set_time_zone(set_stmt.time_zone);
+
+                Ok(ExecutionResult::affected("SET", None))
+            },
+
+            ParsedStatement::ShowTimeZone => {
+                let mut result = ResultSet::new(vec![Column::new("TimeZone", DataType::Text)]);
+                let mut row = Row::new();
+                row.set("TimeZone".to_string(), Value::Text(format_time_zone(self.time_zone())));
+                result.rows.push(row);
+
+                Ok(ExecutionResult::rows("SHOW", result))
+            },
+
+            ParsedStatement::CreateType(create_stmt) => {
+                self.repository.create_type(&create_stmt.enum_type).await?;
+
+                Ok(ExecutionResult::affected("CREATE_TYPE", None))
+            },
+
+            ParsedStatement::AlterType(alter_stmt) => {
+                self.repository.add_enum_label(
+                    &alter_stmt.type_name,
+                    &alter_stmt.label,
+                    alter_stmt.position.as_ref(),
+                    alter_stmt.if_not_exists
+                ).await?;
+
+                Ok(ExecutionResult::affected("ALTER_TYPE", None))
+            },
+        }
+    }
+
+    /// カラム定義の列挙型（配列の要素を含む）を登録済みの型の定義に置き換える
+    async fn resolve_column_type(&self, data_type: &DataType) -> Result<DataType, ExecutorError> {
+        match data_type {
+            DataType::Enum(enum_type) => Ok(DataType::Enum(self.repository.get_type(&enum_type.name).await?)),
+            DataType::Array(element) => {
+                Ok(DataType::Array(Box::new(Box::pin(self.resolve_column_type(element)).await?)))
+            },
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// 条件の文字列の定数を、比較するカラムの型の値に変換する
+    fn coerce_filter(&self, filter: &FilterCondition, columns: &[Column]) -> Result<FilterCondition, ExecutorError> {
+        let time_zone = self.time_zone();
+        Ok(filter.coerce_literals(columns, &mut |text, column| convert_literal(text, column, time_zone))?)
+    }
+
+    /// SELECT文を実行する
+    pub async fn execute_select(&self, stmt: &SelectStatement) -> Result<ResultSet, ExecutorError> {
+        self.select_in(stmt, &QueryContext::default()).await
+    }
+
+    /// FROM句のテーブル、ビューまたは共通テーブル式からSELECTを実行する
+    fn select_in<'a>(&'a self, stmt: &'a SelectStatement, ctx: &'a QueryContext) -> SelectFuture<'a> {
+        Box::pin(async move {
+            let with_ctx;
+            let ctx = if stmt.with.is_empty() {
+                ctx
+            } else {
+                with_ctx = self.evaluate_ctes(&stmt.with, ctx).await?;
+                &with_ctx
+            };
+            let stmt = &self.resolve_functions(stmt)?;
+
+            if let Some(set) = &stmt.set_operation {
+                let mut result = self.execute_set_operation(set, ctx).await?;
+                sort_and_distinct(stmt, &mut result, None)?;
+                if let Some(limit) = stmt.limit {
+                    result.rows.truncate(limit);
+                }
+                return Ok(result);
+            }
+
+            let resolved = self.resolve_subqueries(stmt, ctx).await?;
+            let (stmt, filter_subqueries, projection_subqueries) = match &resolved {
+                Some(resolved) => (&resolved.stmt, &resolved.filter_subqueries[..], &resolved.projection_subqueries[..]),
+                None => (stmt, &[][..], &[][..]),
+            };
+
+            let mut source = if filter_subqueries.is_empty() {
+                self.fetch_source(stmt, stmt.filter.as_ref(), ctx).await?
+            } else {
+                // 相関サブクエリは行ごとに実行してから条件を適用する
+                let mut source = self.fetch_source(stmt, None, ctx).await?;
+                self.evaluate_correlated(filter_subqueries, &mut source, ctx).await?;
+                if let Some(filter) = &stmt.filter {
+                    let filter = self.coerce_filter(filter, &source.columns)?.with_collations(&source.columns);
+                    source.rows.retain(|row| filter.matches(row));
+                }
+                source
+            };
+            self.evaluate_correlated(projection_subqueries, &mut source, ctx).await?;
+
+            let mut result = if stmt.contains_window() {
+                window::project_windows(stmt, source)?
+            } else {
+                project(stmt, source)?
+            };
+
+            if let Some(limit) = stmt.limit {
+                result.rows.truncate(limit);
+            }
+
+            Ok(result)
+        })
+    }
+
+    /// FROM句のテーブル、ビュー、共通テーブル式またはテーブル関数から、WHERE句を満たす行をすべてのカラムについて取得する
+    async fn fetch_source(
+        &self,
+        stmt: &SelectStatement,
+        filter: Option<&FilterCondition>,
+        ctx: &QueryContext
+    ) -> Result<ResultSet, ExecutorError> {
+        if let Some(function) = &stmt.table_function {
+            let mut result = table_function_rows(function)?;
+            if let Some(filter) = filter {
+                let filter = self.coerce_filter(filter, &result.columns)?.with_collations(&result.columns);
+                result.rows.retain(|row| filter.matches(row));
+            }
+            return Ok(result);
+        }
+
+        let table_name = stmt.table_name.as_str();
+        // 共通テーブル式は同じ名前のテーブルやビューより優先する
+        if let Some(cte) = ctx.ctes.get(table_name) {
+            let mut result = cte.as_ref().clone();
+            if let Some(filter) = filter {
+                let filter = self.coerce_filter(filter, &result.columns)?.with_collations(&result.columns);
+                result.rows.retain(|row| filter.matches(row));
+            }
+            return Ok(result);
+        }
+
+        if self.repository.view_exists(table_name).await? {
+            let view = self.repository.get_view(table_name).await?;
+
+            // マテリアライズドビューは保持しているテーブルから読み出す
+            if !view.materialized {
+                return self.expand_view(&view, filter, ctx).await;
+            }
+        }
+
+        let filter = match filter {
+            Some(filter) => Some(self.coerce_filter(filter, &self.repository.get_table(table_name).await?.columns)?),
+            None => None,
+        };
+        Ok(self.repository.select(table_name, &[], filter.as_ref()).await?)
+    }
+
+    /// ビューの定義を展開し、外側のクエリの条件を適用する
+    async fn expand_view(
+        &self,
+        view: &View,
+        filter: Option<&FilterCondition>,
+        ctx: &QueryContext
+    ) -> Result<ResultSet, ExecutorError> {
+        if ctx.depth >= MAX_VIEW_DEPTH {
+            return Err(ExecutorError::Execution(format!(
+                "View {} is nested too deeply (circular definition?)", view.name)));
+        }
+
+        let inner = self.view_query(view)?;
+        let mut result = rename_columns(self.select_in(&inner, &ctx.view()).await?, view)?;
+
+        if let Some(filter) = filter {
+            let filter = self.coerce_filter(filter, &result.columns)?.with_collations(&result.columns);
+            result.rows.retain(|row| filter.matches(row));
+        }
+
+        Ok(result)
+    }
+
+    /// ビュー定義のSQLテキストを再解析してSELECT文を取得する
+    fn view_query(&self, view: &View) -> Result<SelectStatement, ExecutorError> {
+        match self.parser.parse(&view.query)?.into_iter().next() {
+            Some(ParsedStatement::Select(inner)) => Ok(inner),
+            _ => Err(ExecutorError::Execution(format!(
+                "View {} does not contain a SELECT query", view.name))),
+        }
+    }
+
+    /// SELECT文（WITH句と集合演算の各クエリを除く）に含まれる関数呼び出しを関数レジストリで解決し、
+    /// キャストとタイムゾーンに依存する関数にセッションのタイムゾーンを結び付ける
+    fn resolve_functions(&self, stmt: &SelectStatement) -> Result<SelectStatement, ExecutorError> {
+        let mut error = None;
+        let time_zone = self.time_zone();
+        let mut resolve = |expr: &Expression| self.functions.resolve(expr, time_zone).unwrap_or_else(|e| {
+            error.get_or_insert(e);
+            expr.clone()
+        });
+
+        let resolved = SelectStatement {
+            distinct: match &stmt.distinct {
+                Some(Distinct::On(exprs)) => Some(Distinct::On(exprs.iter().map(&mut resolve).collect())),
+                distinct => distinct.clone(),
+            },
+            projection: stmt.projection.iter().map(|item| match item {
+                SelectItem::Expression { expr, alias } => SelectItem::Expression {
+                    expr: resolve(expr),
+                    alias: alias.clone(),
+                },
+                SelectItem::Wildcard => SelectItem::Wildcard,
+            }).collect(),
+            filter: stmt.filter.as_ref().map(|filter| map_filter(filter, &mut resolve)),
+            order_by: stmt.order_by.iter().map(|item| OrderByExpr {
+                expr: resolve(&item.expr),
+                ..item.clone()
+            }).collect(),
+            table_function: stmt.table_function.as_ref().map(|function| match function {
+                TableFunction::Unnest { array, column } => TableFunction::Unnest {
+                    array: resolve(array),
+                    column: column.clone(),
+                },
+                TableFunction::SingleRow => TableFunction::SingleRow,
+            }),
+            ..stmt.clone()
+        };
+
+        match error {
+            Some(e) => Err(e.into()),
+            None => Ok(resolved),
+        }
+    }
+
+    /// ON CONFLICT DO UPDATE の式に含まれる関数呼び出しを解決する
+    fn resolve_conflict_functions(&self, on_conflict: &OnConflict) -> Result<OnConflict, ExecutorError> {
+        let action = match &on_conflict.action {
+            ConflictAction::DoUpdate(assignments) => ConflictAction::DoUpdate(assignments.iter()
+                .map(|(column, expr)| Ok((column.clone(), self.functions.resolve(expr, self.time_zone())?)))
+                .collect::<Result<_, ExecutorError>>()?),
+            ConflictAction::DoNothing => ConflictAction::DoNothing,
+        };
+        Ok(OnConflict { columns: on_conflict.columns.clone(), action })
+    }
+
+    /// 行を1行ずつ挿入し、差分更新対象のビューに反映する（格納された行を返す）
+    async fn insert_rows(&self, table_name: &str, rows: Vec<Row>) -> Result<Vec<Row>, ExecutorError> {
+        let mut inserted = Vec::new();
+        let mut outcome = Ok(());
+
+        for row in rows {
+            match self.repository.insert(table_name, &row).await {
+                Ok(stored) => inserted.push(stored),
+                Err(e) => {
+                    outcome = Err(e);
+                    break;
+                },
+            }
+        }
+
+        // 途中で失敗した場合も、挿入済みの行はビューに反映する
+        let dependents = self.incremental_views_on(table_name).await?;
+        self.apply_view_deltas(&dependents, &[], &inserted).await?;
+        outcome?;
+
+        Ok(inserted)
+    }
+
+    /// 行を1行ずつUPSERTし、挿入した行数と更新した行数、および挿入・更新後の行を返す
+    async fn upsert_rows(
+        &self,
+        table_name: &str,
+        rows: Vec<Row>,
+        on_conflict: &OnConflict
+    ) -> Result<(usize, usize, Vec<Row>), ExecutorError> {
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        let mut inserted = 0;
+        let mut updated = 0;
+        let mut outcome = Ok(());
+
+        for row in rows {
+            match self.repository.upsert(table_name, &row, on_conflict).await {
+                Ok(UpsertOutcome::Inserted(stored)) => {
+                    inserted += 1;
+                    added.push(stored);
+                },
+                Ok(UpsertOutcome::Updated(old_row, new_row)) => {
+                    updated += 1;
+                    removed.push(old_row);
+                    added.push(new_row);
+                },
+                Ok(UpsertOutcome::Skipped) => {},
+                Err(e) => {
+                    outcome = Err(e);
+                    break;
+                },
+            }
+        }
+
+        // 途中で失敗した場合も、反映済みの行はビューに反映する
+        let dependents = self.incremental_views_on(table_name).await?;
+        self.apply_view_deltas(&dependents, &removed, &added).await?;
+        outcome?;
+
+        Ok((inserted, updated, added))
+    }
+
+    /// SELECTの結果からテーブルを作成し、挿入した行数を返す
+    async fn create_table_as_select(&self, table_name: &str, query: &SelectStatement) -> Result<usize, ExecutorError> {
+        let result = self.execute_select(query).await?;
+
+        // カラムの型は結果セットから推論し、制約は引き継がない
+        let mut table = Table::new(table_name);
+        for column in &result.columns {
+            // 型の決まらないNULLリテラルのカラムはTEXTとして扱う
+            let data_type = if column.data_type == DataType::Null { DataType::Text } else { column.data_type.clone() };
+            table.add_column(Column::new(&column.name, data_type))
+                .map_err(|e| ExecutorError::Execution(e.to_string()))?;
+        }
+        table.validate().map_err(|e| ExecutorError::Execution(e.to_string()))?;
+
+        self.repository.create_table(&table).await?;
+        Ok(self.insert_rows(table_name, result.rows).await?.len())
+    }
+
+    /// マテリアライズドビューを作成し、作成時点の結果行数を返す
+    async fn create_materialized_view(&self, stmt: &CreateViewStatement) -> Result<usize, ExecutorError> {
+        let mut view = View::new(&stmt.view_name, &stmt.query)
+            .with_columns(stmt.columns.clone());
+        view = if stmt.incremental { view.incremental() } else { view.materialized() };
+
+        let query = self.view_query(&view)?;
+        if view.incremental {
+            self.check_incremental(&view, &query).await?;
+        }
+
+        let result = rename_columns(self.execute_select(&query).await?, &view)?;
+
+        // 結果はカラムの型と照合順序だけを引き継いだテーブルとして保持する
+        let mut table = Table::new(&view.name);
+        for column in &result.columns {
+            let stored = Column::new(&column.name, column.data_type.clone());
+            let stored = match column.collation {
+                Some(collation) => stored.with_collation(collation),
+                None => stored,
+            };
+            table.add_column(stored)
+                .map_err(|e| ExecutorError::Execution(e.to_string()))?;
+        }
+
+        self.repository.create_materialized_view(&view, &table, &result.rows, stmt.or_replace).await?;
+
+        Ok(result.rows.len())
+    }
+
+    /// マテリアライズドビューを再計算し、結果の行数を返す
+    async fn refresh_materialized_view(&self, view_name: &str) -> Result<usize, ExecutorError> {
+        let view = self.repository.get_view(view_name).await?;
+        if !view.materialized {
+            return Err(ExecutorError::Execution(format!(
+                "{} is not a materialized view", view_name)));
+        }
+
+        let query = self.view_query(&view)?;
+        let result = rename_columns(self.execute_select(&query).await?, &view)?;
+        self.repository.replace_rows(view_name, &result.rows).await?;
+
+        Ok(result.rows.len())
+    }
+
+    /// ビューを削除する（DROP VIEW と DROP MATERIALIZED VIEW の取り違えはエラーにする）
+    async fn drop_view(&self, stmt: &DropViewStatement) -> Result<(), ExecutorError> {
+        if self.repository.view_exists(&stmt.view_name).await? {
+            let view = self.repository.get_view(&stmt.view_name).await?;
+            if view.materialized != stmt.materialized {
+                return Err(ExecutorError::Execution(if view.materialized {
+                    format!("{} is a materialized view; use DROP MATERIALIZED VIEW", view.name)
+                } else {
+                    format!("{} is not a materialized view; use DROP VIEW", view.name)
+                }));
+            }
+        }
+
+        self.repository.drop_view(&stmt.view_name, stmt.if_exists).await?;
+        Ok(())
+    }
+
+    /// 差分更新できるビュー定義かどうかを検証する
+    async fn check_incremental(&self, view: &View, query: &SelectStatement) -> Result<(), ExecutorError> {
+        let query = &self.resolve_functions(query)?;
+        let unsupported = |reason: &str| Err(ExecutorError::Execution(format!(
+            "Materialized view {} cannot be maintained incrementally: {}", view.name, reason)));
+
+        if query.set_operation.is_some() {
+            return unsupported("set operations are not supported");
+        }
+        if query.distinct.is_some() {
+            return unsupported("DISTINCT is not supported");
+        }
+        if query.table_function.is_some() || self.repository.view_exists(&query.table_name).await? {
+            return unsupported("the query must read from a base table");
+        }
+        if query.limit.is_some() {
+            return unsupported("LIMIT is not supported");
+        }
+        if query.contains_subquery() {
+            return unsupported("subqueries are not supported");
+        }
+        if !query.with.is_empty() {
+            return unsupported("WITH queries are not supported");
+        }
+        if query.contains_window() {
+            return unsupported("window functions are not supported");
+        }
+        if query.is_aggregate() {
+            group_output_columns(view, query)?;
+        }
+
+        Ok(())
+    }
+
+    /// 指定したテーブルを参照する差分更新対象のマテリアライズドビューを取得する
+    async fn incremental_views_on(&self, table_name: &str) -> Result<Vec<DependentView>, ExecutorError> {
+        let mut dependents = Vec::new();
+        for name in self.repository.get_view_names().await? {
+            let view = self.repository.get_view(&name).await?;
+            if !view.incremental {
+                continue;
+            }
+
+            let query = self.view_query(&view)?;
+            if query.table_name == table_name {
+                let query = self.resolve_functions(&query)?;
+                dependents.push((view, query));
+            }
+        }
+
+        Ok(dependents)
+    }
+
+    /// 変更された行にRETURNING句を適用する（RETURNING句がなければNone）
+    async fn returning(
+        &self,
+        table_name: &str,
+        items: Option<&[SelectItem]>,
+        rows: Vec<Row>
+    ) -> Result<Option<ResultSet>, ExecutorError> {
+        let Some(items) = items else {
+            return Ok(None);
+        };
+
+        let table = self.repository.get_table(table_name).await?;
+        let stmt = self.resolve_functions(&SelectStatement {
+            table_name: table_name.to_string(),
+            table_alias: None,
+            table_function: None,
+            distinct: None,
+            projection: items.to_vec(),
+            filter: None,
+            group_by: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            with: Vec::new(),
+            set_operation: None,
+        })?;
+
+        project(&stmt, ResultSet { columns: table.columns, rows }).map(Some)
+    }
+
+    /// ベーステーブルから削除された行と追加された行をマテリアライズドビューに反映する
+    async fn apply_view_deltas(
+        &self,
+        dependents: &[DependentView],
+        removed: &[Row],
+        added: &[Row]
+    ) -> Result<(), ExecutorError> {
+        if removed.is_empty() && added.is_empty() {
+            return Ok(());
+        }
+
+        for (view, query) in dependents {
+            let schema = self.repository.get_table(&query.table_name).await?;
+            // 条件の定数はSELECTと同じくカラムの型に変換し、カラムの照合順序で比較する
+            let filter = match &query.filter {
+                Some(filter) => Some(self.coerce_filter(filter, &schema.columns)?),
+                None => None,
+            };
+            let collated = filter.as_ref().map(|f| f.with_collations(&schema.columns));
+            let delta = |rows: &[Row]| ResultSet {
+                columns: schema.columns.clone(),
+                rows: rows.iter()
+                    .filter(|row| collated.as_ref().is_none_or(|f| f.matches(*row)))
+                    .cloned()
+                    .collect(),
+            };
+
+            if !query.is_aggregate() {
+                // 射影のみのビューは行単位で追加・削除できる
+                let to_remove = rename_columns(project(query, delta(removed))?, view)?;
+                let to_add = rename_columns(project(query, delta(added))?, view)?;
+                self.repository.apply_view_delta(&view.name, &to_remove.rows, &to_add.rows).await?;
+                continue;
+            }
+
+            if query.group_by.is_empty() {
+                // グループ化なしの集約は結果が1行なので全体を再計算する
+                self.refresh_materialized_view(&view.name).await?;
+                continue;
+            }
+
+            // 影響を受けたグループだけを再計算する（ビューのGROUP BYのカラムは元のカラムの照合順序を引き継ぐ）
+            let collations = group_collations(&query.group_by, &schema.columns);
+            let mut affected = HashSet::new();
+            for row in delta(removed).rows.iter().chain(delta(added).rows.iter()) {
+                affected.insert(group_key(&query.group_by, &collations, row));
+            }
+            if affected.is_empty() {
+                continue;
+            }
+
+            let mut base = self.repository.select(&query.table_name, &[], filter.as_ref()).await?;
+            base.rows.retain(|row| affected.contains(&group_key(&query.group_by, &collations, row)));
+            let regrouped = rename_columns(project(query, base)?, view)?;
+
+            // 影響を受けたグループの古い行を新しい行に置き換える
+            let output_columns = group_output_columns(view, query)?;
+            let mut stale = self.repository.select(&view.name, &[], None).await?.rows;
+            stale.retain(|row| affected.contains(&group_key(&output_columns, &collations, row)));
+            self.repository.apply_view_delta(&view.name, &stale, &regrouped.rows).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 入力の結果セットにSELECT句を適用する
+fn project(stmt: &SelectStatement, source: ResultSet) -> Result<ResultSet, ExecutorError> {
+    if stmt.is_aggregate() {
+        let (stmt, grouped) = group_aggregates(stmt, source)?;
+        return project(&stmt, grouped);
+    }
+
+    let mut columns = Vec::new();
+    let mut exprs = Vec::new();
+    for item in &stmt.projection {
+        match item {
+            SelectItem::Wildcard => {
+                for column in source.columns.iter().filter(|c| !c.name.starts_with(HIDDEN_COLUMN_PREFIX)) {
+                    columns.push(column.clone());
+                    exprs.push(Expression::Column(column.name.clone()));
+                }
+            },
+            SelectItem::Expression { expr, .. } => {
+                columns.push(output_column(stmt, item, expr, &source.columns)?);
+                // カラムの照合順序に従って比較する（結果カラムの名前は元の式から求める）
+                exprs.push(expr.with_collations(&source.columns));
+            },
+        }
+    }
+
+    let mut result = ResultSet::new(columns);
+    for row in &source.rows {
+        let mut projected = Row::new();
+        for (column, expr) in result.columns.iter().zip(&exprs) {
+            let value = match (expr.evaluate(row)?, &column.data_type) {
+                // CASE の分岐などで整数と浮動小数点数（10進数）や日付と日時が混ざる場合は結果カラムの型に揃える
+                (Value::Integer(i), DataType::Float) => Value::Float(i as f64),
+                (Value::Integer(i), DataType::Decimal(..)) => Value::Decimal(i.into()),
+                (Value::Decimal(d), DataType::Float) => Value::Float(d.to_f64().unwrap_or(f64::NAN)),
+                (Value::Date(d), DataType::Timestamp) => Value::Timestamp(d.and_time(NaiveTime::MIN)),
+                (value @ (Value::Date(_) | Value::Timestamp(_)), DataType::TimestampTz) => value.cast_to(&DataType::TimestampTz)
+                    .map_err(|e| ExecutorError::Execution(e.to_string()))?,
+                (value, _) => value,
+            };
+            projected.set(column.name.clone(), value);
+        }
+        result.rows.push(projected);
+    }
+
+    sort_and_distinct(stmt, &mut result, Some(&source))?;
+    Ok(result)
+}
+
+/// 日時の値を書き込むカラムの型に合わせる
+///
+/// タイムゾーン付きの日時のカラムでは、タイムゾーンを持たない日時や文字列を `time_zone` の日時とみなす。
+/// タイムゾーンを持たない日時のカラムでは、タイムゾーン付きの日時を `time_zone` での日時に直す。
+fn localize(value: Value, data_type: &DataType, time_zone: FixedOffset) -> Result<Value, ExecutorError> {
+    let at_zone = |dt: NaiveDateTime| time_zone.from_local_datetime(&dt).single()
+        .map(|dt| Value::TimestampTz(dt.with_timezone(&Utc)))
+        .ok_or_else(|| ExecutorError::Execution(format!("Invalid timestamp {} in time zone {}", dt, time_zone)));
+
+    match (value, data_type) {
+        (Value::Text(s), DataType::TimestampTz) => Value::parse_timestamptz(&s, time_zone)
+            .map(Value::TimestampTz)
+            .ok_or_else(|| ExecutorError::Execution(format!("Cannot convert {} to TIMESTAMPTZ", s))),
+        (Value::Timestamp(dt), DataType::TimestampTz) => at_zone(dt),
+        (Value::Date(d), DataType::TimestampTz) => at_zone(d.and_time(NaiveTime::MIN)),
+        (Value::TimestampTz(dt), DataType::Timestamp) => Ok(Value::Timestamp(dt.with_timezone(&time_zone).naive_local())),
+        (value, _) => Ok(value),
+    }
+}
+
+/// 条件でカラムと比較する文字列の定数を、カラムの型の値に変換する（文字列のまま比較する型ではNone）
+///
+/// WHERE d = '2024-02-29' のように日時や時間間隔、UUID、バイト列、配列のカラムを文字列の定数と比較できるようにする。
+/// タイムゾーンを持たない文字列は、INSERT と同じくセッションのタイムゾーンの日時とみなす。
+/// CHAR(n) のカラムと比較する文字列は、格納された値と同じく空白で埋めてn文字にする。
+fn convert_literal(text: &str, column: &Column, time_zone: FixedOffset) -> Result<Option<Value>, ExpressionError> {
+    let data_type = &column.data_type;
+    match data_type {
+        DataType::Text => Ok(column.type_limit.and_then(|limit| limit.blank_pad(text)).map(Value::Text)),
+        DataType::TimestampTz => Value::parse_timestamptz(text, time_zone)
+            .map(|dt| Some(Value::TimestampTz(dt)))
+            .ok_or_else(|| ExpressionError::InvalidOperation(format!("Cannot convert {} to TIMESTAMPTZ", text))),
+        DataType::Date | DataType::Timestamp | DataType::Time | DataType::Interval | DataType::Uuid | DataType::Blob |
+        DataType::Array(_) =>
+            Value::Text(text.to_string())
+            .cast_to(data_type)
+            .map(Some)
+            .map_err(|e| ExpressionError::InvalidOperation(e.to_string())),
+        _ => Ok(None),
+    }
+}
+
+/// テーブル関数が生成する行を求める
+fn table_function_rows(function: &TableFunction) -> Result<ResultSet, ExecutorError> {
+    match function {
+        TableFunction::Unnest { array, column } => {
+            let data_type = array.data_type(&[])?;
+            let element_type = match data_type {
+                DataType::Array(element) => *element,
+                DataType::Null => DataType::Null,
+                other => return Err(ExecutorError::Execution(
+                    format!("unnest() requires an array, got {}", other))),
+            };
+
+            // NULLの配列は行を生成しない
+            let items = match array.evaluate(&Row::new())? {
+                Value::Array(items) => items,
+                _ => Vec::new(),
+            };
+            let mut result = ResultSet::new(vec![Column::new(column, element_type)]);
+            for item in items {
+                let mut row = Row::new();
+                row.set(column.clone(), item);
+                result.rows.push(row);
+            }
+            Ok(result)
+        },
+        TableFunction::SingleRow => {
+            let mut result = ResultSet::new(Vec::new());
+            result.rows.push(Row::new());
+            Ok(result)
+        },
+    }
+}
+
+/// ORDER BY と DISTINCT を適用する
+///
+/// 射影前の行が渡された場合は、結果に含まれないカラムでも並べ替えや DISTINCT ON に使える
+/// （結果カラムの名前が優先される）。DISTINCT ON は並べ替えた後で最初に現れる行を残す。
+fn sort_and_distinct(stmt: &SelectStatement, result: &mut ResultSet, source: Option<&ResultSet>) -> Result<(), ExecutorError> {
+    if stmt.order_by.is_empty() && stmt.distinct.is_none() {
+        return Ok(());
+    }
+
+    let source_columns = source.map(|source| &source.columns[..]);
+    let order_exprs = output_exprs(
+        stmt, "ORDER BY", stmt.order_by.iter().map(|item| &item.expr), &result.columns, source_columns)?;
+    let distinct_exprs = match &stmt.distinct {
+        Some(Distinct::On(exprs)) => Some(output_exprs(stmt, "DISTINCT ON", exprs.iter(), &result.columns, source_columns)?),
+        Some(Distinct::Rows) => Some(result.columns.iter().map(|c| Expression::Column(c.name.clone())).collect()),
+        None => None,
+    };
+    // 照合順序が指定された項目は、その照合順序で等しい値を重複とみなす
+    let distinct_collations: Vec<_> = distinct_exprs.iter().flatten().map(|expr| {
+        let mut expr = expr.with_collations(&result.columns);
+        if let Some(source_columns) = source_columns {
+            expr = expr.with_collations(source_columns);
+        }
+        expr.collation()
+    }).collect();
+
+    // DISTINCT だけの場合は結果の行だけで判定できる
+    let source = source.filter(|_| !order_exprs.is_empty() || matches!(stmt.distinct, Some(Distinct::On(_))));
+
+    let mut keyed = Vec::with_capacity(result.rows.len());
+    for (i, row) in std::mem::take(&mut result.rows).into_iter().enumerate() {
+        let combined = match source {
+            Some(source) => {
+                let mut combined = source.rows[i].clone();
+                combined.values.extend(row.values.iter().map(|(name, value)| (name.clone(), value.clone())));
+                Some(combined)
+            },
+            None => None,
+        };
+        let evaluate = |exprs: &[Expression]| exprs.iter()
+            .map(|expr| expr.evaluate(combined.as_ref().unwrap_or(&row)))
+            .collect::<Result<Vec<_>, _>>();
+
+        let sort_keys = evaluate(&order_exprs)?;
+        let distinct_key = distinct_exprs.as_deref().map(evaluate).transpose()?
+            .map(|values| ValueKey::collated(&values, &distinct_collations));
+        keyed.push((sort_keys, distinct_key, row));
+    }
+
+    // 照合順序を明示していない項目は、参照するカラムの照合順序で比較する
+    let order_by: Vec<OrderByExpr> = stmt.order_by.iter().zip(&order_exprs).map(|(item, expr)| {
+        if item.expr.collation().is_some() {
+            return item.clone();
+        }
+        let mut expr = expr.with_collations(&result.columns);
+        if let Some(source_columns) = source_columns {
+            expr = expr.with_collations(source_columns);
+        }
+        OrderByExpr { expr, ..item.clone() }
+    }).collect();
+    keyed.sort_by(|(a, ..), (b, ..)| compare_sort_keys(&order_by, a, b));
+
+    // 重複の判定は値のハッシュで行う（浮動小数点数やタイムスタンプも同じ値なら重複とみなす）
+    let mut seen = HashSet::new();
+    result.rows = keyed.into_iter()
+        .filter(|(_, distinct_key, _)| distinct_key.as_ref().is_none_or(|key| seen.insert(key.clone())))
+        .map(|(.., row)| row)
+        .collect();
+    Ok(())
+}
+
+/// ORDER BY などの式を、結果の行（と射影前の行）に対して評価できる式に変換する
+fn output_exprs<'a>(
+    stmt: &SelectStatement,
+    clause: &str,
+    exprs: impl Iterator<Item = &'a Expression>,
+    columns: &[Column],
+    source_columns: Option<&[Column]>
+) -> Result<Vec<Expression>, ExecutorError> {
+    let selected: Vec<(&Expression, String)> = stmt.projection.iter().filter_map(|item| match item {
+        SelectItem::Expression { expr, .. } => Some((expr, item.output_name().unwrap_or_default())),
+        SelectItem::Wildcard => None,
+    }).collect();
+
+    exprs.map(|expr| {
+        // ORDER BY 1 は結果の1番目のカラム
+        if let Expression::Literal(Value::Integer(position)) = expr {
+            return usize::try_from(*position).ok()
+                .and_then(|position| columns.get(position.checked_sub(1)?))
+                .map(|column| Expression::Column(column.name.clone()))
+                .ok_or_else(|| ExecutorError::Execution(format!(
+                    "{} position {} is not in select list", clause, position)));
+        }
+
+        // SELECT句と同じ式は結果カラムを参照する
+        let expr = expr.transform(&mut |node| selected.iter()
+            .find(|(expr, _)| *expr == node)
+            .map(|(_, name)| Expression::Column(name.clone())));
+
+        for name in expr.referenced_columns() {
+            let found = resolve_column(columns, name).is_some()
+                || source_columns.is_some_and(|source| resolve_column(source, name).is_some());
+            // 集合演算の結果は特定のテーブルに属さない
+            if !found && stmt.set_operation.is_some() {
+                return Err(ExpressionError::ColumnNotFound(name.to_string()).into());
+            }
+            if !found {
+                return Err(column_not_found(stmt, name));
+            }
+        }
+
+        Ok(expr)
+    }).collect()
+}
+
+/// 集約を伴うクエリをグループ化し、集約関数を結果の隠しカラムの参照に置き換えた文を返す
+///
+/// 返された文をグループごとの行に対して射影することで、集約関数やGROUP BYのカラムを含む
+/// 任意の式（SUM(x) * 2 や UPPER(dept) など）を評価できる。
+pub(super) fn group_aggregates(stmt: &SelectStatement, source: ResultSet) -> Result<(SelectStatement, ResultSet), ExecutorError> {
+    let mut aggregates: Vec<Expression> = Vec::new();
+    let mut replace = |expr: &Expression| expr.transform(&mut |node| match node {
+        Expression::Aggregate { .. } | Expression::UserAggregate { .. } => {
+            let position = aggregates.iter().position(|a| a == node).unwrap_or_else(|| {
+                aggregates.push(node.clone());
+                aggregates.len() - 1
+            });
+            Some(Expression::Column(format!("{}aggregate{}", HIDDEN_COLUMN_PREFIX, position)))
+        },
+        _ => None,
+    });
+
+    let mut projection = Vec::new();
+    for item in &stmt.projection {
+        let SelectItem::Expression { expr, .. } = item else {
+            return Err(ExecutorError::Execution(
+                "'*' cannot be used in an aggregate query".to_string()));
+        };
+        projection.push(SelectItem::Expression { expr: replace(expr), alias: item.output_name() });
+    }
+    let order_by: Vec<OrderByExpr> = stmt.order_by.iter().map(|item| OrderByExpr {
+        expr: replace(&item.expr),
+        ..item.clone()
+    }).collect();
+
+    // 集約関数の外で参照できるのはGROUP BYのカラム（ORDER BYでは結果カラムの名前も）だけ
+    let output_names: Vec<String> = projection.iter().filter_map(SelectItem::output_name).collect();
+    let projected = projection.iter().filter_map(|item| match item {
+        SelectItem::Expression { expr, .. } => Some(expr.referenced_columns()),
+        SelectItem::Wildcard => None,
+    }).flatten();
+    let ordered = order_by.iter()
+        .flat_map(|item| item.expr.referenced_columns())
+        .filter(|name| !output_names.iter().any(|output| output == name));
+    for name in projected.chain(ordered) {
+        if !name.starts_with(HIDDEN_COLUMN_PREFIX) && !stmt.group_by.iter().any(|g| g == name) {
+            return Err(ExecutorError::Execution(format!(
+                "Column {} must appear in the GROUP BY clause or be used in an aggregate function", name)));
+        }
+    }
+
+    let grouping = SelectStatement {
+        projection: stmt.group_by.iter()
+            .map(|name| SelectItem::Expression { expr: Expression::Column(name.clone()), alias: None })
+            .chain(aggregates.iter().enumerate().map(|(i, expr)| SelectItem::Expression {
+                expr: expr.clone(),
+                alias: Some(format!("{}aggregate{}", HIDDEN_COLUMN_PREFIX, i)),
+            }))
+            .collect(),
+        order_by: Vec::new(),
+        limit: None,
+        ..stmt.clone()
+    };
+    let grouped = aggregate(&grouping, source)?;
+
+    Ok((SelectStatement { projection, order_by, group_by: Vec::new(), ..stmt.clone() }, grouped))
+}
+
+/// GROUP BY と集約関数を適用する
+fn aggregate(stmt: &SelectStatement, source: ResultSet) -> Result<ResultSet, ExecutorError> {
+    for name in &stmt.group_by {
+        if !source.columns.iter().any(|c| &c.name == name) {
+            return Err(column_not_found(stmt, name));
+        }
+    }
+
+    let mut columns = Vec::new();
+    let mut exprs = Vec::new();
+    for item in &stmt.projection {
+        let SelectItem::Expression { expr, .. } = item else {
+            return Err(ExecutorError::Execution(
+                "'*' cannot be used in an aggregate query".to_string()));
+        };
+        if let Expression::Column(name) = expr {
+            if !stmt.group_by.contains(name) {
+                return Err(ExecutorError::Execution(format!(
+                    "Column {} must appear in the GROUP BY clause or be used in an aggregate function", name)));
+            }
+        }
+        columns.push(output_column(stmt, item, expr, &source.columns)?);
+        exprs.push(expr);
+    }
+
+    // グループキーごとのアキュムレーター
+    let new_accumulators = || exprs.iter().map(|expr| match expr {
+        Expression::Aggregate { function, .. } => Some(Accumulator::new(*function)),
+        Expression::UserAggregate { function, .. } => Some(Accumulator::user(function)),
+        _ => None,
+    }).collect::<Vec<_>>();
+
+    // 照合順序のあるカラムは、その照合順序で等しい値を同じグループにする
+    let collations = &group_collations(&stmt.group_by, &source.columns);
+
+    // 行が多い場合は一定の行数ごとに並行して部分的に集約し、途中状態を入力の順にまとめる
+    let chunks: Vec<&[Row]> = source.rows.chunks(PARTIAL_AGGREGATE_ROWS).collect();
+    let mut groups = if chunks.len() <= 1 {
+        accumulate(&stmt.group_by, collations, &exprs, &source.rows, &new_accumulators)?
+    } else {
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get()).min(chunks.len());
+        let mut partials = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers).map(|worker| {
+                let (chunks, exprs, new_accumulators) = (&chunks, &exprs, &new_accumulators);
+                scope.spawn(move || {
+                    chunks.iter().enumerate().skip(worker).step_by(workers)
+                        .map(|(i, chunk)| Ok((i, accumulate(&stmt.group_by, collations, exprs, chunk, new_accumulators)?)))
+                        .collect::<Result<Vec<_>, ExecutorError>>()
+                })
+            }).collect();
+            handles.into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect::<Result<Vec<_>, _>>()
+        })?.into_iter().flatten().collect::<Vec<_>>();
+        partials.sort_by_key(|(i, _)| *i);
+        merge_groups(collations, partials.into_iter().map(|(_, groups)| groups))?
+    };
+
+    // GROUP BY のない集約は入力が空でも1行を返す
+    if groups.is_empty() && stmt.group_by.is_empty() {
+        groups.push((ValueKey(Vec::new()), new_accumulators()));
+    }
+
+    let mut result = ResultSet::new(columns);
+    for (key, accumulators) in &groups {
+        let mut row = Row::new();
+        for ((column, expr), accumulator) in result.columns.iter().zip(&exprs).zip(accumulators) {
+            let value = match (expr, accumulator) {
+                (_, Some(accumulator)) => accumulator.finish()?,
+                (Expression::Column(name), None) => {
+                    let position = stmt.group_by.iter().position(|g| g == name).unwrap_or_default();
+                    key.0[position].clone()
+                },
+                (expr, None) => expr.evaluate(&Row::new())?,
+            };
+            row.set(column.name.clone(), value);
+        }
+        result.rows.push(row);
+    }
+
+    Ok(result)
+}
+
+/// 行をグループキーごとのアキュムレーターに取り込む
+///
+/// グループは最初に現れた順に並べ、キーにはそのグループで最初に現れた行の値を使う。
+fn accumulate(
+    group_by: &[String],
+    collations: &[Option<Collation>],
+    exprs: &[&Expression],
+    rows: &[Row],
+    new_accumulators: &(dyn Fn() -> Vec<Option<Accumulator>> + Sync)
+) -> Result<Vec<AccumulatedGroup>, ExecutorError> {
+    let mut group_index: HashMap<ValueKey, usize> = HashMap::new();
+    let mut groups: Vec<AccumulatedGroup> = Vec::new();
+    for row in rows {
+        let index = *group_index.entry(group_key(group_by, collations, row)).or_insert_with(|| {
+            groups.push((group_key(group_by, &[], row), new_accumulators()));
+            groups.len() - 1
+        });
+
+        for (expr, accumulator) in exprs.iter().zip(groups[index].1.iter_mut()) {
+            match (expr, accumulator) {
+                (Expression::Aggregate { argument, .. }, Some(accumulator)) => {
+                    let value = match argument {
+                        Some(arg) => arg.evaluate(row)?,
+                        // COUNT(*) はすべての行を数える
+                        None => Value::Boolean(true),
+                    };
+                    accumulator.update(&value)?;
+                },
+                (Expression::UserAggregate { args, .. }, Some(accumulator)) => {
+                    let values = args.iter()
+                        .map(|arg| arg.evaluate(row))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    accumulator.update_args(&values)?;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+/// 部分的に集約したグループを順にまとめる（同じキーのグループは途中状態を合わせる）
+fn merge_groups(
+    collations: &[Option<Collation>],
+    partials: impl Iterator<Item = Vec<AccumulatedGroup>>
+) -> Result<Vec<AccumulatedGroup>, ExecutorError> {
+    let mut group_index: HashMap<ValueKey, usize> = HashMap::new();
+    let mut groups: Vec<AccumulatedGroup> = Vec::new();
+    for (key, accumulators) in partials.flatten() {
+        let collated = ValueKey::collated(&key.0, collations);
+        let Some(&index) = group_index.get(&collated) else {
+            group_index.insert(collated, groups.len());
+            groups.push((key, accumulators));
+            continue;
+        };
+        for (merged, partial) in groups[index].1.iter_mut().zip(accumulators) {
+            if let (Some(merged), Some(partial)) = (merged, partial) {
+                merged.merge(partial)?;
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+/// FROM句のテーブルにカラムが見つからないことを表すエラー
+fn column_not_found(stmt: &SelectStatement, name: &str) -> ExecutorError {
+    match stmt.table_function {
+        Some(TableFunction::SingleRow) => ExecutorError::Execution(
+            format!("Column {} not found (the query has no FROM clause)", name)),
+        _ => RepositoryError::ColumnNotFound(name.to_string(), stmt.table_name.clone()).into(),
+    }
+}
+
+/// SELECT句の項目に対応する結果カラムを求める
+fn output_column(
+    stmt: &SelectStatement,
+    item: &SelectItem,
+    expr: &Expression,
+    source_columns: &[Column]
+) -> Result<Column, ExecutorError> {
+    for name in expr.referenced_columns() {
+        if resolve_column(source_columns, name).is_none() {
+            return Err(column_not_found(stmt, name));
+        }
+    }
+
+    let name = item.output_name().unwrap_or_default();
+    match expr {
+        // カラム参照は元のカラム定義（制約を含む）を引き継ぐ
+        Expression::Column(source_name) => {
+            let mut column = resolve_column(source_columns, source_name)
+                .cloned()
+                .ok_or_else(|| ExpressionError::ColumnNotFound(source_name.clone()))?;
+            column.name = name;
+            Ok(column)
+        },
+        _ => {
+            let column = Column::new(name, expr.data_type(source_columns)?);
+            Ok(match expr.collation() {
+                Some(collation) => column.with_collation(collation),
+                None => column,
+            })
+        },
+    }
+}
+
+/// 行からGROUP BYのキーを取り出す（照合順序のあるカラムは比較に使う形にし、照合順序を渡さなければ元の値のまま）
+fn group_key(group_by: &[String], collations: &[Option<Collation>], row: &Row) -> ValueKey {
+    let values: Vec<_> = group_by.iter()
+        .map(|name| row.get(name).cloned().unwrap_or(Value::Null))
+        .collect();
+    ValueKey::collated(&values, collations)
+}
+
+/// GROUP BYの各カラムの照合順序を求める
+fn group_collations(group_by: &[String], columns: &[Column]) -> Vec<Option<Collation>> {
+    group_by.iter()
+        .map(|name| resolve_column(columns, name).and_then(|column| column.collation))
+        .collect()
+}
+
+/// GROUP BYの各カラムに対応するビューの結果カラム名を求める
+fn group_output_columns(view: &View, query: &SelectStatement) -> Result<Vec<String>, ExecutorError> {
+    query.group_by.iter().map(|name| {
+        let position = query.projection.iter().position(|item| {
+            matches!(item, SelectItem::Expression { expr: Expression::Column(c), .. } if c == name)
+        }).ok_or_else(|| ExecutorError::Execution(format!(
+            "Materialized view {} cannot be maintained incrementally: GROUP BY column {} must be selected",
+            view.name, name)))?;
+
+        Ok(match view.columns.get(position) {
+            Some(alias) => alias.clone(),
+            None => query.projection[position].output_name().unwrap_or_default(),
+        })
+    }).collect()
+}
+
+/// ビューで指定された別名に結果カラムの名前を置き換える
+fn rename_columns(result: ResultSet, view: &View) -> Result<ResultSet, ExecutorError> {
+    if view.columns.is_empty() {
+        return Ok(result);
+    }
+
+    if view.columns.len() != result.columns.len() {
+        return Err(ExecutorError::Execution(format!(
+            "View {} declares {} columns but its query returns {}",
+            view.name, view.columns.len(), result.columns.len())));
+    }
+
+    Ok(rename_result(result, &view.columns))
+}
+
+/// 結果カラムの名前を先頭から順に置き換える
+fn rename_result(result: ResultSet, names: &[String]) -> ResultSet {
+    let mut columns = result.columns;
+    let old_names: Vec<String> = columns.iter().map(|c| c.name.clone()).collect();
+    for (column, alias) in columns.iter_mut().zip(names) {
+        column.name = alias.clone();
+    }
+
+    let mut renamed = ResultSet::new(columns);
+    for row in result.rows {
+        let mut new_row = Row::new();
+        for (old_name, alias) in old_names.iter().zip(names) {
+            if let Some(value) = row.get(old_name) {
+                new_row.set(alias.clone(), value.clone());
+            }
+        }
+        renamed.add_row(new_row);
+    }
+
+    renamed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{error, exec, executor, int, query, text};
+    use chrono::NaiveDate;
+    use super::*;
+
+    async fn orders() -> QueryExecutor {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE orders (id INTEGER PRIMARY KEY, customer TEXT, amount INTEGER);
+            INSERT INTO orders (id, customer, amount) VALUES (1, 'alice', 10), (2, 'bob', 20), (3, 'alice', 30)
+        ").await;
+        executor
+    }
+
+    #[tokio::test]
+    async fn views_are_expanded_with_their_filter_and_aliases() {
+        let executor = orders().await;
+        exec(&executor, "INSERT INTO orders (id, customer, amount) VALUES (4, 'carol', NULL)").await;
+        exec(&executor, "CREATE VIEW big (id, who) AS SELECT id, customer FROM orders WHERE amount > 10").await;
+
+        let rows = query(&executor, "SELECT who FROM big WHERE id > 2").await;
+        assert_eq!(rows, vec![vec![text("alice")]]);
+        // ビューは問い合わせのたびに元のテーブルから求める
+        exec(&executor, "UPDATE orders SET amount = 50 WHERE id = 4").await;
+        let rows = query(&executor, "SELECT who FROM big WHERE id > 2").await;
+        assert_eq!(rows, vec![vec![text("alice")], vec![text("carol")]]);
+
+        exec(&executor, "CREATE OR REPLACE VIEW big AS SELECT id FROM orders WHERE amount < 20").await;
+        let rows = query(&executor, "SELECT * FROM big").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+    }
+
+    #[tokio::test]
+    async fn view_definition_errors() {
+        let executor = orders().await;
+        exec(&executor, "CREATE VIEW v AS SELECT id FROM orders").await;
+
+        let message = error(&executor, "CREATE VIEW v AS SELECT id FROM orders").await;
+        assert!(message.contains("View v already exists"), "{}", message);
+        let message = error(&executor, "INSERT INTO v VALUES (9)").await;
+        assert!(message.contains("not found"), "{}", message);
+
+        exec(&executor, "DROP VIEW v").await;
+        let message = error(&executor, "DROP VIEW v").await;
+        assert!(message.contains("View v not found"), "{}", message);
+        exec(&executor, "DROP VIEW IF EXISTS v").await;
+        let message = error(&executor, "SELECT * FROM v").await;
+        assert!(message.contains("not found"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn materialized_view_keeps_snapshot_until_refresh() {
+        let executor = orders().await;
+        exec(&executor, "CREATE MATERIALIZED VIEW totals AS SELECT customer, SUM(amount) AS total FROM orders GROUP BY customer").await;
+        exec(&executor, "INSERT INTO orders (id, customer, amount) VALUES (4, 'bob', 5)").await;
+
+        let rows = query(&executor, "SELECT customer, total FROM totals ORDER BY customer").await;
+        assert_eq!(rows, vec![vec![text("alice"), int(40)], vec![text("bob"), int(20)]]);
+
+        exec(&executor, "REFRESH MATERIALIZED VIEW totals").await;
+        let rows = query(&executor, "SELECT customer, total FROM totals ORDER BY customer").await;
+        assert_eq!(rows, vec![vec![text("alice"), int(40)], vec![text("bob"), int(25)]]);
+    }
+
+    #[tokio::test]
+    async fn incremental_materialized_view_follows_changes() {
+        let executor = orders().await;
+        exec(&executor, "
+            CREATE MATERIALIZED VIEW totals WITH (incremental = true) AS
+                SELECT customer, SUM(amount) AS total FROM orders GROUP BY customer;
+            CREATE MATERIALIZED VIEW big WITH (incremental = true) AS
+                SELECT id, customer FROM orders WHERE amount >= 20
+        ").await;
+
+        exec(&executor, "
+            INSERT INTO orders (id, customer, amount) VALUES (4, NULL, 7), (5, 'carol', 50);
+            UPDATE orders SET amount = 1 WHERE id = 2;
+            DELETE FROM orders WHERE id = 1
+        ").await;
+
+        for (customer, total) in [("alice", 30), ("bob", 1), ("carol", 50)] {
+            let sql = format!("SELECT total FROM totals WHERE customer = '{}'", customer);
+            assert_eq!(query(&executor, &sql).await, vec![vec![int(total)]]);
+        }
+        let rows = query(&executor, "SELECT customer, total FROM totals").await;
+        assert!(rows.contains(&vec![Value::Null, int(7)]), "{:?}", rows);
+        let rows = query(&executor, "SELECT id FROM big ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(3)], vec![int(5)]]);
+
+        // NULLのグループも差分で置き換えられる
+        exec(&executor, "INSERT INTO orders (id, customer, amount) VALUES (6, NULL, 3)").await;
+        let rows = query(&executor, "SELECT customer, total FROM totals").await;
+        assert!(rows.contains(&vec![Value::Null, int(10)]), "{:?}", rows);
+    }
+
+    #[tokio::test]
+    async fn incremental_view_rejects_unsupported_queries() {
+        let executor = orders().await;
+        let message = error(&executor,
+            "CREATE MATERIALIZED VIEW v WITH (incremental = true) AS SELECT customer FROM orders LIMIT 1").await;
+        assert!(message.contains("cannot be maintained incrementally"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn materialized_view_cannot_be_modified_directly() {
+        let executor = orders().await;
+        exec(&executor, "CREATE MATERIALIZED VIEW recent AS SELECT id, amount FROM orders").await;
+
+        for sql in [
+            "INSERT INTO recent VALUES (9, 9)",
+            "INSERT INTO recent VALUES (1, 9) ON CONFLICT DO NOTHING",
+            "UPDATE recent SET amount = 0",
+            "DELETE FROM recent",
+        ] {
+            let message = error(&executor, sql).await;
+            assert!(message.contains("Cannot modify materialized view recent"), "{}: {}", sql, message);
+        }
+        let rows = query(&executor, "SELECT COUNT(*) FROM recent").await;
+        assert_eq!(rows, vec![vec![int(3)]]);
+
+        let message = error(&executor, "DROP TABLE recent").await;
+        assert!(message.contains("use DROP MATERIALIZED VIEW"), "{}", message);
+
+        let message = error(&executor, "DROP VIEW recent").await;
+        assert!(message.contains("use DROP MATERIALIZED VIEW"), "{}", message);
+
+        exec(&executor, "DROP MATERIALIZED VIEW recent").await;
+        let message = error(&executor, "SELECT * FROM recent").await;
+        assert!(message.contains("not found"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn create_table_as_select_takes_columns_from_the_query() {
+        let executor = orders().await;
+        exec(&executor, "INSERT INTO orders (id, customer, amount) VALUES (4, 'carol', NULL)").await;
+        exec(&executor, "CREATE TABLE totals AS SELECT customer, SUM(amount) AS total FROM orders GROUP BY customer").await;
+
+        let rows = query(&executor, "SELECT customer, total FROM totals ORDER BY customer").await;
+        assert_eq!(rows, vec![
+            vec![text("alice"), int(40)],
+            vec![text("bob"), int(20)],
+            vec![text("carol"), Value::Null],
+        ]);
+
+        exec(&executor, "CREATE TABLE empty AS SELECT id FROM orders WHERE id > 99").await;
+        let rows = query(&executor, "SELECT COUNT(*) FROM empty").await;
+        assert_eq!(rows, vec![vec![int(0)]]);
+
+        let message = error(&executor, "CREATE TABLE totals AS SELECT id FROM orders").await;
+        assert!(message.contains("Table totals already exists"), "{}", message);
+        exec(&executor, "CREATE TABLE IF NOT EXISTS totals AS SELECT id FROM orders").await;
+    }
+
+    #[tokio::test]
+    async fn insert_select_checks_columns_and_constraints() {
+        let executor = orders().await;
+        exec(&executor, "INSERT INTO orders (id, customer, amount) VALUES (4, 'carol', NULL)").await;
+        exec(&executor, "CREATE TABLE archive (id INTEGER PRIMARY KEY, amount INTEGER NOT NULL)").await;
+
+        exec(&executor, "INSERT INTO archive (id, amount) SELECT id + 10, amount FROM orders WHERE amount > 10").await;
+        let rows = query(&executor, "SELECT id, amount FROM archive ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(12), int(20)], vec![int(13), int(30)]]);
+
+        let message = error(&executor, "INSERT INTO archive (id, bogus) VALUES (9, 9)").await;
+        assert!(message.contains("Column bogus not found in table archive"), "{}", message);
+        let message = error(&executor, "INSERT INTO archive SELECT id FROM orders").await;
+        assert!(message.contains("more target columns than expressions"), "{}", message);
+        let message = error(&executor, "INSERT INTO archive SELECT id, amount, customer FROM orders").await;
+        assert!(message.contains("more expressions than target columns"), "{}", message);
+        let message = error(&executor, "INSERT INTO archive SELECT id, amount FROM orders WHERE id = 4").await;
+        assert!(message.contains("NOT NULL"), "{}", message);
+        let rows = query(&executor, "SELECT COUNT(*) FROM archive").await;
+        assert_eq!(rows, vec![vec![int(2)]]);
+    }
+
+    #[tokio::test]
+    async fn update_checks_constraints_after_all_rows_change() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE);
+            INSERT INTO users VALUES (1, 'a'), (2, 'b'), (3, 'c')
+        ").await;
+
+        let message = error(&executor, "UPDATE users SET id = 1 WHERE id = 2").await;
+        assert!(message.contains("PRIMARY KEY constraint violation"), "{}", message);
+        let message = error(&executor, "UPDATE users SET email = 'a' WHERE id = 3").await;
+        assert!(message.contains("UNIQUE constraint violation for column email"), "{}", message);
+        // 複数の行が同じ値になる場合も違反になり、どの行も更新しない
+        let message = error(&executor, "UPDATE users SET email = 'x' WHERE id >= 2").await;
+        assert!(message.contains("UNIQUE constraint violation for column email"), "{}", message);
+        let rows = query(&executor, "SELECT id, email FROM users ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(1), text("a")], vec![int(2), text("b")], vec![int(3), text("c")]]);
+
+        // 更新の途中で重複しても、更新後に一意であればよい
+        exec(&executor, "UPDATE users SET id = id + 1").await;
+        let rows = query(&executor, "SELECT id FROM users ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(2)], vec![int(3)], vec![int(4)]]);
+    }
+
+    #[tokio::test]
+    async fn on_conflict_updates_or_skips_existing_rows() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE stock (sku TEXT PRIMARY KEY, qty INTEGER NOT NULL, note TEXT)").await;
+        exec(&executor, "INSERT INTO stock VALUES ('a', 1, NULL), ('b', 2, 'x')").await;
+
+        let result = exec(&executor, "INSERT INTO stock VALUES ('a', 5, 'new'), ('c', 3, NULL) \
+            ON CONFLICT (sku) DO UPDATE SET qty = stock.qty + excluded.qty, note = excluded.note").await;
+        assert_eq!((result.inserted_rows, result.updated_rows), (Some(1), Some(1)));
+        let result = exec(&executor, "INSERT INTO stock VALUES ('b', 9, NULL) ON CONFLICT DO NOTHING").await;
+        assert_eq!((result.inserted_rows, result.updated_rows), (Some(0), Some(0)));
+        exec(&executor, "INSERT INTO stock VALUES ('c', 7, NULL) ON DUPLICATE KEY UPDATE qty = VALUES(qty)").await;
+
+        let rows = query(&executor, "SELECT sku, qty, note FROM stock ORDER BY sku").await;
+        assert_eq!(rows, vec![
+            vec![text("a"), int(6), text("new")],
+            vec![text("b"), int(2), text("x")],
+            vec![text("c"), int(7), Value::Null],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn on_conflict_errors() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE stock (sku TEXT PRIMARY KEY, qty INTEGER NOT NULL)").await;
+        exec(&executor, "INSERT INTO stock VALUES ('a', 1)").await;
+
+        let message = error(&executor, "INSERT INTO stock VALUES ('a', 2) ON CONFLICT (qty) DO NOTHING").await;
+        assert!(message.contains("no unique or primary key constraint on column qty"), "{}", message);
+        let message = error(&executor, "INSERT INTO stock VALUES ('a', 2) ON CONFLICT (sku) DO UPDATE SET qty = NULL").await;
+        assert!(message.contains("NOT NULL"), "{}", message);
+        let rows = query(&executor, "SELECT qty FROM stock").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+    }
+
+    #[tokio::test]
+    async fn returning_reports_stored_values() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE items (id INTEGER PRIMARY KEY, price DECIMAL(10,2), due DATE, note TEXT)").await;
+
+        let rows = query(&executor, "INSERT INTO items (id, price, due) VALUES (1, 1.5, '2024-02-29') RETURNING *").await;
+        assert_eq!(rows, vec![vec![
+            int(1),
+            Value::Decimal("1.50".parse().unwrap()),
+            Value::Date(chrono::NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+            Value::Null,
+        ]]);
+
+        let rows = query(&executor, "UPDATE items SET price = 2, due = '2024-03-01' WHERE id = 1 RETURNING price, due").await;
+        assert_eq!(rows, vec![vec![
+            Value::Decimal("2.00".parse().unwrap()),
+            Value::Date(chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()),
+        ]]);
+
+        let rows = query(&executor, "INSERT INTO items (id, price) VALUES (2, 3) ON CONFLICT (id) DO NOTHING RETURNING price").await;
+        assert_eq!(rows, vec![vec![Value::Decimal("3.00".parse().unwrap())]]);
+
+        let rows = query(&executor, "DELETE FROM items WHERE id = 2 RETURNING id, price").await;
+        assert_eq!(rows, vec![vec![int(2), Value::Decimal("3.00".parse().unwrap())]]);
+    }
+
+    #[tokio::test]
+    async fn returning_errors_leave_table_unchanged() {
+        let executor = orders().await;
+        let message = error(&executor, "DELETE FROM orders RETURNING missing").await;
+        assert!(message.contains("missing"), "{}", message);
+        let rows = query(&executor, "SELECT COUNT(*) FROM orders").await;
+        assert_eq!(rows, vec![vec![int(3)]]);
+    }
+
+    #[tokio::test]
+    async fn incremental_view_receives_stored_values() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE prices (id INTEGER, price DECIMAL(10,2));
+            CREATE MATERIALIZED VIEW cheap WITH (incremental = true) AS SELECT id, price FROM prices WHERE price < 5;
+            INSERT INTO prices VALUES (1, 1.005), (2, 9)
+        ").await;
+        exec(&executor, "UPDATE prices SET price = 2 WHERE id = 2").await;
+
+        let rows = query(&executor, "SELECT id, price FROM cheap ORDER BY id").await;
+        assert_eq!(rows, vec![
+            vec![int(1), Value::Decimal("1.01".parse().unwrap())],
+            vec![int(2), Value::Decimal("2.00".parse().unwrap())],
+        ]);
+
+        // 格納された値で差分を計算するので、丸めた値の行も削除できる
+        exec(&executor, "DELETE FROM prices WHERE id = 1").await;
+        let rows = query(&executor, "SELECT id FROM cheap").await;
+        assert_eq!(rows, vec![vec![int(2)]]);
+    }
+
+    #[tokio::test]
+    async fn incremental_view_filters_like_refresh() {
+        let executor = executor();
+        let definitions = [
+            ("by_day", "SELECT id FROM events WHERE day = '2024-03-01'"),
+            ("by_code", "SELECT code, SUM(amount) AS total FROM events WHERE code = 'ab' GROUP BY code"),
+            ("by_nick", "SELECT id FROM events WHERE nick = 'ALICE'"),
+        ];
+        exec(&executor, "CREATE TABLE events (id INTEGER, day DATE, code CHAR(3), nick TEXT COLLATE nocase, amount INTEGER)").await;
+        for (name, sql) in definitions {
+            exec(&executor, &format!("CREATE MATERIALIZED VIEW {} WITH (incremental = true) AS {}", name, sql)).await;
+            exec(&executor, &format!("CREATE MATERIALIZED VIEW {}_full AS {}", name, sql)).await;
+        }
+
+        exec(&executor, "
+            INSERT INTO events VALUES
+                (1, '2024-03-01', 'ab', 'alice', 10),
+                (2, '2024-03-02', 'ab', 'Alice', 20),
+                (3, '2024-03-01', 'cd', 'bob', 30);
+            UPDATE events SET day = '2024-03-01', amount = 25 WHERE id = 2;
+            DELETE FROM events WHERE id = 1
+        ").await;
+
+        // 差分更新した結果は REFRESH で再計算した結果と一致する
+        for (name, _) in definitions {
+            exec(&executor, &format!("REFRESH MATERIALIZED VIEW {}_full", name)).await;
+            let incremental = query(&executor, &format!("SELECT * FROM {} ORDER BY 1", name)).await;
+            let refreshed = query(&executor, &format!("SELECT * FROM {}_full ORDER BY 1", name)).await;
+            assert!(!refreshed.is_empty(), "{}", name);
+            assert_eq!(incremental, refreshed, "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn transaction_scripts_roll_back_on_error() {
+        let executor = orders().await;
+        let statements = SqlParser::new().parse("
+            INSERT INTO orders VALUES (4, 'carol', 40);
+            INSERT INTO orders VALUES (1, 'dup', 0);
+            INSERT INTO orders VALUES (5, 'dave', 50)
+        ").unwrap();
+
+        let options = ScriptOptions { on_error: OnError::Stop, transaction: true };
+        let script = executor.execute_script(&statements, options).await.unwrap();
+        assert!(script.rolled_back);
+        assert_eq!(script.results.len(), 2);
+        let rows = query(&executor, "SELECT COUNT(*) FROM orders").await;
+        assert_eq!(rows, vec![vec![int(3)]]);
+    }
+
+    #[tokio::test]
+    async fn transaction_scripts_reject_continue_on_error() {
+        let executor = orders().await;
+        let statements = SqlParser::new().parse("INSERT INTO orders VALUES (4, 'carol', 40)").unwrap();
+
+        let options = ScriptOptions { on_error: OnError::Continue, transaction: true };
+        let message = executor.execute_script(&statements, options).await.unwrap_err().to_string();
+        assert!(message.contains("on_error=continue"), "{}", message);
+        let rows = query(&executor, "SELECT COUNT(*) FROM orders").await;
+        assert_eq!(rows, vec![vec![int(3)]]);
+
+        // トランザクションでなければエラーの後も続ける
+        let statements = SqlParser::new().parse("INSERT INTO orders VALUES (1, 'dup', 0); INSERT INTO orders VALUES (4, 'carol', 40)").unwrap();
+        let options = ScriptOptions { on_error: OnError::Continue, transaction: false };
+        let script = executor.execute_script(&statements, options).await.unwrap();
+        assert!(script.results[0].is_err() && script.results[1].is_ok());
+    }
+
+    #[tokio::test]
+    async fn select_without_from_returns_one_row() {
+        let executor = orders().await;
+
+        let rows = query(&executor, "SELECT 1 AS n, 'a' || 'b', NULL").await;
+        assert_eq!(rows, vec![vec![int(1), text("ab"), Value::Null]]);
+        let rows = query(&executor, "SELECT COUNT(*), (SELECT SUM(amount) FROM orders)").await;
+        assert_eq!(rows, vec![vec![int(1), int(60)]]);
+        let rows = query(&executor, "SELECT 1 AS n WHERE 1 = 2").await;
+        assert!(rows.is_empty());
+        let rows = query(&executor, "SELECT 2 AS n UNION SELECT 1 ORDER BY n").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)]]);
+    }
+
+    #[tokio::test]
+    async fn select_without_from_reports_unknown_columns() {
+        let executor = executor();
+        let message = error(&executor, "SELECT amount").await;
+        assert!(message.contains("Column amount not found (the query has no FROM clause)"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn distinct_treats_nulls_as_equal() {
+        let executor = orders().await;
+        exec(&executor, "INSERT INTO orders VALUES (4, 'alice', 10), (5, NULL, NULL), (6, NULL, NULL)").await;
+
+        let rows = query(&executor, "SELECT DISTINCT customer, amount FROM orders ORDER BY customer, amount").await;
+        assert_eq!(rows, vec![
+            vec![text("alice"), int(10)],
+            vec![text("alice"), int(30)],
+            vec![text("bob"), int(20)],
+            vec![Value::Null, Value::Null],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn distinct_on_keeps_the_first_row_of_each_group() {
+        let executor = orders().await;
+        exec(&executor, "INSERT INTO orders VALUES (4, 'bob', 20), (5, NULL, NULL), (6, NULL, 60)").await;
+
+        let rows = query(&executor, "SELECT DISTINCT ON (customer) customer, id FROM orders ORDER BY customer, amount DESC").await;
+        assert_eq!(rows, vec![
+            vec![text("alice"), int(3)],
+            vec![text("bob"), int(2)],
+            vec![Value::Null, int(5)],
+        ]);
+
+        let message = error(&executor, "SELECT DISTINCT ON (nope) id FROM orders").await;
+        assert!(message.contains("Column nope not found"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn update_evaluates_set_expressions_per_row() {
+        let executor = orders().await;
+        exec(&executor, "INSERT INTO orders (id, customer) VALUES (4, 'carol')").await;
+        exec(&executor, "UPDATE orders AS o SET amount = o.amount + 1, customer = UPPER(customer) WHERE id > 1").await;
+
+        let rows = query(&executor, "SELECT id, customer, amount FROM orders ORDER BY id").await;
+        assert_eq!(rows, vec![
+            vec![int(1), text("alice"), int(10)],
+            vec![int(2), text("BOB"), int(21)],
+            vec![int(3), text("ALICE"), int(31)],
+            vec![int(4), text("CAROL"), Value::Null],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn failed_update_leaves_every_row_unchanged() {
+        let executor = orders().await;
+        let message = error(&executor, "UPDATE orders SET amount = 100 / (amount - 20)").await;
+        assert!(message.contains("Division by zero"), "{}", message);
+        let message = error(&executor, "UPDATE orders SET amount = customer").await;
+        assert!(message.contains("mismatch"), "{}", message);
+
+        let rows = query(&executor, "SELECT amount FROM orders ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(10)], vec![int(20)], vec![int(30)]]);
+    }
+
+    #[tokio::test]
+    async fn power_overflow_is_an_error() {
+        let executor = executor();
+        let rows = query(&executor, "SELECT POWER(2, 10)").await;
+        assert_eq!(rows, vec![vec![Value::Float(1024.0)]]);
+        let message = error(&executor, "SELECT POWER(10, 400)").await;
+        assert!(message.contains("Result of POWER is out of range"), "{}", message);
+        let message = error(&executor, "SELECT POWER(0, -1)").await;
+        assert!(message.contains("Zero raised to a negative power"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn cast_expressions_and_typed_literals() {
+        let executor = executor();
+
+        let rows = query(&executor, "SELECT '12'::INTEGER + 1, CAST(NULL AS INTEGER), CAST('2024-02-29' AS DATE) + 1").await;
+        assert_eq!(rows, vec![vec![int(13), Value::Null, Value::Date(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap())]]);
+        let rows = query(&executor, "SELECT DATE '2024-02-29', TIME '13:45:00'").await;
+        assert_eq!(rows, vec![vec![
+            Value::Date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+            Value::Time(NaiveTime::from_hms_opt(13, 45, 0).unwrap()),
+        ]]);
+
+        let message = error(&executor, "SELECT CAST('abc' AS INTEGER)").await;
+        assert!(message.contains("Cannot convert abc to INTEGER"), "{}", message);
+        let message = error(&executor, "SELECT DATE '2023-02-29'").await;
+        assert!(message.contains("Cannot convert 2023-02-29 to DATE"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn decimal_literals_keep_decimal_arithmetic_exact() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE items (id INTEGER, price DECIMAL(10,2), weight FLOAT);
+            INSERT INTO items VALUES (1, 0.1, 1.5), (2, NULL, 2.0)
+        ").await;
+
+        let rows = query(&executor, "SELECT price + 0.2, 0.1 + 0.2 = 0.3, weight * 2.0 FROM items ORDER BY id").await;
+        assert_eq!(rows, vec![
+            vec![Value::Decimal("0.30".parse().unwrap()), Value::Boolean(true), Value::Float(3.0)],
+            vec![Value::Null, Value::Boolean(true), Value::Float(4.0)],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn decimal_columns_round_to_scale_and_aggregate_exactly() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE m (id INTEGER, price NUMERIC(5,2));
+            INSERT INTO m VALUES (1, 1.005), (2, 2.5), (3, NULL), (4, 10)
+        ").await;
+        let decimal = |s: &str| Value::Decimal(s.parse().unwrap());
+
+        let rows = query(&executor, "SELECT price FROM m ORDER BY id").await;
+        assert_eq!(rows, vec![vec![decimal("1.01")], vec![decimal("2.50")], vec![Value::Null], vec![decimal("10.00")]]);
+        let rows = query(&executor, "SELECT SUM(price), AVG(price) FROM m").await;
+        assert_eq!(rows, vec![vec![decimal("13.51"), decimal("4.5033333333333333")]]);
+        let rows = query(&executor, "SELECT CAST(price AS INTEGER), CAST(price AS FLOAT) FROM m WHERE id = 2").await;
+        assert_eq!(rows, vec![vec![int(3), Value::Float(2.5)]]);
+
+        let message = error(&executor, "INSERT INTO m VALUES (5, 1000.00)").await;
+        assert!(message.contains("Cannot convert 1000.00 to DECIMAL(5,2)"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn text_literals_are_compared_as_dates_and_intervals() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE shifts (id INTEGER, d DATE, length INTERVAL);
+            INSERT INTO shifts VALUES (1, '2024-02-29', '8 hours'), (2, '2024-03-01', '4 hours'), (3, NULL, NULL)
+        ").await;
+
+        let rows = query(&executor, "SELECT id FROM shifts WHERE d = '2024-02-29'").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+        let rows = query(&executor, "SELECT id FROM shifts WHERE '2024-03-01' <= d AND length < '6 hours'").await;
+        assert_eq!(rows, vec![vec![int(2)]]);
+        let rows = query(&executor, "SELECT id FROM shifts WHERE d IN ('2024-03-01', '2024-02-29') ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)]]);
+
+        exec(&executor, "UPDATE shifts SET length = '9 hours' WHERE d = '2024-02-29'").await;
+        exec(&executor, "DELETE FROM shifts WHERE d = '2024-03-01'").await;
+        let rows = query(&executor, "SELECT id, CAST(length AS TEXT) FROM shifts WHERE id < 3").await;
+        assert_eq!(rows, vec![vec![int(1), text("09:00:00")]]);
+
+        // ビューのカラムとの比較でも変換する
+        exec(&executor, "CREATE VIEW dated AS SELECT id, d FROM shifts").await;
+        let rows = query(&executor, "SELECT id FROM dated WHERE d = '2024-02-29'").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+    }
+
+    #[tokio::test]
+    async fn invalid_date_literal_in_filter_is_an_error() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE shifts (id INTEGER, d DATE)").await;
+        let message = error(&executor, "SELECT id FROM shifts WHERE d = 'someday'").await;
+        assert!(message.contains("Cannot convert someday to DATE"), "{}", message);
+        let message = error(&executor, "DELETE FROM shifts WHERE d < '2024-13-01'").await;
+        assert!(message.contains("DATE"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn timestamptz_functions_use_session_time_zone() {
+        let executor = executor();
+        exec(&executor, "
+            SET TIME ZONE '+09:00';
+            CREATE TABLE events (id INTEGER, at TIMESTAMPTZ);
+            INSERT INTO events VALUES (1, '2023-12-31 15:00:00+00:00')
+        ").await;
+
+        let rows = query(&executor, "
+            SELECT CAST(at AS DATE), EXTRACT(HOUR FROM at), EXTRACT(EPOCH FROM at), CAST(DATE_TRUNC('day', at) AS TEXT)
+            FROM events
+        ").await;
+        assert_eq!(rows, vec![vec![
+            Value::Date(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+            Value::Float(0.0),
+            Value::Float(1_704_034_800.0),
+            text("2024-01-01 00:00:00 +09:00"),
+        ]]);
+
+        // 時差がなければUTCの日付になる
+        exec(&executor, "SET TIME ZONE 'UTC'").await;
+        let rows = query(&executor, "SELECT CAST(at AS DATE), EXTRACT(HOUR FROM at) FROM events").await;
+        assert_eq!(rows, vec![vec![Value::Date(NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()), Value::Float(15.0)]]);
+    }
+
+    #[tokio::test]
+    async fn sessions_do_not_share_time_zone() {
+        let executor = executor();
+        let session = executor.session();
+        exec(&session, "SET TIME ZONE '+09:00'").await;
+
+        assert_eq!(session.time_zone().local_minus_utc(), 9 * 3600);
+        assert_eq!(executor.time_zone().local_minus_utc(), 0);
+
+        // テーブルはセッションの間で共有する
+        exec(&session, "CREATE TABLE shared (id INTEGER)").await;
+        let rows = query(&executor, "SELECT COUNT(*) FROM shared").await;
+        assert_eq!(rows, vec![vec![int(0)]]);
+    }
+
+    #[tokio::test]
+    async fn named_time_zones_are_rejected() {
+        let executor = executor();
+        let message = error(&executor, "SET TIME ZONE 'Asia/Tokyo'").await;
+        assert!(message.contains("Time zone names are not supported"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn text_literals_are_compared_as_timestamps() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE logs (id INTEGER, ts TIMESTAMP, at TIMESTAMPTZ);
+            INSERT INTO logs VALUES (1, '2024-01-01 00:00:00', '2024-01-01 00:00:00+00:00'), (2, '2024-01-01 12:30:00', NULL)
+        ").await;
+
+        let rows = query(&executor, "SELECT id FROM logs WHERE ts = '2024-01-01 00:00:00'").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+        let rows = query(&executor, "SELECT id FROM logs WHERE ts > '2024-01-01'").await;
+        assert_eq!(rows, vec![vec![int(2)]]);
+
+        // タイムゾーンのない文字列はセッションのタイムゾーンの日時とみなす
+        exec(&executor, "SET TIME ZONE '+09:00'").await;
+        let rows = query(&executor, "SELECT id FROM logs WHERE at = '2024-01-01 09:00:00'").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+        let rows = query(&executor, "SELECT id FROM logs WHERE at = '2024-01-01 00:00:00Z'").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+
+        let message = error(&executor, "SELECT id FROM logs WHERE ts = 'yesterday noon'").await;
+        assert!(message.contains("TIMESTAMP"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn json_path_operators() {
+        let executor = executor();
+        exec(&executor, r#"
+            CREATE TABLE docs (id INTEGER, body JSON);
+            INSERT INTO docs VALUES (1, '{"name": "a", "tags": ["x", "y"]}'), (2, '{"name": "b"}'), (3, NULL)
+        "#).await;
+
+        let rows = query(&executor, "
+            SELECT body ->> 'name', body -> 'tags' -> 1, body #>> '{tags,0}', body ->> 'missing'
+            FROM docs ORDER BY id
+        ").await;
+        assert_eq!(rows, vec![
+            vec![text("a"), Value::Json(serde_json::json!("y")), text("x"), Value::Null],
+            vec![text("b"), Value::Null, Value::Null, Value::Null],
+            vec![Value::Null, Value::Null, Value::Null, Value::Null],
+        ]);
+        let rows = query(&executor, "SELECT id FROM docs WHERE body ->> 'name' = 'b'").await;
+        assert_eq!(rows, vec![vec![int(2)]]);
+
+        let message = error(&executor, "INSERT INTO docs VALUES (4, '{bad')").await;
+        assert!(message.contains("Cannot convert {bad to JSON"), "{}", message);
+        let message = error(&executor, "SELECT 1 -> 'a'").await;
+        assert!(message.contains("Operator -> cannot be applied to Integer and Text"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn bytea_values_from_hex_and_text() {
+        let executor = executor();
+        exec(&executor, r"
+            CREATE TABLE files (id INTEGER, data BYTEA);
+            INSERT INTO files VALUES (1, X'CAFE'), (2, '\xdead'), (3, 'hi'), (4, NULL)
+        ").await;
+
+        let rows = query(&executor, "SELECT LENGTH(data), CAST(data AS TEXT) FROM files ORDER BY id").await;
+        assert_eq!(rows, vec![
+            vec![int(2), text(r"\xcafe")],
+            vec![int(2), text(r"\xdead")],
+            vec![int(2), text(r"\x6869")],
+            vec![Value::Null, Value::Null],
+        ]);
+        let rows = query(&executor, r"SELECT id FROM files WHERE data = X'CAFE' OR data IN ('\xdead', 'hi') ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)], vec![int(3)]]);
+        let rows = query(&executor, "SELECT data || X'00' FROM files WHERE id = 1").await;
+        assert_eq!(rows, vec![vec![Value::Bytes(bytes::Bytes::from_static(b"\xca\xfe\x00"))]]);
+    }
+
+    #[tokio::test]
+    async fn bytea_errors() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE files (id INTEGER, data BYTEA)").await;
+
+        let message = error(&executor, "INSERT INTO files VALUES (1, X'ABC')").await;
+        assert!(message.contains("Invalid hex string: X'ABC'"), "{}", message);
+        let message = error(&executor, "INSERT INTO files VALUES (1, 12)").await;
+        assert!(message.contains("expected Blob, got Integer"), "{}", message);
+        let message = error(&executor, r"SELECT id FROM files WHERE data = '\xzz'").await;
+        assert!(message.contains("Cannot convert"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn text_literals_are_compared_as_uuids() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE items (id UUID PRIMARY KEY, name TEXT);
+            INSERT INTO items VALUES ('a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11', 'a'), ('00000000-0000-0000-0000-000000000001', 'b')
+        ").await;
+
+        // 大文字やハイフンのない形式でも同じUUIDとして比較する
+        let rows = query(&executor, "SELECT name FROM items WHERE id = 'A0EEBC999C0B4EF8BB6D6BB9BD380A11'").await;
+        assert_eq!(rows, vec![vec![text("a")]]);
+        let rows = query(&executor, "SELECT name FROM items WHERE id <> 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'").await;
+        assert_eq!(rows, vec![vec![text("b")]]);
+
+        exec(&executor, "DELETE FROM items WHERE id IN ('00000000-0000-0000-0000-000000000001')").await;
+        let rows = query(&executor, "SELECT COUNT(*) FROM items").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+
+        let message = error(&executor, "SELECT name FROM items WHERE id = 'not-a-uuid'").await;
+        assert!(message.contains("not-a-uuid"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn generated_uuids_differ_per_row_and_order_by_bytes() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE u (id UUID PRIMARY KEY, n INTEGER)").await;
+        exec(&executor, "INSERT INTO u VALUES (gen_random_uuid(), 1), (gen_random_uuid(), 2)").await;
+
+        let rows = query(&executor, "SELECT id FROM u").await;
+        assert_eq!(rows.len(), 2);
+        assert_ne!(rows[0], rows[1]);
+        let rows = query(&executor, "
+            SELECT CAST('00000000-0000-0000-0000-000000000002' AS UUID) > CAST('00000000-0000-0000-0000-000000000001' AS UUID),
+                   CAST(CAST('A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11' AS UUID) AS TEXT)
+        ").await;
+        assert_eq!(rows, vec![vec![Value::Boolean(true), text("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")]]);
+
+        let message = error(&executor, "SELECT CAST('xyz' AS UUID)").await;
+        assert!(message.contains("xyz"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn array_columns_support_literals_indexing_and_any() {
+        let executor = executor();
+        exec(&executor, r#"
+            CREATE TABLE posts (id INTEGER, tags TEXT[], scores INTEGER[]);
+            INSERT INTO posts VALUES (1, ARRAY['a', 'b'], ARRAY[1, 2, 3]), (2, '{c,"d e",NULL}', '{}'), (3, NULL, NULL)
+        "#).await;
+
+        let rows = query(&executor, "
+            SELECT scores[1], scores[9], ARRAY_LENGTH(scores, 1), CARDINALITY(tags) FROM posts ORDER BY id
+        ").await;
+        assert_eq!(rows, vec![
+            vec![int(1), Value::Null, int(3), int(2)],
+            vec![Value::Null, Value::Null, Value::Null, int(3)],
+            vec![Value::Null, Value::Null, Value::Null, Value::Null],
+        ]);
+        let rows = query(&executor, "SELECT tags FROM posts WHERE id = 2").await;
+        assert_eq!(rows, vec![vec![Value::Array(vec![text("c"), text("d e"), Value::Null])]]);
+
+        let rows = query(&executor, "SELECT id FROM posts WHERE 'a' = ANY(tags) OR 2 > ALL(scores) ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)]]);
+        let rows = query(&executor, "SELECT id FROM posts WHERE tags = '{a,b}' OR scores IN ('{}')").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)]]);
+    }
+
+    #[tokio::test]
+    async fn array_element_errors() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE posts (id INTEGER, scores INTEGER[])").await;
+
+        let message = error(&executor, "INSERT INTO posts VALUES (1, '{1,x}')").await;
+        assert!(message.contains("Cannot convert x to INTEGER"), "{}", message);
+        let message = error(&executor, "SELECT id FROM posts WHERE scores = '{1,x}'").await;
+        assert!(message.contains("Cannot convert x to INTEGER"), "{}", message);
+        let message = error(&executor, "SELECT * FROM UNNEST(5)").await;
+        assert!(message.contains("unnest() requires an array, got Integer"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn unnest_returns_one_row_per_element() {
+        let executor = executor();
+
+        let rows = query(&executor, "SELECT * FROM UNNEST(ARRAY[1, NULL, 3])").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![Value::Null], vec![int(3)]]);
+        let rows = query(&executor, "SELECT n FROM UNNEST(ARRAY[1, NULL, 3]) AS u (n) WHERE n > 1").await;
+        assert_eq!(rows, vec![vec![int(3)]]);
+    }
+
+    #[tokio::test]
+    async fn enum_columns_validate_and_order_by_declaration() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy');
+            CREATE TABLE people (id INTEGER, feeling mood);
+            INSERT INTO people VALUES (1, 'happy'), (2, 'sad'), (3, NULL), (4, 'ok')
+        ").await;
+
+        let rows = query(&executor, "SELECT id FROM people ORDER BY feeling").await;
+        assert_eq!(rows, vec![vec![int(2)], vec![int(4)], vec![int(1)], vec![int(3)]]);
+        let rows = query(&executor, "SELECT CAST(MAX(feeling) AS TEXT) FROM people").await;
+        assert_eq!(rows, vec![vec![text("happy")]]);
+
+        exec(&executor, "ALTER TYPE mood ADD VALUE 'meh' AFTER 'sad'").await;
+        exec(&executor, "ALTER TYPE mood ADD VALUE IF NOT EXISTS 'ok'").await;
+        exec(&executor, "INSERT INTO people VALUES (5, 'meh')").await;
+        let rows = query(&executor, "SELECT id FROM people WHERE feeling < 'ok' ORDER BY feeling").await;
+        assert_eq!(rows, vec![vec![int(2)], vec![int(5)]]);
+    }
+
+    #[tokio::test]
+    async fn enum_definition_errors() {
+        let executor = executor();
+        exec(&executor, "CREATE TYPE mood AS ENUM ('sad', 'happy'); CREATE TABLE people (feeling mood)").await;
+
+        let message = error(&executor, "INSERT INTO people VALUES ('angry')").await;
+        assert!(message.contains("Invalid input value for enum mood: angry"), "{}", message);
+        let message = error(&executor, "CREATE TYPE mood AS ENUM ('x')").await;
+        assert!(message.contains("Type mood already exists"), "{}", message);
+        let message = error(&executor, "CREATE TYPE dup AS ENUM ('a', 'a')").await;
+        assert!(message.contains("Enum label a already exists in type dup"), "{}", message);
+        let message = error(&executor, "ALTER TYPE mood ADD VALUE 'sad'").await;
+        assert!(message.contains("Enum label sad already exists in type mood"), "{}", message);
+        let message = error(&executor, "ALTER TYPE mood ADD VALUE 'x' BEFORE 'nope'").await;
+        assert!(message.contains("Enum label nope not found in type mood"), "{}", message);
+        let message = error(&executor, "CREATE TABLE bad (m nosuchtype)").await;
+        assert!(message.contains("Type nosuchtype not found"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn char_columns_are_blank_padded_and_compared_with_padding() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE codes (code CHAR(3), label VARCHAR(3))").await;
+        exec(&executor, "INSERT INTO codes VALUES ('ab', 'ab'), ('abc', 'abc')").await;
+
+        let rows = query(&executor, "SELECT code, label FROM codes ORDER BY code").await;
+        assert_eq!(rows, vec![vec![text("ab "), text("ab")], vec![text("abc"), text("abc")]]);
+        let rows = query(&executor, "SELECT label FROM codes WHERE code = 'ab'").await;
+        assert_eq!(rows, vec![vec![text("ab")]]);
+        let rows = query(&executor, "SELECT label FROM codes WHERE code IN ('ab ', 'x') AND label = 'ab'").await;
+        assert_eq!(rows, vec![vec![text("ab")]]);
+
+        exec(&executor, "UPDATE codes SET code = 'z' WHERE label = 'abc'").await;
+        let rows = query(&executor, "SELECT code FROM codes WHERE label = 'abc'").await;
+        assert_eq!(rows, vec![vec![text("z  ")]]);
+        let message = error(&executor, "UPDATE codes SET code = 'long'").await;
+        assert!(message.contains("too long"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn varchar_lengths_and_integer_widths_are_enforced() {
+        let executor = executor();
+        exec(&executor, "CREATE TABLE w (s SMALLINT, v VARCHAR(3))").await;
+        exec(&executor, "INSERT INTO w VALUES (32767, 'abc'), (NULL, NULL)").await;
+
+        let message = error(&executor, "INSERT INTO w VALUES (32768, 'a')").await;
+        assert!(message.contains("Value 32768 out of range for column s (SMALLINT)"), "{}", message);
+        let message = error(&executor, "UPDATE w SET v = 'abcd'").await;
+        assert!(message.contains("Value too long for column v (maximum 3 characters)"), "{}", message);
+        let rows = query(&executor, "SELECT v FROM w WHERE s = 32767").await;
+        assert_eq!(rows, vec![vec![text("abc")]]);
+    }
+
+    #[tokio::test]
+    async fn in_list_and_distinct_follow_column_collation() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE members (id INTEGER, nick TEXT COLLATE nocase);
+            INSERT INTO members VALUES (1, 'Bob'), (2, 'BOB'), (3, 'alice'), (4, NULL)
+        ").await;
+
+        let rows = query(&executor, "SELECT id FROM members WHERE nick IN ('bob') ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)]]);
+        let rows = query(&executor, "SELECT id FROM members WHERE nick NOT IN ('BOB') ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(3)]]);
+        let rows = query(&executor, "SELECT id FROM members WHERE nick COLLATE \"C\" IN ('BOB')").await;
+        assert_eq!(rows, vec![vec![int(2)]]);
+
+        // 照合順序で等しい値は最初の行の値で1つにまとめる
+        let rows = query(&executor, "SELECT DISTINCT nick FROM members ORDER BY nick").await;
+        assert_eq!(rows, vec![vec![text("alice")], vec![text("Bob")], vec![Value::Null]]);
+    }
+
+    #[tokio::test]
+    async fn group_by_follows_column_collation() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE members (id INTEGER, nick TEXT COLLATE nocase, points INTEGER);
+            INSERT INTO members VALUES (1, 'Bob', 1), (2, 'alice', 2), (3, 'BOB', 3), (4, NULL, 4), (5, 'ALICE', 5)
+        ").await;
+
+        // 照合順序で等しい値は同じグループにし、最初の行の値で表す
+        let rows = query(&executor, "SELECT nick, SUM(points), COUNT(*) FROM members GROUP BY nick ORDER BY nick").await;
+        assert_eq!(rows, vec![
+            vec![text("alice"), int(7), int(2)],
+            vec![text("Bob"), int(4), int(2)],
+            vec![Value::Null, int(4), int(1)],
+        ]);
+        let rows = query(&executor, "SELECT UPPER(nick) AS n FROM members GROUP BY nick ORDER BY n").await;
+        assert_eq!(rows, vec![vec![text("ALICE")], vec![text("BOB")], vec![Value::Null]]);
+
+        // 差分更新するビューも同じグループにまとめる
+        exec(&executor, "
+            CREATE MATERIALIZED VIEW totals WITH (incremental = true) AS
+                SELECT nick, SUM(points) AS total FROM members GROUP BY nick;
+            INSERT INTO members VALUES (6, 'bob', 10)
+        ").await;
+        let rows = query(&executor, "SELECT total FROM totals WHERE nick = 'BOB'").await;
+        assert_eq!(rows, vec![vec![int(14)]]);
+    }
+
+    #[tokio::test]
+    async fn unique_columns_follow_collation_on_update() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT COLLATE nocase UNIQUE);
+            INSERT INTO users VALUES (1, 'a@example.com'), (2, 'b@example.com')
+        ").await;
+
+        let message = error(&executor, "INSERT INTO users VALUES (3, 'A@EXAMPLE.COM')").await;
+        assert!(message.contains("UNIQUE constraint violation for column email"), "{}", message);
+        let message = error(&executor, "UPDATE users SET email = 'A@Example.com' WHERE id = 2").await;
+        assert!(message.contains("UNIQUE constraint violation for column email"), "{}", message);
+
+        // 自分自身の大文字小文字だけを変える更新はできる
+        exec(&executor, "UPDATE users SET email = 'B@EXAMPLE.COM' WHERE id = 2").await;
+        let rows = query(&executor, "SELECT email FROM users ORDER BY id").await;
+        assert_eq!(rows, vec![vec![text("a@example.com")], vec![text("B@EXAMPLE.COM")]]);
+    }
+
+    #[tokio::test]
+    async fn filters_resolve_aliases_and_qualified_names_after_alter_type() {
+        let executor = executor();
+        exec(&executor, "CREATE TYPE mood AS ENUM ('sad', 'happy')").await;
+        exec(&executor, "CREATE TABLE people (id INTEGER, name TEXT, feeling mood)").await;
+        exec(&executor, "INSERT INTO people VALUES (1, 'a', 'sad'), (2, 'b', 'happy'), (3, 'c', NULL)").await;
+        exec(&executor, "ALTER TYPE mood ADD VALUE 'ok' BEFORE 'happy'").await;
+        exec(&executor, "INSERT INTO people VALUES (4, 'd', 'ok')").await;
+
+        let rows = query(&executor, "SELECT p.id FROM people AS p WHERE p.feeling >= 'ok' ORDER BY p.id").await;
+        assert_eq!(rows, vec![vec![int(2)], vec![int(4)]]);
+        let rows = query(&executor, "SELECT people.id FROM people WHERE people.name = 'a' AND people.feeling = 'sad'").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+
+        exec(&executor, "UPDATE people AS p SET name = 'z' WHERE p.feeling = 'ok'").await;
+        exec(&executor, "DELETE FROM people WHERE people.feeling IS NULL").await;
+        let rows = query(&executor, "SELECT id, name FROM people ORDER BY id").await;
+        assert_eq!(rows, vec![
+            vec![int(1), text("a")],
+            vec![int(2), text("b")],
+            vec![int(4), text("z")],
+        ]);
+
+        let rows = query(&executor, "SELECT id FROM people WHERE feeling = 'angry'").await;
+        assert!(rows.is_empty());
+    }
+}
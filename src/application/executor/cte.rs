@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::{rename_result, ExecutorError, QueryContext, QueryExecutor};
+use crate::domain::entity::{ResultSet, Row, Value, ValueKey};
+use crate::infrastructure::parser::{CommonTableExpression, RecursiveTerm};
+
+/// WITH RECURSIVE の再帰部分を繰り返す最大回数（終わらない再帰の検出用）
+const MAX_RECURSIVE_ITERATIONS: usize = 10_000;
+
+impl QueryExecutor {
+    /// WITH句の共通テーブル式を定義順に実行し、それらを参照できる文脈を返す
+    ///
+    /// 後に定義された共通テーブル式からは、先に定義されたものを参照できる。
+    pub(super) async fn evaluate_ctes(
+        &self,
+        with: &[CommonTableExpression],
+        ctx: &QueryContext
+    ) -> Result<QueryContext, ExecutorError> {
+        let mut ctx = ctx.clone();
+        for cte in with {
+            let result = match &cte.recursive {
+                Some(term) => self.evaluate_recursive(cte, term, &ctx).await?,
+                None => name_columns(cte, self.select_in(&cte.query, &ctx.nested()).await?)?,
+            };
+            ctx.ctes.insert(cte.name.clone(), Arc::new(result));
+        }
+
+        Ok(ctx)
+    }
+
+    /// 再帰的な共通テーブル式を実行する
+    ///
+    /// 非再帰部分の結果から始め、直前の繰り返しで追加された行だけを共通テーブル式の内容として
+    /// 再帰部分を実行し、新しい行が得られなくなるまで繰り返す。
+    /// UNION の場合は、それまでに得られた行と重複する行を追加しない。
+    async fn evaluate_recursive(
+        &self,
+        cte: &CommonTableExpression,
+        term: &RecursiveTerm,
+        ctx: &QueryContext
+    ) -> Result<ResultSet, ExecutorError> {
+        let anchor = name_columns(cte, self.select_in(&cte.query, &ctx.nested()).await?)?;
+        let names: Vec<String> = anchor.columns.iter().map(|c| c.name.clone()).collect();
+
+        let mut seen = HashSet::new();
+        let mut result = ResultSet::new(anchor.columns.clone());
+        let mut working = Vec::new();
+        let mut append = |rows: Vec<Row>, result: &mut ResultSet, working: &mut Vec<Row>| {
+            for row in rows {
+                if term.union_all || seen.insert(row_key(&names, &row)) {
+                    result.add_row(row.clone());
+                    working.push(row);
+                }
+            }
+        };
+        append(anchor.rows, &mut result, &mut working);
+
+        let mut iterations = 0;
+        while !working.is_empty() {
+            iterations += 1;
+            if iterations > MAX_RECURSIVE_ITERATIONS {
+                return Err(ExecutorError::Execution(format!(
+                    "Recursive query {} did not finish within {} iterations", cte.name, MAX_RECURSIVE_ITERATIONS)));
+            }
+
+            let mut step = ctx.nested();
+            step.ctes.insert(cte.name.clone(), Arc::new(ResultSet {
+                columns: result.columns.clone(),
+                rows: std::mem::take(&mut working),
+            }));
+            let rows = self.select_in(&term.query, &step).await?;
+            if rows.columns.len() != names.len() {
+                return Err(ExecutorError::Execution(format!(
+                    "Recursive query {} returns {} columns in its recursive term but {} in its non-recursive term",
+                    cte.name, rows.columns.len(), names.len())));
+            }
+
+            append(rename_result(rows, &names).rows, &mut result, &mut working);
+        }
+
+        Ok(result)
+    }
+}
+
+/// 共通テーブル式で指定された別名に結果カラムの名前を置き換える
+fn name_columns(cte: &CommonTableExpression, result: ResultSet) -> Result<ResultSet, ExecutorError> {
+    if cte.columns.is_empty() {
+        return Ok(result);
+    }
+
+    if cte.columns.len() != result.columns.len() {
+        return Err(ExecutorError::Execution(format!(
+            "WITH query {} declares {} columns but its query returns {}",
+            cte.name, cte.columns.len(), result.columns.len())));
+    }
+
+    Ok(rename_result(result, &cte.columns))
+}
+
+/// 重複を判定するための行のキー
+fn row_key(names: &[String], row: &Row) -> ValueKey {
+    ValueKey(names.iter().map(|name| row.get(name).cloned().unwrap_or(Value::Null)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::executor::testing::{error, exec, executor, int, query};
+
+    #[tokio::test]
+    async fn ctes_can_refer_to_earlier_ctes() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE orders (id INTEGER, amount INTEGER);
+            INSERT INTO orders VALUES (1, 10), (2, 20), (3, NULL)
+        ").await;
+
+        let rows = query(&executor, "
+            WITH a AS (SELECT id, amount FROM orders WHERE amount IS NOT NULL),
+                 b AS (SELECT id FROM a WHERE amount > 10)
+            SELECT id FROM b
+        ").await;
+        assert_eq!(rows, vec![vec![int(2)]]);
+
+        let message = error(&executor, "WITH t AS (SELECT 1 AS x) SELECT y FROM t").await;
+        assert!(message.contains("Column y not found"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn recursive_ctes_run_until_no_new_rows() {
+        let executor = executor();
+
+        let rows = query(&executor, "
+            WITH RECURSIVE n (x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 5)
+            SELECT SUM(x) FROM n
+        ").await;
+        assert_eq!(rows, vec![vec![int(15)]]);
+        // UNION は既に得られた行を追加しないので、同じ行を返す再帰も終わる
+        let rows = query(&executor, "WITH RECURSIVE n AS (SELECT 1 AS x UNION SELECT x FROM n) SELECT x FROM n").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+
+        let message = error(&executor, "WITH RECURSIVE n AS (SELECT 1 AS x UNION ALL SELECT x + 1 FROM n) SELECT x FROM n").await;
+        assert!(message.contains("did not finish within 10000 iterations"), "{}", message);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{ExecutorError, QueryContext, QueryExecutor};
+use crate::domain::entity::{Column, ResultSet, Row, TypeLimit, Value, ValueKey};
+use crate::infrastructure::parser::{SetOperation, SetOperator};
+
+impl QueryExecutor {
+    /// UNION / INTERSECT / EXCEPT の両側のクエリを実行し、結果を組み合わせる
+    ///
+    /// 結果カラムの名前は左側のクエリに従い、型は両側の型をまとめた型に揃える。
+    pub(super) async fn execute_set_operation(
+        &self,
+        set: &SetOperation,
+        ctx: &QueryContext
+    ) -> Result<ResultSet, ExecutorError> {
+        let left = self.select_in(&set.left, ctx).await?;
+        let right = self.select_in(&set.right, ctx).await?;
+
+        if left.columns.len() != right.columns.len() {
+            return Err(ExecutorError::Execution(format!(
+                "Each {} query must have the same number of columns ({} and {})",
+                set.op, left.columns.len(), right.columns.len())));
+        }
+
+        let columns = left.columns.iter().zip(&right.columns).map(|(l, r)| {
+            let data_type = l.data_type.common_type(&r.data_type).ok_or_else(|| ExecutorError::Execution(format!(
+                "{} types {} and {} cannot be matched for column {}", set.op, l.data_type, r.data_type, l.name)))?;
+            // 長さの指定は両側が同じときだけ引き継ぎ、どちらかのカラムに照合順序があれば、その照合順序で比較する
+            let column = match l.type_limit {
+                Some(limit) if l.type_limit == r.type_limit => Column::new(&l.name, data_type).with_type_limit(limit),
+                _ => Column::new(&l.name, data_type),
+            };
+            Ok(match l.collation.or(r.collation) {
+                Some(collation) => column.with_collation(collation),
+                None => column,
+            })
+        }).collect::<Result<Vec<_>, ExecutorError>>()?;
+
+        let left_rows = unify_rows(&left, &columns)?;
+        let right_rows = unify_rows(&right, &columns)?;
+        let collations: Vec<_> = columns.iter().map(|c| c.collation).collect();
+        let key = |ValueKey(values): &ValueKey| ValueKey::collated(values, &collations);
+
+        let rows = match (set.op, set.all) {
+            (SetOperator::Union, true) => left_rows.into_iter().chain(right_rows).collect(),
+            (SetOperator::Union, false) => {
+                let mut seen = HashSet::new();
+                left_rows.into_iter().chain(right_rows).filter(|row| seen.insert(key(row))).collect()
+            },
+            (SetOperator::Intersect, false) | (SetOperator::Except, false) => {
+                let keep_matches = set.op == SetOperator::Intersect;
+                let right_set: HashSet<ValueKey> = right_rows.iter().map(key).collect();
+                let mut seen = HashSet::new();
+                left_rows.into_iter()
+                    .filter(|row| {
+                        let row = key(row);
+                        right_set.contains(&row) == keep_matches && seen.insert(row)
+                    })
+                    .collect()
+            },
+            // ALL の場合は右側に現れる回数だけ対応させる
+            (SetOperator::Intersect, true) | (SetOperator::Except, true) => {
+                let keep_matches = set.op == SetOperator::Intersect;
+                let mut counts: HashMap<ValueKey, usize> = HashMap::new();
+                for row in &right_rows {
+                    *counts.entry(key(row)).or_default() += 1;
+                }
+                left_rows.into_iter().filter(|row| {
+                    let matched = match counts.get_mut(&key(row)) {
+                        Some(count) if *count > 0 => {
+                            *count -= 1;
+                            true
+                        },
+                        _ => false,
+                    };
+                    matched == keep_matches
+                }).collect::<Vec<_>>()
+            },
+        };
+
+        let mut result = ResultSet::new(columns);
+        for ValueKey(values) in rows {
+            let mut row = Row::new();
+            for (column, value) in result.columns.iter().zip(values) {
+                row.set(column.name.clone(), value);
+            }
+            result.add_row(row);
+        }
+
+        Ok(result)
+    }
+}
+
+/// 結果の各行を、まとめた型に変換した値の組にする
+///
+/// CHAR(n) の値を CHAR(n) でないカラムにまとめるときは、埋めた空白を取り除いてから比較する。
+fn unify_rows(result: &ResultSet, columns: &[Column]) -> Result<Vec<ValueKey>, ExecutorError> {
+    result.rows.iter().map(|row| {
+        let values = result.columns.iter().zip(columns).map(|(source, target)| {
+            let padded = matches!(source.type_limit, Some(TypeLimit::Char(_))) && target.type_limit != source.type_limit;
+            let value = match row.get(&source.name).cloned().unwrap_or(Value::Null) {
+                Value::Text(s) if padded => Value::Text(s.trim_end_matches(' ').to_string()),
+                value => value,
+            };
+            value.cast_to(&target.data_type).map_err(|e| ExecutorError::Execution(e.to_string()))
+        }).collect::<Result<Vec<_>, _>>()?;
+        Ok(ValueKey(values))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::executor::testing::{exec, executor, int, query, text};
+    use crate::domain::entity::{DataType, Value};
+
+    #[tokio::test]
+    async fn set_operations_remove_duplicates() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE a (n INTEGER);
+            CREATE TABLE b (n FLOAT);
+            INSERT INTO a VALUES (1), (2), (2), (NULL);
+            INSERT INTO b VALUES (2.0), (3.0), (NULL)
+        ").await;
+
+        let rows = query(&executor, "SELECT n FROM a UNION SELECT n FROM b").await;
+        assert_eq!(rows.len(), 4);
+        let rows = query(&executor, "SELECT n FROM a INTERSECT SELECT n FROM b").await;
+        assert_eq!(rows.len(), 2);
+        let rows = query(&executor, "SELECT n FROM a EXCEPT ALL SELECT n FROM b").await;
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn set_operations_follow_column_collation() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE members (nick TEXT COLLATE nocase);
+            CREATE TABLE guests (nick TEXT);
+            INSERT INTO members VALUES ('Bob'), ('alice');
+            INSERT INTO guests VALUES ('BOB'), ('carol')
+        ").await;
+
+        let rows = query(&executor, "SELECT nick FROM members UNION SELECT nick FROM guests").await;
+        assert_eq!(rows, vec![vec![text("Bob")], vec![text("alice")], vec![text("carol")]]);
+        let rows = query(&executor, "SELECT nick FROM members INTERSECT SELECT nick FROM guests").await;
+        assert_eq!(rows, vec![vec![text("Bob")]]);
+        let rows = query(&executor, "SELECT nick FROM members EXCEPT SELECT nick FROM guests").await;
+        assert_eq!(rows, vec![vec![text("alice")]]);
+
+        // 照合順序は右側のカラムのものでも使う
+        let rows = query(&executor, "SELECT nick FROM guests UNION SELECT nick FROM members").await;
+        assert_eq!(rows, vec![vec![text("BOB")], vec![text("carol")], vec![text("alice")]]);
+    }
+
+    #[tokio::test]
+    async fn set_operations_keep_multiplicity() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE a (n INTEGER);
+            CREATE TABLE b (n INTEGER);
+            INSERT INTO a VALUES (1), (1), (2), (2), (2), (3);
+            INSERT INTO b VALUES (1), (2), (2), (4)
+        ").await;
+
+        for (op, expected) in [
+            ("UNION", vec![1, 2, 3, 4]),
+            ("UNION ALL", vec![1, 1, 1, 2, 2, 2, 2, 2, 3, 4]),
+            ("INTERSECT", vec![1, 2]),
+            // 右側に現れる回数までを残す
+            ("INTERSECT ALL", vec![1, 2, 2]),
+            ("EXCEPT", vec![3]),
+            // 右側に現れる回数だけ取り除く
+            ("EXCEPT ALL", vec![1, 2, 3]),
+        ] {
+            let rows = query(&executor, &format!("SELECT n FROM a {} SELECT n FROM b ORDER BY n", op)).await;
+            let expected: Vec<_> = expected.into_iter().map(|n| vec![int(n)]).collect();
+            assert_eq!(rows, expected, "{}", op);
+        }
+    }
+
+    #[tokio::test]
+    async fn set_operations_unify_column_types() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE a (n INTEGER, s TEXT);
+            CREATE TABLE b (d DECIMAL(5,2), c CHAR(3));
+            INSERT INTO a VALUES (1, 'ab'), (2, 'x');
+            INSERT INTO b VALUES (1.00, 'ab'), (2.50, 'y')
+        ").await;
+        let decimal = |s: &str| Value::Decimal(s.parse().unwrap());
+
+        let result = exec(&executor, "SELECT n FROM a UNION SELECT d FROM b ORDER BY n").await.result_set.unwrap();
+        assert_eq!(result.columns[0].data_type, DataType::Decimal(DataType::MAX_DECIMAL_PRECISION, 2));
+        let rows = query(&executor, "SELECT n FROM a UNION SELECT d FROM b ORDER BY n").await;
+        assert_eq!(rows, vec![vec![decimal("1.00")], vec![decimal("2")], vec![decimal("2.50")]]);
+
+        // CHAR(n) の埋めた空白は TEXT にまとめるときに取り除く
+        let result = exec(&executor, "SELECT s FROM a UNION SELECT c FROM b").await.result_set.unwrap();
+        assert_eq!(result.columns[0].data_type, DataType::Text);
+        assert_eq!(result.columns[0].type_limit, None);
+        let rows = query(&executor, "SELECT s FROM a UNION SELECT c FROM b ORDER BY s").await;
+        assert_eq!(rows, vec![vec![text("ab")], vec![text("x")], vec![text("y")]]);
+        let rows = query(&executor, "SELECT c FROM b INTERSECT ALL SELECT s FROM a").await;
+        assert_eq!(rows, vec![vec![text("ab")]]);
+
+        // 同じ CHAR(n) 同士は空白で埋めた値のまま比較する
+        let rows = query(&executor, "SELECT c FROM b EXCEPT SELECT c FROM b WHERE c = 'y'").await;
+        assert_eq!(rows, vec![vec![text("ab ")]]);
+    }
+}
@@ -0,0 +1,676 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{ExecutorError, QueryContext, QueryExecutor, MAX_VIEW_DEPTH};
+use crate::domain::entity::{Column, DataType, ResultSet, Row, Value, ValueKey};
+use crate::domain::expression::{BinaryOperator, Expression, ValueSet};
+use crate::domain::repository::FilterCondition;
+use crate::infrastructure::parser::{ParsedStatement, SelectItem, SelectStatement};
+
+/// 行ごとに計算した値を格納する隠しカラムの接頭辞（'*' の展開には含まれない）
+pub(super) const HIDDEN_COLUMN_PREFIX: &str = "$";
+
+/// FROM句のテーブルと、そこから参照できるカラム
+struct Scope {
+    /// テーブル名と別名
+    names: Vec<String>,
+    columns: Vec<Column>,
+}
+
+impl Scope {
+    /// カラム参照がこのスコープのカラムを指すかどうか
+    fn owns(&self, name: &str) -> bool {
+        match name.split_once('.') {
+            Some((qualifier, column)) => {
+                self.names.iter().any(|n| n == qualifier) && self.columns.iter().any(|c| c.name == column)
+            },
+            None => self.columns.iter().any(|c| c.name == name),
+        }
+    }
+
+    fn has_name(&self, qualifier: &str) -> bool {
+        self.names.iter().any(|n| n == qualifier)
+    }
+}
+
+/// サブクエリ内のカラム参照が外側のクエリを指すかどうか
+/// （内側のテーブルで解決できる場合は内側を優先する）
+fn is_outer_reference(name: &str, inner: &Scope, outer: &Scope) -> bool {
+    match name.split_once('.') {
+        Some((qualifier, _)) => !inner.has_name(qualifier) && outer.owns(name),
+        None => !inner.owns(name) && outer.owns(name),
+    }
+}
+
+/// サブクエリの種類
+#[derive(Clone)]
+enum SubqueryKind {
+    Scalar,
+    Exists { negated: bool },
+    In { expr: Expression, negated: bool },
+}
+
+/// 行ごとに実行する相関サブクエリ
+pub(super) struct CorrelatedSubquery {
+    /// 結果を格納する隠しカラム
+    column: String,
+    kind: SubqueryKind,
+    query: SelectStatement,
+    /// 外側のクエリを参照するカラム名
+    outer_references: Vec<String>,
+}
+
+/// サブクエリを実行済みの値や行ごとの計算に置き換えたSELECT文
+pub(super) struct ResolvedSelect {
+    pub stmt: SelectStatement,
+    /// WHERE句で使われる相関サブクエリ
+    pub filter_subqueries: Vec<CorrelatedSubquery>,
+    /// SELECT句で使われる相関サブクエリ
+    pub projection_subqueries: Vec<CorrelatedSubquery>,
+}
+
+/// 式から取り出した未実行のサブクエリ
+struct PendingSubquery {
+    /// サブクエリの代わりに式に埋め込んだ隠しカラム
+    column: String,
+    node: Expression,
+    /// WHERE句の条件そのものかどうか（偽とNULLを区別しなくてよい）
+    top_level: bool,
+    in_filter: bool,
+}
+
+/// サブクエリの実行結果
+enum Resolution {
+    /// 実行済みの値や集合で置き換える
+    Replace(Expression),
+    /// 行ごとに実行する
+    PerRow(Box<CorrelatedSubquery>),
+}
+
+impl QueryExecutor {
+    /// SELECT文に含まれるサブクエリを実行する（サブクエリがなければNone）
+    ///
+    /// 相関のないサブクエリは一度だけ実行して値に置き換え、EXISTS / IN の相関サブクエリは
+    /// 等価条件を結合キーとするセミ結合（アンチ結合）に変換する。
+    /// 変換できない相関サブクエリは行ごとに実行する。
+    pub(super) async fn resolve_subqueries(
+        &self,
+        stmt: &SelectStatement,
+        ctx: &QueryContext
+    ) -> Result<Option<ResolvedSelect>, ExecutorError> {
+        if !stmt.contains_subquery() {
+            return Ok(None);
+        }
+        if ctx.depth >= MAX_VIEW_DEPTH {
+            return Err(ExecutorError::Execution("Subqueries are nested too deeply".to_string()));
+        }
+
+        let outer = self.scope_of(stmt, ctx).await?;
+
+        // サブクエリを隠しカラムの参照に置き換えながら取り出す
+        let mut pending = Vec::new();
+        let filter = stmt.filter.as_ref()
+            .map(|filter| map_filter(filter, &mut |expr| extract_subqueries(expr, true, &mut pending)));
+        let projection: Vec<SelectItem> = stmt.projection.iter().map(|item| match item {
+            SelectItem::Expression { expr, .. } if expr.contains_subquery() => SelectItem::Expression {
+                expr: extract_subqueries(expr, false, &mut pending),
+                // 結果カラムの名前は置き換え前の式から決める
+                alias: item.output_name(),
+            },
+            _ => item.clone(),
+        }).collect();
+
+        let mut replacements = HashMap::new();
+        let mut filter_subqueries = Vec::new();
+        let mut projection_subqueries = Vec::new();
+        for subquery in pending {
+            match self.resolve_subquery(&subquery, &outer, ctx).await? {
+                Resolution::Replace(expr) => {
+                    replacements.insert(subquery.column, expr);
+                },
+                Resolution::PerRow(correlated) if subquery.in_filter => filter_subqueries.push(*correlated),
+                Resolution::PerRow(correlated) => projection_subqueries.push(*correlated),
+            }
+        }
+
+        // 実行済みのサブクエリを結果で置き換える（行ごとに実行するものは隠しカラムの参照のまま残す）
+        let mut replace = |expr: &Expression| expr.transform(&mut |node| match node {
+            Expression::Column(name) => replacements.get(name).cloned(),
+            _ => None,
+        });
+        let filter = filter.map(|filter| map_filter(&filter, &mut replace));
+        let projection = projection.into_iter().map(|item| match item {
+            SelectItem::Expression { expr, alias } => SelectItem::Expression { expr: replace(&expr), alias },
+            wildcard => wildcard,
+        }).collect();
+
+        Ok(Some(ResolvedSelect {
+            stmt: SelectStatement {
+                filter,
+                projection,
+                ..stmt.clone()
+            },
+            filter_subqueries,
+            projection_subqueries,
+        }))
+    }
+
+    /// UPDATE / DELETE のWHERE句に含まれるサブクエリを実行し、文字列の定数をカラムの型に変換する
+    pub(super) async fn resolve_filter(
+        &self,
+        table_name: &str,
+        table_alias: Option<&str>,
+        filter: Option<&FilterCondition>
+    ) -> Result<Option<FilterCondition>, ExecutorError> {
+        let stmt = SelectStatement {
+            table_name: table_name.to_string(),
+            table_alias: table_alias.map(String::from),
+            table_function: None,
+            distinct: None,
+            projection: Vec::new(),
+            filter: filter.cloned(),
+            group_by: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            with: Vec::new(),
+            set_operation: None,
+        };
+        let stmt = self.resolve_functions(&stmt)?;
+
+        let filter = match self.resolve_subqueries(&stmt, &QueryContext::default()).await? {
+            None => stmt.filter,
+            Some(resolved) if resolved.filter_subqueries.is_empty() => resolved.stmt.filter,
+            Some(_) => return Err(ExecutorError::Execution(
+                "Correlated subqueries in UPDATE or DELETE must be EXISTS or IN with equality conditions".to_string())),
+        };
+        match filter {
+            Some(filter) => Ok(Some(self.coerce_filter(&filter, &self.repository.get_table(table_name).await?.columns)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 行ごとに相関サブクエリを実行し、結果を隠しカラムとして行に加える
+    pub(super) async fn evaluate_correlated(
+        &self,
+        subqueries: &[CorrelatedSubquery],
+        source: &mut ResultSet,
+        ctx: &QueryContext
+    ) -> Result<(), ExecutorError> {
+        for subquery in subqueries {
+            let mut data_type = match subquery.kind {
+                SubqueryKind::Scalar => DataType::Null,
+                _ => DataType::Boolean,
+            };
+
+            // 外側の値の組が同じ行では結果を再利用する
+            let mut cache: HashMap<ValueKey, Expression> = HashMap::new();
+            for row in &mut source.rows {
+                let outer_values = subquery.outer_references.iter()
+                    .map(|name| Expression::Column(name.clone()).evaluate(row))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let key = ValueKey(outer_values);
+
+                if !cache.contains_key(&key) {
+                    let bound = bind_outer_values(&subquery.query, &subquery.outer_references, &key.0);
+                    let result = self.select_in(&bound, &ctx.nested()).await?;
+                    if let (SubqueryKind::Scalar, Some(column)) = (&subquery.kind, result.columns.first()) {
+                        data_type = column.data_type.clone();
+                    }
+                    cache.insert(key.clone(), subquery_result(&subquery.kind, result)?);
+                }
+
+                let value = cache[&key].evaluate(row)?;
+                row.set(subquery.column.clone(), value);
+            }
+
+            source.columns.push(Column::new(&subquery.column, data_type));
+        }
+
+        Ok(())
+    }
+
+    /// サブクエリを1つ実行するか、行ごとの実行に回す
+    async fn resolve_subquery(
+        &self,
+        subquery: &PendingSubquery,
+        outer: &Scope,
+        ctx: &QueryContext
+    ) -> Result<Resolution, ExecutorError> {
+        let (sql, kind) = match &subquery.node {
+            Expression::ScalarSubquery(query) => (query, SubqueryKind::Scalar),
+            Expression::Exists { query, negated } => (query, SubqueryKind::Exists { negated: *negated }),
+            Expression::InSubquery { expr, query, negated } => {
+                if expr.contains_subquery() {
+                    return Err(ExecutorError::Execution(
+                        "Subqueries on the left-hand side of IN are not supported".to_string()));
+                }
+                (query, SubqueryKind::In { expr: *expr.clone(), negated: *negated })
+            },
+            _ => return Err(ExecutorError::Execution(format!("{} is not a subquery", subquery.node))),
+        };
+
+        let inner = match self.parser.parse(sql)?.into_iter().next() {
+            Some(ParsedStatement::Select(inner)) => inner,
+            _ => return Err(ExecutorError::Execution("Subquery must be a SELECT query".to_string())),
+        };
+        // サブクエリ自身のWITH句は外側のクエリの共通テーブル式に加えて一度だけ評価する
+        let correlated_query = inner.clone();
+        let ctx = self.evaluate_ctes(&inner.with, &ctx.nested()).await?;
+        let inner = SelectStatement { with: Vec::new(), ..inner };
+
+        // 集合演算のサブクエリは外側を参照しないものとして一度だけ実行する
+        if inner.set_operation.is_some() {
+            let result = self.select_in(&inner, &ctx).await?;
+            return Ok(Resolution::Replace(subquery_result(&kind, result)?));
+        }
+
+        let scope = self.scope_of(&inner, &ctx).await?;
+
+        let mut outer_references: Vec<String> = Vec::new();
+        for name in select_references(&inner) {
+            if is_outer_reference(name, &scope, outer) && !outer_references.iter().any(|r| r == name) {
+                outer_references.push(name.to_string());
+            }
+        }
+
+        if outer_references.is_empty() {
+            let result = self.select_in(&inner, &ctx).await?;
+            return Ok(Resolution::Replace(subquery_result(&kind, result)?));
+        }
+
+        if let Some(semi_join) = self.decorrelate(&inner, &kind, subquery.top_level, &scope, outer, &ctx).await? {
+            return Ok(Resolution::Replace(semi_join));
+        }
+
+        Ok(Resolution::PerRow(Box::new(CorrelatedSubquery {
+            column: subquery.column.clone(),
+            kind,
+            query: correlated_query,
+            outer_references,
+        })))
+    }
+
+    /// EXISTS / IN の相関サブクエリをセミ結合に変換する（変換できなければNone）
+    ///
+    /// サブクエリのWHERE句が「内側のカラム = 外側のカラム」の等価条件と外側を参照しない条件の
+    /// AND で構成される場合、外側を参照しない条件だけでサブクエリを一度実行し、結合キーの集合を作る。
+    async fn decorrelate(
+        &self,
+        inner: &SelectStatement,
+        kind: &SubqueryKind,
+        top_level: bool,
+        scope: &Scope,
+        outer: &Scope,
+        ctx: &QueryContext
+    ) -> Result<Option<Expression>, ExecutorError> {
+        // IN はNULLの扱いが変わるため、偽とNULLを区別しないWHERE句の条件の場合だけ変換する
+        let negated = match kind {
+            SubqueryKind::Exists { negated } => *negated,
+            SubqueryKind::In { negated: false, .. } if top_level => false,
+            _ => return Ok(None),
+        };
+        // 利用者定義の集約関数を含むかどうかは関数を解決するまで分からない
+        let inner = &self.resolve_functions(inner)?;
+        if inner.is_aggregate() || inner.limit.is_some() || inner.contains_window() {
+            return Ok(None);
+        }
+
+        let is_outer = |name: &str| is_outer_reference(name, scope, outer);
+        let conjuncts = match &inner.filter {
+            None => Vec::new(),
+            Some(FilterCondition::And(conditions)) => conditions.clone(),
+            Some(condition) => vec![condition.clone()],
+        };
+
+        let mut inner_keys = Vec::new();
+        let mut outer_keys = Vec::new();
+        let mut remaining = Vec::new();
+        for condition in conjuncts {
+            if let FilterCondition::Expression(Expression::BinaryOp { left, op: BinaryOperator::Eq, right }) = &condition {
+                if let (Expression::Column(l), Expression::Column(r)) = (left.as_ref(), right.as_ref()) {
+                    match (is_outer(l), is_outer(r)) {
+                        (false, true) => {
+                            inner_keys.push(Expression::Column(l.clone()));
+                            outer_keys.push(Expression::Column(r.clone()));
+                            continue;
+                        },
+                        (true, false) => {
+                            inner_keys.push(Expression::Column(r.clone()));
+                            outer_keys.push(Expression::Column(l.clone()));
+                            continue;
+                        },
+                        _ => {},
+                    }
+                }
+            }
+
+            if condition.contains_subquery() || condition.referenced_columns().into_iter().any(is_outer) {
+                return Ok(None);
+            }
+            remaining.push(condition);
+        }
+
+        // IN の場合はサブクエリの結果カラムも結合キーに加える
+        if let SubqueryKind::In { expr, .. } = kind {
+            match inner.projection.as_slice() {
+                [SelectItem::Expression { expr: projected, .. }]
+                    if !projected.contains_subquery() && !projected.referenced_columns().into_iter().any(is_outer) => {
+                    inner_keys.push(projected.clone());
+                    outer_keys.push(expr.clone());
+                },
+                _ => return Ok(None),
+            }
+        }
+
+        if inner_keys.is_empty() {
+            return Ok(None);
+        }
+
+        let probe = SelectStatement {
+            table_name: inner.table_name.clone(),
+            table_alias: inner.table_alias.clone(),
+            table_function: inner.table_function.clone(),
+            distinct: None,
+            projection: inner_keys.into_iter().enumerate().map(|(i, expr)| SelectItem::Expression {
+                expr,
+                alias: Some(format!("{}key{}", HIDDEN_COLUMN_PREFIX, i)),
+            }).collect(),
+            filter: match remaining.len() {
+                0 => None,
+                1 => remaining.pop(),
+                _ => Some(FilterCondition::And(remaining)),
+            },
+            group_by: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            with: Vec::new(),
+            set_operation: None,
+        };
+        let result = self.select_in(&probe, ctx).await?;
+
+        let mut set = ValueSet::new();
+        for row in &result.rows {
+            let key: Vec<Value> = result.columns.iter()
+                .map(|c| row.get(&c.name).cloned().unwrap_or(Value::Null))
+                .collect();
+            // NULLを含むキーはどの行とも一致しない
+            if !key.contains(&Value::Null) {
+                set.insert_key(key);
+            }
+        }
+
+        Ok(Some(Expression::SemiJoin {
+            keys: outer_keys,
+            set: Arc::new(set),
+            negated,
+        }))
+    }
+
+    /// SELECT文のFROM句から参照できるカラムを求める
+    async fn scope_of(&self, stmt: &SelectStatement, ctx: &QueryContext) -> Result<Scope, ExecutorError> {
+        let columns = match ctx.ctes.get(&stmt.table_name) {
+            _ if stmt.table_function.is_some() => self.fetch_source(stmt, None, ctx).await?.columns,
+            Some(cte) => cte.columns.clone(),
+            None => match self.repository.get_view(&stmt.table_name).await {
+                Ok(view) if !view.materialized => self.expand_view(&view, None, ctx).await?.columns,
+                _ => self.repository.get_table(&stmt.table_name).await?.columns,
+            },
+        };
+
+        let mut names = vec![stmt.table_name.clone()];
+        names.extend(stmt.table_alias.clone());
+
+        Ok(Scope { names, columns })
+    }
+}
+
+/// 式の中のサブクエリを隠しカラムの参照に置き換え、取り出したサブクエリを記録する
+fn extract_subqueries(expr: &Expression, in_filter: bool, pending: &mut Vec<PendingSubquery>) -> Expression {
+    expr.transform(&mut |node| match node {
+        Expression::ScalarSubquery(_) | Expression::InSubquery { .. } | Expression::Exists { .. } => {
+            let column = format!("{}subquery{}", HIDDEN_COLUMN_PREFIX, pending.len());
+            pending.push(PendingSubquery {
+                column: column.clone(),
+                node: node.clone(),
+                top_level: in_filter && std::ptr::eq(node, expr),
+                in_filter,
+            });
+            Some(Expression::Column(column))
+        },
+        _ => None,
+    })
+}
+
+/// 条件に含まれる式を書き換える
+pub(super) fn map_filter(filter: &FilterCondition, f: &mut dyn FnMut(&Expression) -> Expression) -> FilterCondition {
+    match filter {
+        FilterCondition::Simple { .. } | FilterCondition::SimpleAt { .. } => filter.clone(),
+        FilterCondition::And(conditions) => {
+            FilterCondition::And(conditions.iter().map(|c| map_filter(c, f)).collect())
+        },
+        FilterCondition::Or(conditions) => {
+            FilterCondition::Or(conditions.iter().map(|c| map_filter(c, f)).collect())
+        },
+        FilterCondition::Expression(expr) => FilterCondition::Expression(f(expr)),
+    }
+}
+
+/// SELECT文が参照するカラム名を収集する
+fn select_references(stmt: &SelectStatement) -> Vec<&str> {
+    let mut names: Vec<&str> = stmt.filter.iter().flat_map(|f| f.referenced_columns()).collect();
+    for item in &stmt.projection {
+        if let SelectItem::Expression { expr, .. } = item {
+            names.extend(expr.referenced_columns());
+        }
+    }
+    names.extend(stmt.group_by.iter().map(String::as_str));
+    names
+}
+
+/// サブクエリ内の外側への参照を、外側の行の値で置き換える
+fn bind_outer_values(query: &SelectStatement, references: &[String], values: &[Value]) -> SelectStatement {
+    let lookup = |name: &str| references.iter().position(|r| r == name).map(|i| values[i].clone());
+    let mut bind = |expr: &Expression| expr.transform(&mut |node| match node {
+        Expression::Column(name) => lookup(name).map(Expression::Literal),
+        _ => None,
+    });
+
+    fn bind_filter(
+        filter: &FilterCondition,
+        lookup: &dyn Fn(&str) -> Option<Value>,
+        bind: &mut dyn FnMut(&Expression) -> Expression
+    ) -> FilterCondition {
+        match filter {
+            // 外側のカラムと値の比較は定数になる
+            FilterCondition::Simple { column, .. } | FilterCondition::SimpleAt { column, .. } => match lookup(column) {
+                Some(value) => {
+                    let mut row = Row::new();
+                    row.set(column.clone(), value);
+                    FilterCondition::Expression(Expression::Literal(Value::Boolean(filter.matches(&row))))
+                },
+                None => filter.clone(),
+            },
+            FilterCondition::And(conditions) => {
+                FilterCondition::And(conditions.iter().map(|c| bind_filter(c, lookup, bind)).collect())
+            },
+            FilterCondition::Or(conditions) => {
+                FilterCondition::Or(conditions.iter().map(|c| bind_filter(c, lookup, bind)).collect())
+            },
+            FilterCondition::Expression(expr) => FilterCondition::Expression(bind(expr)),
+        }
+    }
+
+    SelectStatement {
+        filter: query.filter.as_ref().map(|filter| bind_filter(filter, &lookup, &mut bind)),
+        projection: query.projection.iter().map(|item| match item {
+            SelectItem::Expression { expr, alias } => SelectItem::Expression {
+                expr: bind(expr),
+                alias: alias.clone(),
+            },
+            SelectItem::Wildcard => SelectItem::Wildcard,
+        }).collect(),
+        ..query.clone()
+    }
+}
+
+/// 実行したサブクエリの結果を、式の中で使える値や集合に変換する
+fn subquery_result(kind: &SubqueryKind, result: ResultSet) -> Result<Expression, ExecutorError> {
+    let single_column = |result: &ResultSet| match result.columns.as_slice() {
+        [column] => Ok(column.name.clone()),
+        _ => Err(ExecutorError::Execution("Subquery must return only one column".to_string())),
+    };
+
+    match kind {
+        SubqueryKind::Scalar => {
+            let column = single_column(&result)?;
+            if result.rows.len() > 1 {
+                return Err(ExecutorError::Execution(
+                    "More than one row returned by a subquery used as an expression".to_string()));
+            }
+            let value = result.rows.first()
+                .and_then(|row| row.get(&column).cloned())
+                .unwrap_or(Value::Null);
+            Ok(Expression::Literal(value))
+        },
+        SubqueryKind::Exists { negated } => {
+            Ok(Expression::Literal(Value::Boolean(result.rows.is_empty() == *negated)))
+        },
+        SubqueryKind::In { expr, negated } => {
+            let column = single_column(&result)?;
+            let set = result.rows.iter()
+                .map(|row| row.get(&column).cloned().unwrap_or(Value::Null))
+                .collect::<ValueSet>();
+            Ok(Expression::InSet {
+                expr: Box::new(expr.clone()),
+                set: Arc::new(set),
+                negated: *negated,
+            })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::executor::testing::{error, exec, executor, int, query, text};
+    use crate::application::QueryExecutor;
+
+    async fn setup() -> QueryExecutor {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, score FLOAT);
+            INSERT INTO users VALUES (1, 'alice', 1.0), (2, 'bob', 2.0), (3, 'carol', 3.0), (4, 'dave', NULL);
+            CREATE TABLE orders (oid INTEGER PRIMARY KEY, user_id INTEGER, amt FLOAT);
+            INSERT INTO orders VALUES (10, 1, 1.0), (11, 1, 3.0), (12, 3, 2.0)
+        ").await;
+        executor
+    }
+
+    #[tokio::test]
+    async fn scalar_subquery() {
+        let executor = setup().await;
+        let rows = query(&executor, "SELECT name FROM users WHERE id = (SELECT MAX(user_id) FROM orders)").await;
+        assert_eq!(rows, vec![vec![text("carol")]]);
+
+        // 行がなければNULL
+        let rows = query(&executor, "SELECT name FROM users WHERE id = (SELECT user_id FROM orders WHERE oid = 99)").await;
+        assert!(rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn scalar_subquery_with_many_rows_fails() {
+        let executor = setup().await;
+        let message = error(&executor, "SELECT name FROM users WHERE id = (SELECT user_id FROM orders)").await;
+        assert!(message.contains("More than one row"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn in_and_not_in_subquery() {
+        let executor = setup().await;
+        let rows = query(&executor, "SELECT id FROM users WHERE id IN (SELECT user_id FROM orders) ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(3)]]);
+
+        let rows = query(&executor, "SELECT id FROM users WHERE id NOT IN (SELECT user_id FROM orders) ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(2)], vec![int(4)]]);
+    }
+
+    #[tokio::test]
+    async fn not_in_with_null_matches_nothing() {
+        let executor = setup().await;
+        let rows = query(&executor, "SELECT id FROM users WHERE id NOT IN (SELECT score FROM users)").await;
+        assert!(rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn in_compares_numbers_of_different_types() {
+        let executor = setup().await;
+        let rows = query(&executor, "SELECT name FROM users WHERE score IN (2, 3) ORDER BY id").await;
+        assert_eq!(rows, vec![vec![text("bob")], vec![text("carol")]]);
+
+        let rows = query(&executor, "SELECT name FROM users WHERE score IN (SELECT oid - 9 FROM orders) ORDER BY id").await;
+        assert_eq!(rows, vec![vec![text("alice")], vec![text("bob")], vec![text("carol")]]);
+
+        let rows = query(&executor, "SELECT name FROM users WHERE id IN (SELECT amt FROM orders) ORDER BY id").await;
+        assert_eq!(rows, vec![vec![text("alice")], vec![text("bob")], vec![text("carol")]]);
+    }
+
+    #[tokio::test]
+    async fn correlated_exists() {
+        let executor = setup().await;
+        let rows = query(&executor,
+            "SELECT u.name FROM users u WHERE EXISTS (SELECT 1 FROM orders o WHERE o.user_id = u.id) ORDER BY u.id").await;
+        assert_eq!(rows, vec![vec![text("alice")], vec![text("carol")]]);
+
+        let rows = query(&executor,
+            "SELECT u.name FROM users u WHERE NOT EXISTS (SELECT 1 FROM orders o WHERE o.user_id = u.id) ORDER BY u.id").await;
+        assert_eq!(rows, vec![vec![text("bob")], vec![text("dave")]]);
+    }
+
+    #[tokio::test]
+    async fn correlated_exists_compares_numbers_of_different_types() {
+        let executor = setup().await;
+        let rows = query(&executor,
+            "SELECT u.id FROM users u WHERE EXISTS (SELECT 1 FROM orders o WHERE o.amt = u.id) ORDER BY u.id").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)], vec![int(3)]]);
+
+        // NULLのキーはどの行とも一致しない
+        let rows = query(&executor,
+            "SELECT u.id FROM users u WHERE EXISTS (SELECT 1 FROM orders o WHERE o.amt = u.score) ORDER BY u.id").await;
+        assert_eq!(rows, vec![vec![int(1)], vec![int(2)], vec![int(3)]]);
+        let rows = query(&executor,
+            "SELECT u.id FROM users u WHERE NOT EXISTS (SELECT 1 FROM orders o WHERE o.amt = u.score)").await;
+        assert_eq!(rows, vec![vec![int(4)]]);
+    }
+
+    #[tokio::test]
+    async fn subquery_in_select_list() {
+        let executor = setup().await;
+        let rows = query(&executor,
+            "SELECT u.id, (SELECT COUNT(*) FROM orders o WHERE o.user_id = u.id) FROM users u ORDER BY u.id").await;
+        assert_eq!(rows, vec![
+            vec![int(1), int(2)],
+            vec![int(2), int(0)],
+            vec![int(3), int(1)],
+            vec![int(4), int(0)],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn in_subquery_and_exists_follow_column_collation() {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE members (id INTEGER, nick TEXT COLLATE nocase);
+            CREATE TABLE bans (nick TEXT);
+            INSERT INTO members VALUES (1, 'Bob'), (2, 'alice'), (3, NULL);
+            INSERT INTO bans VALUES ('BOB')
+        ").await;
+
+        let rows = query(&executor, "SELECT id FROM members WHERE nick IN (SELECT nick FROM bans)").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+        let rows = query(&executor, "SELECT id FROM members m WHERE EXISTS (SELECT 1 FROM bans b WHERE b.nick = m.nick) ORDER BY id").await;
+        assert_eq!(rows, vec![vec![int(1)]]);
+
+        // 明示的に指定した照合順序はカラムの照合順序より優先する
+        let rows = query(&executor, "SELECT id FROM members WHERE nick COLLATE \"C\" IN (SELECT nick FROM bans)").await;
+        assert!(rows.is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use crate::application::executor::{ExecutionResult, ExecutorError, QueryExecutor};
+use crate::domain::entity::Value;
+use crate::infrastructure::parser::SqlParser;
+use crate::infrastructure::repository::MemoryTableRepository;
+use crate::infrastructure::storage::MemoryStorage;
+
+/// 空のメモリストレージの上に実行器を作る
+pub(crate) fn executor() -> QueryExecutor {
+    let storage = Arc::new(MemoryStorage::new());
+    QueryExecutor::new(Arc::new(MemoryTableRepository::new(storage)))
+}
+
+/// セミコロンで区切った文を順に実行し、最後の文の結果を返す
+pub(crate) async fn run(executor: &QueryExecutor, sql: &str) -> Result<ExecutionResult, ExecutorError> {
+    let statements = SqlParser::new().parse(sql)?;
+    let mut last = None;
+    for statement in &statements {
+        last = Some(executor.execute(statement).await?);
+    }
+    Ok(last.expect("no statement to execute"))
+}
+
+/// 文を実行し、失敗した場合はSQLとエラーを表示して中断する
+pub(crate) async fn exec(executor: &QueryExecutor, sql: &str) -> ExecutionResult {
+    match run(executor, sql).await {
+        Ok(result) => result,
+        Err(e) => panic!("{}: {}", sql, e),
+    }
+}
+
+/// 問い合わせを実行し、行をカラムの順に並べた値として返す
+pub(crate) async fn query(executor: &QueryExecutor, sql: &str) -> Vec<Vec<Value>> {
+    let result = exec(executor, sql).await;
+    let result_set = result.result_set.unwrap_or_else(|| panic!("{}: no result set", sql));
+    result_set.rows.iter()
+        .map(|row| result_set.columns.iter()
+            .map(|c| row.get(&c.name).cloned().unwrap_or(Value::Null))
+            .collect())
+        .collect()
+}
+
+/// 文の実行が失敗することを確かめ、エラーメッセージを返す
+pub(crate) async fn error(executor: &QueryExecutor, sql: &str) -> String {
+    match run(executor, sql).await {
+        Ok(_) => panic!("{}: expected an error", sql),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// 整数の値を作る
+pub(crate) fn int(i: i64) -> Value {
+    Value::Integer(i)
+}
+
+/// 文字列の値を作る
+pub(crate) fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
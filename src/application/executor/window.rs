@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use super::subquery::HIDDEN_COLUMN_PREFIX;
+use super::{group_aggregates, project, ExecutorError};
+use crate::application::aggregate::Accumulator;
+use crate::domain::entity::{Column, ResultSet, Row, Value, ValueKey};
+use crate::domain::expression::{
+    compare_sort_keys, Expression, FrameBound, FrameUnits, OrderByExpr, WindowFrame, WindowFunction, WindowSpec
+};
+use crate::infrastructure::parser::{SelectItem, SelectStatement};
+
+/// ウィンドウ関数を含むSELECT文を射影する
+///
+/// 集約を伴うクエリは先にグループ化し、グループごとの行に対してウィンドウ関数を計算する。
+/// 計算した値は隠しカラムとして行に加え、ウィンドウ関数の呼び出しをその参照に置き換えてから射影する。
+pub(super) fn project_windows(stmt: &SelectStatement, source: ResultSet) -> Result<ResultSet, ExecutorError> {
+    let (stmt, mut source) = if stmt.is_aggregate() {
+        group_aggregates(stmt, source)?
+    } else {
+        (stmt.clone(), source)
+    };
+
+    let mut windows = Vec::new();
+    let mut extract = |expr: &Expression| expr.transform(&mut |node| match node {
+        Expression::Window { .. } => {
+            windows.push(node.clone());
+            Some(Expression::Column(format!("{}window{}", HIDDEN_COLUMN_PREFIX, windows.len() - 1)))
+        },
+        _ => None,
+    });
+    let projection = stmt.projection.iter().map(|item| match item {
+        SelectItem::Expression { expr, .. } if expr.contains_window() => SelectItem::Expression {
+            expr: extract(expr),
+            // 結果カラムの名前は置き換え前の式から決める
+            alias: item.output_name(),
+        },
+        _ => item.clone(),
+    }).collect();
+    let order_by = stmt.order_by.iter().map(|item| OrderByExpr {
+        expr: extract(&item.expr),
+        ..item.clone()
+    }).collect();
+
+    for (i, window) in windows.iter().enumerate() {
+        let Expression::Window { function, args, spec } = window else {
+            unreachable!("only window functions are extracted");
+        };
+
+        let data_type = window.data_type(&source.columns)?;
+        let values = evaluate_window(*function, args, spec, &source.rows)?;
+
+        let name = format!("{}window{}", HIDDEN_COLUMN_PREFIX, i);
+        for (row, value) in source.rows.iter_mut().zip(values) {
+            row.set(name.clone(), value);
+        }
+        source.columns.push(Column::new(&name, data_type));
+    }
+
+    project(&SelectStatement { projection, order_by, ..stmt }, source)
+}
+
+/// 各行についてウィンドウ関数の値を求める（結果は入力の行と同じ順序）
+fn evaluate_window(
+    function: WindowFunction,
+    args: &[Expression],
+    spec: &WindowSpec,
+    rows: &[Row]
+) -> Result<Vec<Value>, ExecutorError> {
+    // パーティションごとに行の位置を集め、並べ替えのキーを求める
+    let mut partition_index: HashMap<ValueKey, usize> = HashMap::new();
+    let mut partitions: Vec<Vec<usize>> = Vec::new();
+    let mut sort_keys = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        let key = spec.partition_by.iter()
+            .map(|expr| expr.evaluate(row))
+            .collect::<Result<Vec<_>, _>>()?;
+        let partition = *partition_index.entry(ValueKey(key)).or_insert_with(|| {
+            partitions.push(Vec::new());
+            partitions.len() - 1
+        });
+        partitions[partition].push(i);
+
+        sort_keys.push(spec.order_by.iter()
+            .map(|item| item.expr.evaluate(row))
+            .collect::<Result<Vec<_>, _>>()?);
+    }
+
+    let mut values = vec![Value::Null; rows.len()];
+    for mut partition in partitions {
+        partition.sort_by(|&a, &b| compare_sort_keys(&spec.order_by, &sort_keys[a], &sort_keys[b]));
+        let peers = |a: usize, b: usize| {
+            compare_sort_keys(&spec.order_by, &sort_keys[partition[a]], &sort_keys[partition[b]]).is_eq()
+        };
+
+        match function {
+            WindowFunction::RowNumber => {
+                for (position, &i) in partition.iter().enumerate() {
+                    values[i] = Value::Integer(position as i64 + 1);
+                }
+            },
+            WindowFunction::Rank | WindowFunction::DenseRank => {
+                let (mut rank, mut dense_rank) = (0, 0);
+                for position in 0..partition.len() {
+                    if position == 0 || !peers(position - 1, position) {
+                        rank = position + 1;
+                        dense_rank += 1;
+                    }
+                    let value = if function == WindowFunction::Rank { rank } else { dense_rank };
+                    values[partition[position]] = Value::Integer(value as i64);
+                }
+            },
+            WindowFunction::Lag | WindowFunction::Lead => {
+                for position in 0..partition.len() {
+                    let row = &rows[partition[position]];
+                    let offset = match args.get(1).map(|offset| offset.evaluate(row)).transpose()? {
+                        None => 1,
+                        Some(Value::Integer(n)) if n >= 0 => n as usize,
+                        Some(other) => return Err(ExecutorError::Execution(format!(
+                            "{} offset must be a non-negative integer, got {}", function, other))),
+                    };
+                    let target = if function == WindowFunction::Lag {
+                        position.checked_sub(offset)
+                    } else {
+                        position.checked_add(offset).filter(|&target| target < partition.len())
+                    };
+
+                    values[partition[position]] = match (target, args.get(2)) {
+                        (Some(target), _) => args[0].evaluate(&rows[partition[target]])?,
+                        (None, Some(default)) => default.evaluate(row)?,
+                        (None, None) => Value::Null,
+                    };
+                }
+            },
+            WindowFunction::FirstValue | WindowFunction::LastValue | WindowFunction::Aggregate(_) => {
+                // 引数の値（COUNT(*) の場合はすべての行を数えるための非NULLの値）
+                let arguments = partition.iter().map(|&i| match args.first() {
+                    Some(arg) => arg.evaluate(&rows[i]),
+                    None => Ok(Value::Boolean(true)),
+                }).collect::<Result<Vec<_>, _>>()?;
+
+                // 同順位の行の範囲（RANGE の CURRENT ROW に使う）
+                let mut peer_start = vec![0; partition.len()];
+                for position in 1..partition.len() {
+                    peer_start[position] = if peers(position - 1, position) { peer_start[position - 1] } else { position };
+                }
+                let mut peer_end = vec![partition.len(); partition.len()];
+                for position in (0..partition.len().saturating_sub(1)).rev() {
+                    peer_end[position] = if peers(position, position + 1) { peer_end[position + 1] } else { position + 1 };
+                }
+
+                let frame = spec.frame.unwrap_or(if spec.order_by.is_empty() {
+                    WindowFrame {
+                        units: FrameUnits::Rows,
+                        start: FrameBound::UnboundedPreceding,
+                        end: FrameBound::UnboundedFollowing,
+                    }
+                } else {
+                    WindowFrame {
+                        units: FrameUnits::Range,
+                        start: FrameBound::UnboundedPreceding,
+                        end: FrameBound::CurrentRow,
+                    }
+                });
+
+                // 直前の行とフレームの開始位置が同じで終了位置が後ろなら、集約の途中状態を引き継ぐ
+                let mut accumulator: Option<(usize, usize, Accumulator)> = None;
+                for position in 0..partition.len() {
+                    let (start, end) = frame_range(&frame, position, partition.len(), &peer_start, &peer_end);
+
+                    values[partition[position]] = match function {
+                        WindowFunction::FirstValue if start < end => arguments[start].clone(),
+                        WindowFunction::LastValue if start < end => arguments[end - 1].clone(),
+                        WindowFunction::Aggregate(aggregate) => {
+                            let (from, mut state) = match accumulator.take() {
+                                Some((s, e, state)) if s == start && e <= end => (e, state),
+                                _ => (start, Accumulator::new(aggregate)),
+                            };
+                            for value in arguments.iter().take(end).skip(from) {
+                                state.update(value)?;
+                            }
+                            let value = state.finish()?;
+                            accumulator = Some((start, end.max(from), state));
+                            value
+                        },
+                        _ => Value::Null,
+                    };
+                }
+            },
+        }
+    }
+
+    Ok(values)
+}
+
+/// パーティション内の位置に対するフレームの範囲を求める（終了位置は含まない。空の場合もある）
+fn frame_range(
+    frame: &WindowFrame,
+    position: usize,
+    len: usize,
+    peer_start: &[usize],
+    peer_end: &[usize]
+) -> (usize, usize) {
+    let start = match frame.start {
+        FrameBound::UnboundedPreceding => 0,
+        FrameBound::Preceding(n) => position.saturating_sub(n),
+        FrameBound::CurrentRow if frame.units == FrameUnits::Range => peer_start[position],
+        FrameBound::CurrentRow => position,
+        FrameBound::Following(n) => position.saturating_add(n).min(len),
+        FrameBound::UnboundedFollowing => len,
+    };
+    let end = match frame.end {
+        FrameBound::UnboundedPreceding => 0,
+        FrameBound::Preceding(n) => (position + 1).saturating_sub(n),
+        FrameBound::CurrentRow if frame.units == FrameUnits::Range => peer_end[position],
+        FrameBound::CurrentRow => position + 1,
+        FrameBound::Following(n) => position.saturating_add(n).saturating_add(1).min(len),
+        FrameBound::UnboundedFollowing => len,
+    };
+
+    (start, end.max(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::executor::testing::{error, exec, executor, int, query};
+    use crate::application::executor::QueryExecutor;
+    use crate::domain::entity::Value;
+
+    async fn orders() -> QueryExecutor {
+        let executor = executor();
+        exec(&executor, "
+            CREATE TABLE orders (id INTEGER, customer TEXT, amount INTEGER);
+            INSERT INTO orders VALUES (1, 'alice', 10), (2, 'bob', 20), (3, 'alice', 30), (4, 'bob', 20), (5, 'carol', NULL)
+        ").await;
+        executor
+    }
+
+    #[tokio::test]
+    async fn ranking_functions_follow_partition_and_order() {
+        let executor = orders().await;
+
+        let rows = query(&executor, "
+            SELECT ROW_NUMBER() OVER (PARTITION BY customer ORDER BY id) AS rn,
+                   RANK() OVER (ORDER BY amount) AS r,
+                   DENSE_RANK() OVER (ORDER BY amount) AS d
+            FROM orders ORDER BY id
+        ").await;
+        // NULL は昇順で最後に並ぶ
+        assert_eq!(rows, vec![
+            vec![int(1), int(1), int(1)],
+            vec![int(1), int(2), int(2)],
+            vec![int(2), int(4), int(3)],
+            vec![int(2), int(2), int(2)],
+            vec![int(1), int(5), int(4)],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn aggregate_and_offset_functions_over_windows() {
+        let executor = orders().await;
+
+        let rows = query(&executor, "
+            SELECT SUM(amount) OVER (PARTITION BY customer) AS total,
+                   SUM(amount) OVER (ORDER BY id) AS running,
+                   LAG(amount) OVER (ORDER BY id) AS prev,
+                   LEAD(amount, 2, 0) OVER (ORDER BY id) AS next2
+            FROM orders ORDER BY id
+        ").await;
+        assert_eq!(rows, vec![
+            vec![int(40), int(10), Value::Null, int(30)],
+            vec![int(40), int(30), int(10), int(20)],
+            vec![int(40), int(60), int(20), Value::Null],
+            vec![int(40), int(80), int(30), int(0)],
+            vec![Value::Null, int(80), int(20), int(0)],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn window_function_errors() {
+        let executor = orders().await;
+
+        let message = error(&executor, "SELECT id FROM orders WHERE ROW_NUMBER() OVER (ORDER BY id) = 1").await;
+        assert!(message.contains("Window functions are not allowed in WHERE"), "{}", message);
+        let message = error(&executor, "SELECT NTILE(2) OVER (ORDER BY id) FROM orders").await;
+        assert!(message.contains("Unknown window function: NTILE"), "{}", message);
+    }
+}
@@ -0,0 +1,457 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{FixedOffset, NaiveDateTime, NaiveTime};
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::domain::entity::{DataType, Interval, Value};
+use crate::domain::expression::{Expression, ExpressionError};
+use crate::domain::function::{ScalarFunction, ScalarFunctionRef, UserAggregate, UserAggregateRef};
+
+mod array;
+mod datetime;
+mod json;
+mod math;
+mod string;
+mod udf;
+mod uuid;
+
+pub use udf::{AggregateUdf, ScalarUdf};
+
+/// 名前で呼び出せるスカラー関数と利用者定義の集約関数の一覧（名前の大文字小文字は区別しない）
+#[derive(Clone, Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, Arc<dyn ScalarFunction>>,
+    aggregates: HashMap<String, Arc<dyn UserAggregate>>,
+}
+
+impl FunctionRegistry {
+    /// 関数を1つも含まないレジストリを作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 組み込み関数を登録したレジストリを作成する
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        let builtins = string::functions().into_iter()
+            .chain(math::functions())
+            .chain(datetime::functions())
+            .chain(json::functions())
+            .chain(uuid::functions())
+            .chain(array::functions());
+        for builtin in builtins {
+            let aliases = builtin.aliases;
+            let function: Arc<dyn ScalarFunction> = Arc::new(builtin);
+            for alias in aliases {
+                registry.functions.insert(alias.to_string(), function.clone());
+            }
+            registry.register(function);
+        }
+        registry
+    }
+
+    /// 関数を登録する（同じ名前の関数があれば置き換える）
+    pub fn register(&mut self, function: Arc<dyn ScalarFunction>) {
+        self.functions.insert(function.name().to_uppercase(), function);
+    }
+
+    /// 利用者定義の集約関数を登録する（同じ名前のスカラー関数があればそちらを優先する）
+    ///
+    /// COUNT や SUM などの組み込みの集約関数と同じ名前では呼び出せない。
+    pub fn register_aggregate(&mut self, function: Arc<dyn UserAggregate>) {
+        self.aggregates.insert(function.name().to_uppercase(), function);
+    }
+
+    /// 名前から関数を取得する
+    pub fn get(&self, name: &str) -> Option<Arc<dyn ScalarFunction>> {
+        self.functions.get(&name.to_uppercase()).cloned()
+    }
+
+    /// 名前から利用者定義の集約関数を取得する
+    pub fn get_aggregate(&self, name: &str) -> Option<Arc<dyn UserAggregate>> {
+        self.aggregates.get(&name.to_uppercase()).cloned()
+    }
+
+    /// 式に含まれる関数呼び出しを登録された関数に結び付ける（見つからない関数はエラー）
+    ///
+    /// 利用者定義の集約関数の呼び出しは `Expression::UserAggregate` に置き換える。
+    /// キャストとタイムゾーンに依存する関数は `time_zone` で日時を扱うようにする。
+    pub fn resolve(&self, expr: &Expression, time_zone: FixedOffset) -> Result<Expression, ExpressionError> {
+        let mut error = None;
+        let resolved = expr.transform(&mut |node| {
+            let resolved = match node {
+                Expression::Function { name, args, .. } => self.resolve_call(name, args, time_zone),
+                Expression::Cast { expr, data_type, .. } => self.resolve(expr, time_zone).map(|expr| Expression::Cast {
+                    expr: Box::new(expr),
+                    data_type: data_type.clone(),
+                    time_zone,
+                }),
+                _ => return None,
+            };
+            match resolved {
+                Ok(resolved) => Some(resolved),
+                Err(e) => {
+                    error.get_or_insert(e);
+                    Some(node.clone())
+                },
+            }
+        });
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(resolved),
+        }
+    }
+
+    /// 関数呼び出しを名前と引数から解決する
+    fn resolve_call(&self, name: &str, args: &[Expression], time_zone: FixedOffset) -> Result<Expression, ExpressionError> {
+        let args: Vec<Expression> = args.iter().map(|arg| self.resolve(arg, time_zone)).collect::<Result<_, _>>()?;
+
+        if let Some(function) = self.get(name) {
+            let function = function.in_time_zone(time_zone).unwrap_or(function);
+            return Ok(Expression::Function {
+                name: name.to_string(),
+                args,
+                function: Some(ScalarFunctionRef::new(function)),
+            });
+        }
+
+        let function = self.get_aggregate(name)
+            .ok_or_else(|| ExpressionError::UnknownFunction(name.to_string()))?;
+        if args.iter().any(Expression::contains_aggregate) {
+            return Err(ExpressionError::InvalidOperation(
+                "Nested aggregate functions are not supported".to_string()));
+        }
+        if args.iter().any(Expression::contains_window) {
+            return Err(ExpressionError::InvalidOperation(
+                "Window functions cannot be used inside aggregate functions".to_string()));
+        }
+        Ok(Expression::UserAggregate { function: UserAggregateRef::new(function), args })
+    }
+}
+
+/// 組み込み関数の引数として受け付ける型（NULLはどの型としても受け付ける）
+#[derive(Debug, Clone, Copy)]
+enum Param {
+    Any,
+    Text,
+    /// 文字列またはバイト列
+    TextOrBlob,
+    Integer,
+    Numeric,
+    Timestamp,
+    TimestampOrInterval,
+    /// JSON（文字列はJSONとして解析する）
+    Json,
+    /// 任意の要素の型の配列
+    Array,
+}
+
+impl Param {
+    fn accepts(&self, data_type: &DataType) -> bool {
+        match self {
+            Param::Any => true,
+            Param::Text => matches!(data_type, DataType::Text | DataType::Null),
+            Param::TextOrBlob => matches!(data_type, DataType::Text | DataType::Blob | DataType::Null),
+            Param::Integer => matches!(data_type, DataType::Integer | DataType::Null),
+            Param::Numeric => {
+                matches!(data_type, DataType::Integer | DataType::Float | DataType::Decimal(..) | DataType::Null)
+            },
+            Param::Timestamp => {
+                matches!(data_type, DataType::Timestamp | DataType::TimestampTz | DataType::Date | DataType::Null)
+            },
+            Param::TimestampOrInterval => matches!(data_type,
+                DataType::Timestamp | DataType::TimestampTz | DataType::Date | DataType::Time |
+                DataType::Interval | DataType::Null),
+            Param::Json => matches!(data_type, DataType::Json | DataType::Text | DataType::Null),
+            Param::Array => matches!(data_type, DataType::Array(_) | DataType::Null),
+        }
+    }
+}
+
+/// 組み込み関数の結果の型
+#[derive(Debug, Clone)]
+enum Returns {
+    Type(DataType),
+    /// 1番目の引数と同じ型
+    SameAsFirst,
+    /// 引数にFLOATがあればFLOAT、DECIMALがあればDECIMAL、すべて整数ならINTEGER
+    Numeric,
+    /// 引数にTIMESTAMPTZがあればTIMESTAMPTZ、そうでなければTIMESTAMP
+    Timestamp,
+}
+
+/// 組み込み関数の本体
+#[derive(Clone, Copy)]
+enum Body {
+    Plain(fn(&[Value]) -> Result<Value, ExpressionError>),
+    /// セッションのタイムゾーンで日時を扱う
+    Zoned(fn(&[Value], FixedOffset) -> Result<Value, ExpressionError>),
+}
+
+/// 組み込みのスカラー関数
+#[derive(Clone)]
+struct BuiltinFunction {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    params: &'static [Param],
+    /// 省略できない引数の数（残りは省略できる）
+    required: usize,
+    /// 可変長引数の型（Noneの場合は params の数まで）
+    rest: Option<Param>,
+    returns: Returns,
+    /// NULLの引数を含む場合は呼び出さずにNULLを返す
+    strict: bool,
+    body: Body,
+    /// Body::Zoned の本体に渡すタイムゾーン
+    time_zone: FixedOffset,
+}
+
+impl BuiltinFunction {
+    fn new(
+        name: &'static str,
+        params: &'static [Param],
+        returns: Returns,
+        body: fn(&[Value]) -> Result<Value, ExpressionError>
+    ) -> Self {
+        Self::with_body(name, params, returns, Body::Plain(body))
+    }
+
+    /// セッションのタイムゾーンで日時を扱う関数を作る（解決されるまではUTCで扱う）
+    fn zoned(
+        name: &'static str,
+        params: &'static [Param],
+        returns: Returns,
+        body: fn(&[Value], FixedOffset) -> Result<Value, ExpressionError>
+    ) -> Self {
+        Self::with_body(name, params, returns, Body::Zoned(body))
+    }
+
+    fn with_body(name: &'static str, params: &'static [Param], returns: Returns, body: Body) -> Self {
+        Self {
+            name,
+            aliases: &[],
+            params,
+            required: params.len(),
+            rest: None,
+            returns,
+            strict: true,
+            body,
+            time_zone: FixedOffset::east_opt(0).unwrap(),
+        }
+    }
+
+    /// 別名を付ける
+    fn aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// 末尾の引数を省略できるようにする
+    fn optional(mut self, count: usize) -> Self {
+        self.required = self.params.len() - count;
+        self
+    }
+
+    /// 指定した型の引数をいくつでも受け付ける
+    fn variadic(mut self, param: Param) -> Self {
+        self.rest = Some(param);
+        self
+    }
+
+    /// NULLの引数も関数の本体に渡す
+    fn lenient(mut self) -> Self {
+        self.strict = false;
+        self
+    }
+}
+
+impl ScalarFunction for BuiltinFunction {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn return_type(&self, args: &[DataType]) -> Result<DataType, ExpressionError> {
+        let too_many = self.rest.is_none() && args.len() > self.params.len();
+        if args.len() < self.required || too_many {
+            let expected = match (self.rest, self.required == self.params.len()) {
+                (Some(_), _) => format!("at least {}", self.required),
+                (None, true) => self.required.to_string(),
+                (None, false) => format!("{} to {}", self.required, self.params.len()),
+            };
+            return Err(ExpressionError::InvalidOperation(format!(
+                "Function {} expects {} arguments, got {}", self.name, expected, args.len())));
+        }
+
+        for (i, data_type) in args.iter().enumerate() {
+            let param = self.params.get(i).copied().or(self.rest).unwrap_or(Param::Any);
+            if !param.accepts(data_type) {
+                return Err(ExpressionError::InvalidOperation(format!(
+                    "Function {} does not accept {} as argument {}", self.name, data_type, i + 1)));
+            }
+        }
+
+        Ok(match &self.returns {
+            Returns::Type(data_type) => data_type.clone(),
+            Returns::SameAsFirst => args.first().cloned().unwrap_or(DataType::Null),
+            Returns::Numeric if args.contains(&DataType::Float) => DataType::Float,
+            Returns::Numeric => args.iter()
+                .try_fold(DataType::Integer, |result, arg| result.common_type(arg))
+                .unwrap_or(DataType::Integer),
+            Returns::Timestamp if args.contains(&DataType::TimestampTz) => DataType::TimestampTz,
+            Returns::Timestamp => DataType::Timestamp,
+        })
+    }
+
+    fn invoke(&self, args: &[Value]) -> Result<Value, ExpressionError> {
+        if self.strict && args.contains(&Value::Null) {
+            return Ok(Value::Null);
+        }
+        match self.body {
+            Body::Plain(body) => body(args),
+            Body::Zoned(body) => body(args, self.time_zone),
+        }
+    }
+
+    fn in_time_zone(&self, time_zone: FixedOffset) -> Option<Arc<dyn ScalarFunction>> {
+        matches!(self.body, Body::Zoned(_)).then(|| Arc::new(Self { time_zone, ..self.clone() }) as Arc<dyn ScalarFunction>)
+    }
+}
+
+/// 引数の値が期待した型でない場合のエラー
+fn argument_error(value: &Value, expected: &str) -> ExpressionError {
+    ExpressionError::InvalidOperation(format!("Expected {}, got {}", expected, value.data_type()))
+}
+
+fn text(value: &Value) -> Result<&str, ExpressionError> {
+    match value {
+        Value::Text(s) => Ok(s),
+        other => Err(argument_error(other, "TEXT")),
+    }
+}
+
+fn integer(value: &Value) -> Result<i64, ExpressionError> {
+    match value {
+        Value::Integer(i) => Ok(*i),
+        other => Err(argument_error(other, "INTEGER")),
+    }
+}
+
+fn float(value: &Value) -> Result<f64, ExpressionError> {
+    match value {
+        Value::Integer(i) => Ok(*i as f64),
+        Value::Float(f) => Ok(*f),
+        Value::Decimal(d) => d.to_f64().ok_or_else(|| argument_error(value, "FLOAT")),
+        other => Err(argument_error(other, "FLOAT")),
+    }
+}
+
+/// 日時を取り出す（タイムゾーン付きの日時は `time_zone` の日時、日付はその日の0時とみなす）
+fn timestamp(value: &Value, time_zone: FixedOffset) -> Result<NaiveDateTime, ExpressionError> {
+    match value {
+        Value::Timestamp(dt) => Ok(*dt),
+        Value::TimestampTz(dt) => Ok(dt.with_timezone(&time_zone).naive_local()),
+        Value::Date(d) => Ok(d.and_time(NaiveTime::MIN)),
+        other => Err(argument_error(other, "TIMESTAMP")),
+    }
+}
+
+/// JSONの値を取り出す（文字列はJSONとして解析する）
+fn json(value: &Value) -> Result<Cow<'_, serde_json::Value>, ExpressionError> {
+    match value {
+        Value::Json(json) => Ok(Cow::Borrowed(json)),
+        Value::Text(s) => serde_json::from_str(s)
+            .map(Cow::Owned)
+            .map_err(|_| ExpressionError::InvalidOperation(format!("Invalid JSON: {}", s))),
+        other => Err(argument_error(other, "JSON")),
+    }
+}
+
+fn array(value: &Value) -> Result<&[Value], ExpressionError> {
+    match value {
+        Value::Array(items) => Ok(items),
+        other => Err(argument_error(other, "an array")),
+    }
+}
+
+fn interval(value: &Value) -> Result<Interval, ExpressionError> {
+    match value {
+        Value::Interval(i) => Ok(*i),
+        other => Err(argument_error(other, "INTERVAL")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    use crate::application::executor::testing::{error, executor, int, query, text};
+    use crate::domain::entity::Value;
+
+    #[tokio::test]
+    async fn case_expressions() {
+        let executor = executor();
+
+        // NULL の条件は偽として扱い、どの分岐にも当たらなければ NULL になる
+        let rows = query(&executor, "
+            SELECT CASE WHEN NULL THEN 'a' WHEN 1 = 1 THEN 'b' ELSE 'c' END,
+                   CASE 2 WHEN 1 THEN 'one' WHEN 2 THEN 'two' END,
+                   CASE 3 WHEN 1 THEN 'one' END
+        ").await;
+        assert_eq!(rows, vec![vec![text("b"), text("two"), Value::Null]]);
+
+        let message = error(&executor, "SELECT CASE WHEN 1 = 1 THEN 1 ELSE 'x' END").await;
+        assert!(message.contains("CASE types Integer and Text cannot be matched"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn builtin_string_and_math_functions() {
+        let executor = executor();
+
+        let rows = query(&executor, "
+            SELECT UPPER('ab'), LOWER(NULL), LENGTH('héllo'), SUBSTRING('hello', 2, 3),
+                   TRIM('  x '), CONCAT('a', NULL, 'b'), REPLACE('aaa', 'a', 'b')
+        ").await;
+        assert_eq!(rows, vec![vec![
+            text("AB"), Value::Null, int(5), text("ell"), text("x"), text("ab"), text("bbb"),
+        ]]);
+
+        let rows = query(&executor, "SELECT ABS(-3), ROUND(2.567, 2), CEIL(1.2), FLOOR(-1.2), MOD(7, 3), ABS(NULL)").await;
+        assert_eq!(rows, vec![vec![
+            int(3),
+            Value::Decimal(Decimal::new(257, 2)),
+            Value::Decimal(Decimal::from(2)),
+            Value::Decimal(Decimal::from(-2)),
+            int(1),
+            Value::Null,
+        ]]);
+    }
+
+    #[tokio::test]
+    async fn builtin_datetime_functions() {
+        let executor = executor();
+
+        let rows = query(&executor, "
+            SELECT DATE_TRUNC('month', TIMESTAMP '2024-02-29 13:45:00'),
+                   EXTRACT(YEAR FROM DATE '2024-02-29'),
+                   DATE '2024-01-31' + INTERVAL '1 month'
+        ").await;
+        let date = |d| NaiveDate::from_ymd_opt(2024, 2, d).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        assert_eq!(rows, vec![vec![Value::Timestamp(date(1)), Value::Float(2024.0), Value::Timestamp(date(29))]]);
+    }
+
+    #[tokio::test]
+    async fn builtin_function_errors() {
+        let executor = executor();
+
+        let message = error(&executor, "SELECT ABS('x')").await;
+        assert!(message.contains("Function ABS does not accept Text as argument 1"), "{}", message);
+        let message = error(&executor, "SELECT MOD(1, 0)").await;
+        assert!(message.contains("Division by zero"), "{}", message);
+        let message = error(&executor, "SELECT DATE_TRUNC('fortnight', TIMESTAMP '2024-02-29 13:45:00')").await;
+        assert!(message.contains("Unsupported DATE_TRUNC unit: fortnight"), "{}", message);
+    }
+}
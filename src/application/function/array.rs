@@ -0,0 +1,43 @@
+use super::{array, integer, BuiltinFunction, Param, Returns};
+use crate::domain::entity::{DataType, Value};
+use crate::domain::expression::ExpressionError;
+
+/// 配列関数
+pub(super) fn functions() -> Vec<BuiltinFunction> {
+    vec![
+        BuiltinFunction::new("ARRAY_LENGTH", &[Param::Array, Param::Integer], Returns::Type(DataType::Integer), array_length)
+            .optional(1),
+        BuiltinFunction::new("CARDINALITY", &[Param::Array], Returns::Type(DataType::Integer), cardinality),
+    ]
+}
+
+/// ARRAY_LENGTH(配列 [, 次元])（次元は1から数え、省略時は1。空の配列や存在しない次元ではNULL）
+fn array_length(args: &[Value]) -> Result<Value, ExpressionError> {
+    let dimension = args.get(1).map(integer).transpose()?.unwrap_or(1);
+    if dimension < 1 {
+        return Ok(Value::Null);
+    }
+
+    let mut items = array(&args[0])?;
+    for _ in 1..dimension {
+        match items.first() {
+            Some(Value::Array(inner)) => items = inner,
+            _ => return Ok(Value::Null),
+        }
+    }
+    Ok(match items.len() {
+        0 => Value::Null,
+        len => Value::Integer(len as i64),
+    })
+}
+
+/// CARDINALITY(配列)（入れ子の配列はすべての要素を数える）
+fn cardinality(args: &[Value]) -> Result<Value, ExpressionError> {
+    fn count(items: &[Value]) -> usize {
+        items.iter().map(|item| match item {
+            Value::Array(inner) => count(inner),
+            _ => 1,
+        }).sum()
+    }
+    Ok(Value::Integer(count(array(&args[0])?) as i64))
+}
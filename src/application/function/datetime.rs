@@ -0,0 +1,136 @@
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+
+use super::{interval, text, timestamp, BuiltinFunction, Param, Returns};
+use crate::domain::entity::{DataType, Interval, Value};
+use crate::domain::expression::ExpressionError;
+
+/// 日付・時刻関数
+pub(super) fn functions() -> Vec<BuiltinFunction> {
+    vec![
+        BuiltinFunction::new("NOW", &[], Returns::Type(DataType::TimestampTz), now)
+            .aliases(&["CURRENT_TIMESTAMP"])
+            .lenient(),
+        BuiltinFunction::zoned("CURRENT_DATE", &[], Returns::Type(DataType::Date), current_date).lenient(),
+        BuiltinFunction::zoned("CURRENT_TIME", &[], Returns::Type(DataType::Time), current_time).lenient(),
+        BuiltinFunction::zoned("DATE_TRUNC", &[Param::Text, Param::Timestamp], Returns::Timestamp, date_trunc),
+        BuiltinFunction::zoned("EXTRACT", &[Param::Text, Param::TimestampOrInterval], Returns::Type(DataType::Float), extract)
+            .aliases(&["DATE_PART"]),
+    ]
+}
+
+/// 現在の日時（呼び出すたびに評価する）
+fn now(_args: &[Value]) -> Result<Value, ExpressionError> {
+    Ok(Value::TimestampTz(Utc::now()))
+}
+
+/// セッションのタイムゾーンでの現在の日付
+fn current_date(_args: &[Value], time_zone: FixedOffset) -> Result<Value, ExpressionError> {
+    Ok(Value::Date(Utc::now().with_timezone(&time_zone).date_naive()))
+}
+
+/// セッションのタイムゾーンでの現在の時刻
+fn current_time(_args: &[Value], time_zone: FixedOffset) -> Result<Value, ExpressionError> {
+    Ok(Value::Time(Utc::now().with_timezone(&time_zone).time()))
+}
+
+/// DATE_TRUNC(単位, 日時)（週は月曜日から始まる。タイムゾーン付きの日時はセッションのタイムゾーンで切り捨てる）
+fn date_trunc(args: &[Value], time_zone: FixedOffset) -> Result<Value, ExpressionError> {
+    let unit = text(&args[0])?;
+    let naive = timestamp(&args[1], time_zone)?;
+    let date = naive.date();
+    let first_day = |year: i32, month: u32| NaiveDate::from_ymd_opt(year, month, 1);
+
+    let truncated: Option<NaiveDateTime> = match unit.to_lowercase().as_str() {
+        "microsecond" | "microseconds" => naive.with_nanosecond(naive.nanosecond() / 1_000 * 1_000),
+        "millisecond" | "milliseconds" => naive.with_nanosecond(naive.nanosecond() / 1_000_000 * 1_000_000),
+        "second" => naive.with_nanosecond(0),
+        "minute" => date.and_hms_opt(naive.hour(), naive.minute(), 0),
+        "hour" => date.and_hms_opt(naive.hour(), 0, 0),
+        "day" => date.and_hms_opt(0, 0, 0),
+        "week" => (date - Duration::days(date.weekday().num_days_from_monday() as i64)).and_hms_opt(0, 0, 0),
+        "month" => first_day(date.year(), date.month()).and_then(|d| d.and_hms_opt(0, 0, 0)),
+        "quarter" => first_day(date.year(), (date.month() - 1) / 3 * 3 + 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+        "year" => first_day(date.year(), 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+        "decade" => first_day(date.year().div_euclid(10) * 10, 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+        // 世紀と千年紀は1年から数える
+        "century" => first_day((date.year() - 1).div_euclid(100) * 100 + 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+        "millennium" => first_day((date.year() - 1).div_euclid(1000) * 1000 + 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+        _ => return Err(ExpressionError::InvalidOperation(format!("Unsupported DATE_TRUNC unit: {}", unit))),
+    };
+
+    truncated
+        .and_then(|dt| match args[1] {
+            Value::TimestampTz(_) => time_zone.from_local_datetime(&dt).single()
+                .map(|dt| Value::TimestampTz(dt.with_timezone(&Utc))),
+            _ => Some(Value::Timestamp(dt)),
+        })
+        .ok_or_else(|| ExpressionError::InvalidOperation(format!("DATE_TRUNC({}) is out of range", unit)))
+}
+
+/// EXTRACT(フィールド FROM 日時、日付、時刻または時間間隔)
+///
+/// タイムゾーン付きの日時はセッションのタイムゾーンでの日時から取り出す（epoch はタイムゾーンによらない）。
+fn extract(args: &[Value], time_zone: FixedOffset) -> Result<Value, ExpressionError> {
+    let field = text(&args[0])?;
+    let value = match &args[1] {
+        Value::Interval(_) => extract_from_interval(field, interval(&args[1])?),
+        // 時刻は0時からの時間間隔として扱う（日付の部分は取り出せない）
+        Value::Time(t) => match field.to_lowercase().as_str() {
+            "hour" | "minute" | "second" | "millisecond" | "milliseconds" |
+            "microsecond" | "microseconds" | "epoch" => extract_from_interval(field, Interval::since_midnight(*t)),
+            _ => None,
+        },
+        Value::TimestampTz(dt) if field.eq_ignore_ascii_case("epoch") => Some(dt.timestamp_micros() as f64 / 1e6),
+        _ => extract_from_timestamp(field, timestamp(&args[1], time_zone)?),
+    };
+    value
+        .map(Value::Float)
+        .ok_or_else(|| ExpressionError::InvalidOperation(format!(
+            "EXTRACT field {} is not supported for {}", field, args[1].data_type())))
+}
+
+fn extract_from_timestamp(field: &str, dt: NaiveDateTime) -> Option<f64> {
+    let year = dt.year();
+    let seconds = dt.second() as f64 + dt.nanosecond() as f64 / 1e9;
+    Some(match field.to_lowercase().as_str() {
+        "year" => year as f64,
+        "month" => dt.month() as f64,
+        "day" => dt.day() as f64,
+        "hour" => dt.hour() as f64,
+        "minute" => dt.minute() as f64,
+        "second" => seconds,
+        "millisecond" | "milliseconds" => seconds * 1e3,
+        "microsecond" | "microseconds" => seconds * 1e6,
+        // 日曜日を0とする曜日
+        "dow" => dt.weekday().num_days_from_sunday() as f64,
+        // 月曜日を1とする曜日
+        "isodow" => dt.weekday().number_from_monday() as f64,
+        "doy" => dt.ordinal() as f64,
+        "week" => dt.iso_week().week() as f64,
+        "isoyear" => dt.iso_week().year() as f64,
+        "quarter" => ((dt.month() - 1) / 3 + 1) as f64,
+        "epoch" => dt.and_utc().timestamp_micros() as f64 / 1e6,
+        "decade" => year.div_euclid(10) as f64,
+        "century" => ((year - 1).div_euclid(100) + 1) as f64,
+        "millennium" => ((year - 1).div_euclid(1000) + 1) as f64,
+        _ => return None,
+    })
+}
+
+fn extract_from_interval(field: &str, interval: Interval) -> Option<f64> {
+    const MICROS_PER_MINUTE: i64 = 60_000_000;
+    let seconds = (interval.micros % MICROS_PER_MINUTE) as f64 / 1e6;
+    Some(match field.to_lowercase().as_str() {
+        "year" => (interval.months / 12) as f64,
+        "quarter" => ((interval.months % 12) / 3 + 1) as f64,
+        "month" => (interval.months % 12) as f64,
+        "day" => interval.days as f64,
+        "hour" => (interval.micros / 3_600_000_000) as f64,
+        "minute" => (interval.micros / MICROS_PER_MINUTE % 60) as f64,
+        "second" => seconds,
+        "millisecond" | "milliseconds" => seconds * 1e3,
+        "microsecond" | "microseconds" => seconds * 1e6,
+        "epoch" => interval.approximate_micros() as f64 / 1e6,
+        _ => return None,
+    })
+}
@@ -0,0 +1,75 @@
+use super::{json, text, BuiltinFunction, Param, Returns};
+use crate::domain::entity::{json_path, json_type_name, DataType, Value};
+use crate::domain::expression::ExpressionError;
+
+/// JSON関数（JSON と JSONB を区別しないので、どちらの名前でも呼び出せる）
+pub(super) fn functions() -> Vec<BuiltinFunction> {
+    vec![
+        BuiltinFunction::new("JSONB_ARRAY_LENGTH", &[Param::Json], Returns::Type(DataType::Integer), array_length)
+            .aliases(&["JSON_ARRAY_LENGTH"]),
+        BuiltinFunction::new("JSONB_TYPEOF", &[Param::Json], Returns::Type(DataType::Text), type_of)
+            .aliases(&["JSON_TYPEOF"]),
+        BuiltinFunction::new("JSONB_EXTRACT_PATH", &[Param::Json], Returns::Type(DataType::Json), extract_path)
+            .aliases(&["JSON_EXTRACT_PATH"])
+            .variadic(Param::Text),
+        BuiltinFunction::new("JSONB_EXTRACT_PATH_TEXT", &[Param::Json], Returns::Type(DataType::Text), extract_path_text)
+            .aliases(&["JSON_EXTRACT_PATH_TEXT"])
+            .variadic(Param::Text),
+    ]
+}
+
+/// 配列の要素数（配列でなければエラー）
+fn array_length(args: &[Value]) -> Result<Value, ExpressionError> {
+    match json(&args[0])?.as_array() {
+        Some(items) => Ok(Value::Integer(items.len() as i64)),
+        None => Err(ExpressionError::InvalidOperation(
+            "Cannot get array length of a non-array JSON value".to_string())),
+    }
+}
+
+fn type_of(args: &[Value]) -> Result<Value, ExpressionError> {
+    Ok(Value::Text(json_type_name(&*json(&args[0])?).to_string()))
+}
+
+/// JSONB_EXTRACT_PATH(JSON, キー...)（#> と同じく、パスに沿った要素を取り出す）
+fn extract_path(args: &[Value]) -> Result<Value, ExpressionError> {
+    let path = args[1..].iter()
+        .map(|key| text(key).map(str::to_string))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(json_path(&*json(&args[0])?, &path).map_or(Value::Null, |found| Value::Json(found.clone())))
+}
+
+/// JSONB_EXTRACT_PATH_TEXT(JSON, キー...)（#>> と同じく、パスに沿った要素を文字列として取り出す）
+fn extract_path_text(args: &[Value]) -> Result<Value, ExpressionError> {
+    Ok(match extract_path(args)? {
+        Value::Json(serde_json::Value::String(s)) => Value::Text(s),
+        Value::Json(serde_json::Value::Null) | Value::Null => Value::Null,
+        found => Value::Text(found.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::application::executor::testing::{error, executor, int, query, text};
+    use crate::domain::entity::Value;
+
+    #[tokio::test]
+    async fn json_functions() {
+        let executor = executor();
+        let doc = r#"'{"tags": ["x", "y"], "n": null}'::JSON"#;
+
+        let rows = query(&executor, &format!(
+            "SELECT JSONB_ARRAY_LENGTH({doc} -> 'tags'), JSON_TYPEOF({doc} -> 'n'), \
+             JSON_EXTRACT_PATH_TEXT({doc}, 'tags', '1'), JSONB_EXTRACT_PATH_TEXT({doc}, 'n'), \
+             JSON_EXTRACT_PATH({doc}, 'missing')")).await;
+        assert_eq!(rows, vec![vec![int(2), text("null"), text("y"), Value::Null, Value::Null]]);
+    }
+
+    #[tokio::test]
+    async fn json_function_errors() {
+        let executor = executor();
+
+        let message = error(&executor, r#"SELECT JSON_ARRAY_LENGTH('{"a": 1}'::JSON)"#).await;
+        assert!(message.contains("Cannot get array length of a non-array JSON value"), "{}", message);
+    }
+}
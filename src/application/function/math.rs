@@ -0,0 +1,134 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use super::{argument_error, float, integer, BuiltinFunction, Param, Returns};
+use crate::domain::entity::{DataType, Value};
+use crate::domain::expression::ExpressionError;
+
+/// 数学関数
+pub(super) fn functions() -> Vec<BuiltinFunction> {
+    vec![
+        BuiltinFunction::new("ABS", &[Param::Numeric], Returns::SameAsFirst, abs),
+        BuiltinFunction::new("ROUND", &[Param::Numeric, Param::Integer], Returns::SameAsFirst, round)
+            .optional(1),
+        BuiltinFunction::new("CEIL", &[Param::Numeric], Returns::SameAsFirst, ceil)
+            .aliases(&["CEILING"]),
+        BuiltinFunction::new("FLOOR", &[Param::Numeric], Returns::SameAsFirst, floor),
+        BuiltinFunction::new("MOD", &[Param::Numeric, Param::Numeric], Returns::Numeric, modulo),
+        BuiltinFunction::new("POWER", &[Param::Numeric, Param::Numeric], Returns::Type(DataType::Float), power)
+            .aliases(&["POW"]),
+    ]
+}
+
+fn out_of_range(function: &str) -> ExpressionError {
+    ExpressionError::InvalidOperation(format!("Result of {} is out of range", function))
+}
+
+fn abs(args: &[Value]) -> Result<Value, ExpressionError> {
+    match &args[0] {
+        Value::Integer(i) => i.checked_abs().map(Value::Integer).ok_or_else(|| out_of_range("ABS")),
+        Value::Float(f) => Ok(Value::Float(f.abs())),
+        Value::Decimal(d) => Ok(Value::Decimal(d.abs())),
+        other => Err(argument_error(other, "a number")),
+    }
+}
+
+/// ROUND(数値 [, 小数点以下の桁数])（0.5 は0から遠い方に丸める。桁数は負でもよい）
+fn round(args: &[Value]) -> Result<Value, ExpressionError> {
+    let digits = args.get(1).map(integer).transpose()?.unwrap_or(0);
+    match &args[0] {
+        Value::Integer(i) if digits >= 0 => Ok(Value::Integer(*i)),
+        Value::Integer(i) => {
+            // 10の桁数乗がi64に収まらなければ結果は0
+            let Some(factor) = u32::try_from(-digits).ok().and_then(|d| 10_i64.checked_pow(d)) else {
+                return Ok(Value::Integer(0));
+            };
+            let remainder = i % factor;
+            let rounded = if remainder.abs() * 2 >= factor {
+                (i - remainder).checked_add(factor * i.signum())
+            } else {
+                Some(i - remainder)
+            };
+            rounded.map(Value::Integer).ok_or_else(|| out_of_range("ROUND"))
+        },
+        Value::Float(f) => {
+            let factor = 10_f64.powi(digits.clamp(i32::MIN as i64, i32::MAX as i64) as i32);
+            let rounded = (f * factor).round() / factor;
+            Ok(Value::Float(if rounded.is_finite() { rounded } else { *f }))
+        },
+        Value::Decimal(d) if digits >= 0 => {
+            let digits = u32::try_from(digits).unwrap_or(u32::MAX);
+            Ok(Value::Decimal(d.round_dp_with_strategy(digits, RoundingStrategy::MidpointAwayFromZero)))
+        },
+        Value::Decimal(d) => {
+            // 10の桁数乗が10進数の範囲を超えれば結果は0
+            let Some(factor) = u32::try_from(-digits).ok().and_then(|d| 10_i128.checked_pow(d))
+                .and_then(|f| Decimal::try_from_i128_with_scale(f, 0).ok()) else {
+                return Ok(Value::Decimal(Decimal::ZERO));
+            };
+            let rounded = (d / factor).round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero);
+            rounded.checked_mul(factor).map(Value::Decimal).ok_or_else(|| out_of_range("ROUND"))
+        },
+        other => Err(argument_error(other, "a number")),
+    }
+}
+
+fn ceil(args: &[Value]) -> Result<Value, ExpressionError> {
+    match &args[0] {
+        Value::Integer(i) => Ok(Value::Integer(*i)),
+        Value::Float(f) => Ok(Value::Float(f.ceil())),
+        Value::Decimal(d) => Ok(Value::Decimal(d.ceil())),
+        other => Err(argument_error(other, "a number")),
+    }
+}
+
+fn floor(args: &[Value]) -> Result<Value, ExpressionError> {
+    match &args[0] {
+        Value::Integer(i) => Ok(Value::Integer(*i)),
+        Value::Float(f) => Ok(Value::Float(f.floor())),
+        Value::Decimal(d) => Ok(Value::Decimal(d.floor())),
+        other => Err(argument_error(other, "a number")),
+    }
+}
+
+/// 剰余（結果の符号は被除数に従う）
+fn modulo(args: &[Value]) -> Result<Value, ExpressionError> {
+    let division_by_zero = || ExpressionError::InvalidOperation("Division by zero".to_string());
+    match (&args[0], &args[1]) {
+        (Value::Integer(_), Value::Integer(0)) => Err(division_by_zero()),
+        (Value::Integer(a), Value::Integer(b)) => a.checked_rem(*b).map(Value::Integer).ok_or_else(|| out_of_range("MOD")),
+        (Value::Decimal(_), Value::Integer(_) | Value::Decimal(_)) | (Value::Integer(_), Value::Decimal(_)) => {
+            let (a, b) = (args[0].to_decimal(), args[1].to_decimal());
+            let (Some(a), Some(b)) = (a, b) else {
+                return Err(out_of_range("MOD"));
+            };
+            if b.is_zero() {
+                return Err(division_by_zero());
+            }
+            a.checked_rem(b).map(Value::Decimal).ok_or_else(|| out_of_range("MOD"))
+        },
+        (a, b) => {
+            let (a, b) = (float(a)?, float(b)?);
+            if b == 0.0 {
+                return Err(division_by_zero());
+            }
+            Ok(Value::Float(a % b))
+        },
+    }
+}
+
+fn power(args: &[Value]) -> Result<Value, ExpressionError> {
+    let (base, exponent) = (float(&args[0])?, float(&args[1])?);
+    let result = base.powf(exponent);
+    if result.is_nan() {
+        return Err(ExpressionError::InvalidOperation(format!(
+            "POWER({}, {}) is not a real number", base, exponent)));
+    }
+    if result.is_infinite() && base.is_finite() && exponent.is_finite() {
+        if base == 0.0 {
+            return Err(ExpressionError::InvalidOperation(
+                "Zero raised to a negative power is undefined".to_string()));
+        }
+        return Err(out_of_range("POWER"));
+    }
+    Ok(Value::Float(result))
+}
@@ -0,0 +1,120 @@
+use super::{integer, text, BuiltinFunction, Param, Returns};
+use crate::domain::entity::{DataType, Value};
+use crate::domain::expression::ExpressionError;
+
+/// 文字列関数
+pub(super) fn functions() -> Vec<BuiltinFunction> {
+    vec![
+        BuiltinFunction::new("UPPER", &[Param::Text], Returns::Type(DataType::Text), upper),
+        BuiltinFunction::new("LOWER", &[Param::Text], Returns::Type(DataType::Text), lower),
+        BuiltinFunction::new("LENGTH", &[Param::TextOrBlob], Returns::Type(DataType::Integer), length)
+            .aliases(&["CHAR_LENGTH", "CHARACTER_LENGTH"]),
+        BuiltinFunction::new("SUBSTRING", &[Param::TextOrBlob, Param::Integer, Param::Integer], Returns::SameAsFirst, substring)
+            .aliases(&["SUBSTR"])
+            .optional(1),
+        BuiltinFunction::new("TRIM", &[Param::Text, Param::Text], Returns::Type(DataType::Text), trim)
+            .aliases(&["BTRIM"])
+            .optional(1),
+        BuiltinFunction::new("LTRIM", &[Param::Text, Param::Text], Returns::Type(DataType::Text), ltrim)
+            .optional(1),
+        BuiltinFunction::new("RTRIM", &[Param::Text, Param::Text], Returns::Type(DataType::Text), rtrim)
+            .optional(1),
+        BuiltinFunction::new("CONCAT", &[], Returns::Type(DataType::Text), concat)
+            .variadic(Param::Any)
+            .lenient(),
+        BuiltinFunction::new("REPLACE", &[Param::Text, Param::Text, Param::Text], Returns::Type(DataType::Text), replace),
+    ]
+}
+
+fn upper(args: &[Value]) -> Result<Value, ExpressionError> {
+    Ok(Value::Text(text(&args[0])?.to_uppercase()))
+}
+
+fn lower(args: &[Value]) -> Result<Value, ExpressionError> {
+    Ok(Value::Text(text(&args[0])?.to_lowercase()))
+}
+
+/// 文字数（バイト数ではない）、バイト列の場合はバイト数
+fn length(args: &[Value]) -> Result<Value, ExpressionError> {
+    match &args[0] {
+        Value::Bytes(b) => Ok(Value::Integer(b.len() as i64)),
+        other => Ok(Value::Integer(text(other)?.chars().count() as i64)),
+    }
+}
+
+/// SUBSTRING(文字列, 開始位置 [, 長さ])（開始位置は1から数え、1より前の部分は切り捨てる）
+///
+/// バイト列の場合は位置と長さをバイト単位で数える。
+fn substring(args: &[Value]) -> Result<Value, ExpressionError> {
+    let (from, to) = substring_range(args)?;
+    match &args[0] {
+        Value::Bytes(b) => {
+            let (from, to) = (from.min(b.len()), to.min(b.len()));
+            Ok(Value::Bytes(b.slice(from..to.max(from))))
+        },
+        other => {
+            let chars: Vec<char> = text(other)?.chars().collect();
+            let (from, to) = (from.min(chars.len()), to.min(chars.len()));
+            Ok(Value::Text(chars[from..to.max(from)].iter().collect()))
+        },
+    }
+}
+
+/// SUBSTRING の開始位置と長さから、取り出す範囲を0から数えた位置で求める
+fn substring_range(args: &[Value]) -> Result<(usize, usize), ExpressionError> {
+    let start = integer(&args[1])?;
+    let end = match args.get(2) {
+        Some(length) => {
+            let length = integer(length)?;
+            if length < 0 {
+                return Err(ExpressionError::InvalidOperation(
+                    "Negative substring length not allowed".to_string()));
+            }
+            start.saturating_add(length)
+        },
+        None => i64::MAX,
+    };
+
+    let position = |n: i64| usize::try_from(n.max(1) - 1).unwrap_or(usize::MAX);
+    Ok((position(start), position(end)))
+}
+
+/// 取り除く文字の集合（省略した場合は空白）
+fn trim_characters(args: &[Value]) -> Result<Vec<char>, ExpressionError> {
+    match args.get(1) {
+        Some(characters) => Ok(text(characters)?.chars().collect()),
+        None => Ok(vec![' ']),
+    }
+}
+
+fn trim(args: &[Value]) -> Result<Value, ExpressionError> {
+    let characters = trim_characters(args)?;
+    Ok(Value::Text(text(&args[0])?.trim_matches(characters.as_slice()).to_string()))
+}
+
+fn ltrim(args: &[Value]) -> Result<Value, ExpressionError> {
+    let characters = trim_characters(args)?;
+    Ok(Value::Text(text(&args[0])?.trim_start_matches(characters.as_slice()).to_string()))
+}
+
+fn rtrim(args: &[Value]) -> Result<Value, ExpressionError> {
+    let characters = trim_characters(args)?;
+    Ok(Value::Text(text(&args[0])?.trim_end_matches(characters.as_slice()).to_string()))
+}
+
+/// 引数を文字列として連結する（NULLは無視する）
+fn concat(args: &[Value]) -> Result<Value, ExpressionError> {
+    Ok(Value::Text(args.iter()
+        .filter(|value| **value != Value::Null)
+        .map(|value| value.to_string())
+        .collect()))
+}
+
+fn replace(args: &[Value]) -> Result<Value, ExpressionError> {
+    let (source, from, to) = (text(&args[0])?, text(&args[1])?, text(&args[2])?);
+    // 空の文字列は置き換えない
+    if from.is_empty() {
+        return Ok(Value::Text(source.to_string()));
+    }
+    Ok(Value::Text(source.replace(from, to)))
+}
@@ -0,0 +1,289 @@
+use std::any::Any;
+use std::fmt;
+
+use crate::domain::entity::{DataType, Value};
+use crate::domain::expression::ExpressionError;
+use crate::domain::function::{AggregateState, ScalarFunction, UserAggregate};
+
+type ScalarBody = Box<dyn Fn(&[Value]) -> Result<Value, ExpressionError> + Send + Sync>;
+type InitBody = Box<dyn Fn() -> AggregateState + Send + Sync>;
+type UpdateBody = Box<dyn Fn(&mut AggregateState, &[Value]) -> Result<(), ExpressionError> + Send + Sync>;
+type MergeBody = Box<dyn Fn(&mut AggregateState, AggregateState) -> Result<(), ExpressionError> + Send + Sync>;
+type FinalizeBody = Box<dyn Fn(&AggregateState) -> Result<Value, ExpressionError> + Send + Sync>;
+
+/// Rustのクロージャで実装する利用者定義のスカラー関数
+///
+/// 引数は宣言した型に変換してから渡す（INTEGER は FLOAT として受け付ける）。
+/// 引数にNULLを含む場合もクロージャを呼び出す。
+pub struct ScalarUdf {
+    name: String,
+    params: Vec<DataType>,
+    returns: DataType,
+    body: ScalarBody,
+}
+
+impl ScalarUdf {
+    pub fn new(
+        name: impl Into<String>,
+        params: Vec<DataType>,
+        returns: DataType,
+        body: impl Fn(&[Value]) -> Result<Value, ExpressionError> + Send + Sync + 'static
+    ) -> Self {
+        Self {
+            name: name.into().to_uppercase(),
+            params,
+            returns,
+            body: Box::new(body),
+        }
+    }
+}
+
+impl ScalarFunction for ScalarUdf {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn return_type(&self, args: &[DataType]) -> Result<DataType, ExpressionError> {
+        check_arguments(&self.name, &self.params, args)?;
+        Ok(self.returns.clone())
+    }
+
+    fn invoke(&self, args: &[Value]) -> Result<Value, ExpressionError> {
+        let args = coerce_arguments(&self.params, args)?;
+        check_result(&self.name, &self.returns, (self.body)(&args)?)
+    }
+}
+
+impl fmt::Debug for ScalarUdf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ScalarUdf({})", self.name)
+    }
+}
+
+/// Rustのクロージャで実装する利用者定義の集約関数
+///
+/// 状態の型 `S` はグループごとに `init` で作成し、`update` で各行の引数の値を取り込み、
+/// `finalize` で結果を求める。`merge` は別々に集約した状態をまとめる。
+pub struct AggregateUdf {
+    name: String,
+    params: Vec<DataType>,
+    returns: DataType,
+    init: InitBody,
+    update: UpdateBody,
+    merge: MergeBody,
+    finalize: FinalizeBody,
+}
+
+impl AggregateUdf {
+    pub fn new<S: Any + Send>(
+        name: impl Into<String>,
+        params: Vec<DataType>,
+        returns: DataType,
+        init: impl Fn() -> S + Send + Sync + 'static,
+        update: impl Fn(&mut S, &[Value]) -> Result<(), ExpressionError> + Send + Sync + 'static,
+        merge: impl Fn(&mut S, S) -> Result<(), ExpressionError> + Send + Sync + 'static,
+        finalize: impl Fn(&S) -> Result<Value, ExpressionError> + Send + Sync + 'static
+    ) -> Self {
+        let name = name.into().to_uppercase();
+        let state_error = {
+            let name = name.clone();
+            move || ExpressionError::InvalidOperation(format!("Invalid state for aggregate function {}", name))
+        };
+        let (update_error, merge_error, finalize_error) = (state_error.clone(), state_error.clone(), state_error);
+
+        Self {
+            name,
+            params,
+            returns,
+            init: Box::new(move || Box::new(init())),
+            update: Box::new(move |state, args| {
+                update(state.downcast_mut().ok_or_else(&update_error)?, args)
+            }),
+            merge: Box::new(move |state, other| {
+                let other = *other.downcast::<S>().map_err(|_| merge_error())?;
+                merge(state.downcast_mut().ok_or_else(&merge_error)?, other)
+            }),
+            finalize: Box::new(move |state| {
+                finalize(state.downcast_ref().ok_or_else(&finalize_error)?)
+            }),
+        }
+    }
+}
+
+impl UserAggregate for AggregateUdf {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn return_type(&self, args: &[DataType]) -> Result<DataType, ExpressionError> {
+        check_arguments(&self.name, &self.params, args)?;
+        Ok(self.returns.clone())
+    }
+
+    fn init(&self) -> AggregateState {
+        (self.init)()
+    }
+
+    fn update(&self, state: &mut AggregateState, args: &[Value]) -> Result<(), ExpressionError> {
+        let args = coerce_arguments(&self.params, args)?;
+        (self.update)(state, &args)
+    }
+
+    fn merge(&self, state: &mut AggregateState, other: AggregateState) -> Result<(), ExpressionError> {
+        (self.merge)(state, other)
+    }
+
+    fn finalize(&self, state: &AggregateState) -> Result<Value, ExpressionError> {
+        check_result(&self.name, &self.returns, (self.finalize)(state)?)
+    }
+}
+
+impl fmt::Debug for AggregateUdf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AggregateUdf({})", self.name)
+    }
+}
+
+/// 引数の数と型が宣言と一致するかを確認する（INTEGER は FLOAT の引数として受け付ける）
+fn check_arguments(name: &str, params: &[DataType], args: &[DataType]) -> Result<(), ExpressionError> {
+    if params.len() != args.len() {
+        return Err(ExpressionError::InvalidOperation(format!(
+            "Function {} expects {} arguments, got {}", name, params.len(), args.len())));
+    }
+
+    for (i, (param, arg)) in params.iter().zip(args).enumerate() {
+        if !arg.common_type(param).is_some_and(|common| common.is_same_kind(param)) {
+            return Err(ExpressionError::InvalidOperation(format!(
+                "Function {} does not accept {} as argument {}", name, arg, i + 1)));
+        }
+    }
+    Ok(())
+}
+
+/// 引数の値を宣言した型に変換する
+fn coerce_arguments(params: &[DataType], args: &[Value]) -> Result<Vec<Value>, ExpressionError> {
+    params.iter().zip(args)
+        .map(|(param, arg)| arg.cast_to(param).map_err(|e| ExpressionError::InvalidOperation(e.to_string())))
+        .collect()
+}
+
+/// 関数が宣言した型の値（またはNULL）を返したかを確認する
+fn check_result(name: &str, returns: &DataType, value: Value) -> Result<Value, ExpressionError> {
+    match value.data_type() {
+        DataType::Null => Ok(value),
+        data_type if data_type.is_same_kind(returns) => Ok(value),
+        data_type => Err(ExpressionError::InvalidOperation(format!(
+            "Function {} returned {} instead of {}", name, data_type, returns))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::executor::testing::{error, exec, executor, int, query, text};
+    use crate::application::executor::QueryExecutor;
+
+    fn product() -> AggregateUdf {
+        AggregateUdf::new(
+            "product",
+            vec![DataType::Integer],
+            DataType::Integer,
+            || None::<i64>,
+            |state, args| {
+                if let Value::Integer(i) = args[0] {
+                    *state = Some(state.unwrap_or(1) * i);
+                }
+                Ok(())
+            },
+            |state, other| {
+                if let Some(other) = other {
+                    *state = Some(state.unwrap_or(1) * other);
+                }
+                Ok(())
+            },
+            |state| Ok(state.map(Value::Integer).unwrap_or(Value::Null)),
+        )
+    }
+
+    async fn executor_with_udfs() -> QueryExecutor {
+        let mut executor = executor();
+        executor.register_function(ScalarUdf::new("double_it", vec![DataType::Float], DataType::Float, |args| {
+            Ok(match args[0] {
+                Value::Float(f) => Value::Float(f * 2.0),
+                _ => Value::Null,
+            })
+        }));
+        executor.register_function(ScalarUdf::new("broken", vec![], DataType::Integer, |_| Ok(Value::Text("x".to_string()))));
+        executor.register_aggregate(product());
+        exec(&executor, "
+            CREATE TABLE t (g TEXT, n INTEGER);
+            INSERT INTO t VALUES ('a', 2), ('a', 3), ('b', NULL), ('b', 5)
+        ").await;
+        executor
+    }
+
+    #[tokio::test]
+    async fn scalar_udf_coerces_arguments_and_receives_nulls() {
+        let executor = executor_with_udfs().await;
+
+        let rows = query(&executor, "SELECT double_it(n) FROM t WHERE g = 'a' ORDER BY n").await;
+        assert_eq!(rows, vec![vec![Value::Float(4.0)], vec![Value::Float(6.0)]]);
+        let rows = query(&executor, "SELECT DOUBLE_IT(NULL)").await;
+        assert_eq!(rows, vec![vec![Value::Null]]);
+    }
+
+    #[tokio::test]
+    async fn aggregate_udf_runs_per_group() {
+        let executor = executor_with_udfs().await;
+
+        let rows = query(&executor, "SELECT g, product(n) FROM t GROUP BY g ORDER BY g").await;
+        assert_eq!(rows, vec![vec![text("a"), int(6)], vec![text("b"), int(5)]]);
+        let rows = query(&executor, "SELECT product(n) FROM t WHERE n > 100").await;
+        assert_eq!(rows, vec![vec![Value::Null]]);
+    }
+
+    #[test]
+    fn aggregate_udf_merges_states() {
+        let product = product();
+        let (mut left, mut right) = (product.init(), product.init());
+        product.update(&mut left, &[Value::Integer(2)]).unwrap();
+        product.update(&mut right, &[Value::Integer(7)]).unwrap();
+
+        product.merge(&mut left, right).unwrap();
+        assert_eq!(product.finalize(&left).unwrap(), Value::Integer(14));
+        assert!(product.merge(&mut left, Box::new("not a state")).is_err());
+    }
+
+    #[tokio::test]
+    async fn aggregate_udf_merges_partial_aggregates() {
+        let mut executor = executor();
+        executor.register_aggregate(product());
+        exec(&executor, "CREATE TABLE big (g TEXT, n INTEGER); INSERT INTO big VALUES ('a', 1), ('b', 1)").await;
+        for _ in 0..12 {
+            exec(&executor, "INSERT INTO big SELECT g, n FROM big").await;
+        }
+        exec(&executor, "INSERT INTO big VALUES ('c', 2), ('a', 7)").await;
+
+        // 一定の行数ごとに集約した途中状態をまとめても、グループは最初に現れた順に並ぶ
+        let rows = query(&executor, "SELECT g, product(n), COUNT(*), SUM(n), AVG(n), MIN(n), MAX(n) FROM big GROUP BY g").await;
+        assert_eq!(rows, vec![
+            vec![text("a"), int(7), int(4097), int(4103), Value::Float(4103.0 / 4097.0), int(1), int(7)],
+            vec![text("b"), int(1), int(4096), int(4096), Value::Float(1.0), int(1), int(1)],
+            vec![text("c"), int(2), int(1), int(2), Value::Float(2.0), int(2), int(2)],
+        ]);
+    }
+
+    #[tokio::test]
+    async fn udf_signature_errors() {
+        let executor = executor_with_udfs().await;
+
+        let message = error(&executor, "SELECT double_it('x')").await;
+        assert!(message.contains("does not accept Text as argument 1"), "{}", message);
+        let message = error(&executor, "SELECT double_it(1, 2)").await;
+        assert!(message.contains("expects 1 arguments, got 2"), "{}", message);
+        let message = error(&executor, "SELECT broken()").await;
+        assert!(message.contains("returned Text instead of Integer"), "{}", message);
+        let message = error(&executor, "SELECT nosuch(1)").await;
+        assert!(message.contains("Unknown function: NOSUCH"), "{}", message);
+    }
+}
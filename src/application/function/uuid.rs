@@ -0,0 +1,16 @@
+use super::{BuiltinFunction, Returns};
+use crate::domain::entity::{DataType, Uuid, Value};
+use crate::domain::expression::ExpressionError;
+
+/// UUID関数
+pub(super) fn functions() -> Vec<BuiltinFunction> {
+    vec![
+        BuiltinFunction::new("GEN_RANDOM_UUID", &[], Returns::Type(DataType::Uuid), gen_random_uuid)
+            .aliases(&["UUID_GENERATE_V4"]),
+    ]
+}
+
+/// ランダムなバージョン4のUUIDを生成する（呼び出すたびに異なる値になる）
+fn gen_random_uuid(_args: &[Value]) -> Result<Value, ExpressionError> {
+    Ok(Value::Uuid(Uuid::new_v4()))
+}
@@ -0,0 +1,6 @@
+pub mod aggregate;
+pub mod executor;
+pub mod function;
+
+pub use executor::{QueryExecutor, ExecutionResult, ExecutorError, OnError, ScriptOptions, ScriptResult};
+pub use function::{AggregateUdf, FunctionRegistry, ScalarUdf};
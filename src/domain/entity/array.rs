@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::domain::entity::data_type::DataType;
+use crate::domain::entity::value::Value;
+
+/// 配列の要素の型（NULL以外の要素の型をまとめたもの。要素がすべてNULLならNULL）
+///
+/// まとめられない型が混ざる場合は最初の要素の型とする。
+pub fn array_element_type(items: &[Value]) -> DataType {
+    let mut types = items.iter().map(Value::data_type).filter(|t| !t.is_null());
+    let Some(first) = types.next() else {
+        return DataType::Null;
+    };
+    types.try_fold(first.clone(), |result, t| result.common_type(&t)).unwrap_or(first)
+}
+
+/// '{1,2,"a b",NULL}' のような配列リテラルの形式の文字列を解析する
+///
+/// 要素は文字列（引用符で囲まない NULL はNULL）として返し、入れ子の波括弧は入れ子の配列になる。
+pub fn parse_array(text: &str) -> Option<Vec<Value>> {
+    let mut chars = text.trim().chars().peekable();
+    let items = parse_items(&mut chars)?;
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    chars.next().is_none().then_some(items)
+}
+
+fn parse_items(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Vec<Value>> {
+    if chars.next()? != '{' {
+        return None;
+    }
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    if chars.next_if_eq(&'}').is_some() {
+        return Some(Vec::new());
+    }
+
+    let mut items = Vec::new();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let item = match chars.peek()? {
+            '{' => Value::Array(parse_items(chars)?),
+            '"' => {
+                chars.next();
+                let mut item = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => item.push(chars.next()?),
+                        c => item.push(c),
+                    }
+                }
+                Value::Text(item)
+            },
+            _ => {
+                let mut item = String::new();
+                while let Some(c) = chars.next_if(|c| !matches!(c, ',' | '}' | '{' | '"')) {
+                    item.push(c);
+                }
+                let item = item.trim();
+                if item.is_empty() {
+                    return None;
+                }
+                if item.eq_ignore_ascii_case("NULL") { Value::Null } else { Value::Text(item.to_string()) }
+            },
+        };
+        items.push(item);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next()? {
+            ',' => continue,
+            '}' => return Some(items),
+            _ => return None,
+        }
+    }
+}
+
+/// 配列を '{1,2,"a b",NULL}' の形式で書き出す
+///
+/// 空文字列や区切り文字・空白を含む要素、NULL と紛らわしい文字列は二重引用符で囲む。
+pub fn format_array(f: &mut fmt::Formatter<'_>, items: &[Value]) -> fmt::Result {
+    write!(f, "{{")?;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ",")?;
+        }
+        match item {
+            Value::Array(inner) => format_array(f, inner)?,
+            Value::Null => write!(f, "NULL")?,
+            item => {
+                let text = item.to_string();
+                let needs_quotes = text.is_empty() || text.eq_ignore_ascii_case("NULL") ||
+                    text.chars().any(|c| matches!(c, '{' | '}' | ',' | '"' | '\\') || c.is_whitespace());
+                if needs_quotes {
+                    write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))?;
+                } else {
+                    write!(f, "{}", text)?;
+                }
+            },
+        }
+    }
+    write!(f, "}}")
+}
@@ -0,0 +1,92 @@
+use icu_normalizer::ComposingNormalizer;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::domain::entity::value::Value;
+
+/// 文字列の比較方法（照合順序）
+///
+/// どの照合順序もロケールには依存しない。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Collation {
+    /// バイト列として比較する（"C"）
+    Binary,
+    /// NFCで正規化してから小文字にそろえて比較する（"nocase"）
+    CaseInsensitive,
+    /// NFCで正規化してからコードポイント順に比較する（"unicode"）
+    Unicode,
+}
+
+impl Collation {
+    /// 照合順序の名前を解析する（大文字小文字は区別しない）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "c" | "posix" | "binary" | "ucs_basic" => Some(Collation::Binary),
+            "nocase" | "case_insensitive" => Some(Collation::CaseInsensitive),
+            "unicode" | "und" | "und-x-icu" => Some(Collation::Unicode),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Collation::Binary => "C",
+            Collation::CaseInsensitive => "nocase",
+            Collation::Unicode => "unicode",
+        }
+    }
+
+    /// 比較に使う形に文字列を変換する（この形が等しい文字列同士を等しいとみなす）
+    pub fn key<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            Collation::Binary => Cow::Borrowed(text),
+            Collation::CaseInsensitive => Cow::Owned(nfc(text).to_lowercase()),
+            Collation::Unicode => nfc(text),
+        }
+    }
+
+    /// 比較に使う形に値を変換する（文字列と配列の要素の文字列だけを変換する）
+    ///
+    /// ハッシュで重複や所属を判定するときに、この形の値をキーにする。
+    pub fn normalize(&self, value: &Value) -> Value {
+        match (self, value) {
+            (Collation::Binary, _) => value.clone(),
+            (_, Value::Text(text)) => Value::Text(self.key(text).into_owned()),
+            (_, Value::Array(items)) => Value::Array(items.iter().map(|item| self.normalize(item)).collect()),
+            _ => value.clone(),
+        }
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Collation::Binary => a.cmp(b),
+            _ => self.key(a).cmp(&self.key(b)),
+        }
+    }
+
+    /// 2つの値を比較する（文字列以外は照合順序によらず Value::compare で比較する）
+    pub fn compare_values(&self, a: &Value, b: &Value) -> Option<Ordering> {
+        match (a, b) {
+            (Value::Text(a), Value::Text(b)) => Some(self.compare(a, b)),
+            _ => a.compare(b),
+        }
+    }
+}
+
+impl fmt::Display for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// NFCで正規化する（正規化済みならそのまま返す）
+fn nfc(text: &str) -> Cow<'_, str> {
+    let normalizer = ComposingNormalizer::new_nfc();
+    if normalizer.is_normalized(text) {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(normalizer.normalize(text))
+    }
+}
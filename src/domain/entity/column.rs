@@ -1,4 +1,5 @@
-use crate::domain::entity::data_type::{DataType, Constraint};
+use crate::domain::entity::collation::Collation;
+use crate::domain::entity::data_type::{DataType, Constraint, TypeLimit};
 // use derive_more::Display;
 use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
@@ -13,6 +14,14 @@ pub struct Column {
     /// データ型
     pub data_type: DataType,
 
+    /// 宣言された長さや整数の幅（VARCHAR(20) や SMALLINT など）
+    #[builder(default)]
+    pub type_limit: Option<TypeLimit>,
+
+    /// 文字列を比較するときの照合順序（指定がなければバイト列として比較する）
+    #[builder(default)]
+    pub collation: Option<Collation>,
+
     /// 制約
     #[builder(default)]
     pub constraints: Vec<Constraint>,
@@ -23,10 +32,32 @@ impl Column {
         Self {
             name: name.into(),
             data_type,
+            type_limit: None,
+            collation: None,
             constraints: Vec::new(),
         }
     }
 
+    // VARCHAR(n) や SMALLINT などの宣言
+    pub fn with_type_limit(mut self, type_limit: TypeLimit) -> Self {
+        self.type_limit = Some(type_limit);
+        self
+    }
+
+    // COLLATE
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    /// 宣言された型の表記（長さや幅の指定があればその表記、なければデータ型）
+    pub fn declared_type(&self) -> String {
+        match &self.type_limit {
+            Some(type_limit) => type_limit.to_string(),
+            None => self.data_type.to_string(),
+        }
+    }
+
     // primary key constraint
     pub fn primary_key(mut self) -> Self {
         self.constraints.push(Constraint::PrimaryKey);
@@ -87,7 +118,10 @@ impl Column {
 
 impl fmt::Display for Column {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {}", self.name, self.data_type)?;
+        write!(f, "{} {}", self.name, self.declared_type())?;
+        if let Some(collation) = self.collation {
+            write!(f, " COLLATE \"{}\"", collation)?;
+        }
         for constraint in &self.constraints {
             write!(f, " {}", constraint)?;
         }
@@ -2,10 +2,13 @@ use derive_more::Display;
 use strum::EnumString;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::Arc;
+
+use crate::domain::entity::enum_type::EnumType;
 
 
 /// データベースでサポートされるデータ型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Display, EnumString, Serialize, Deserialize)]
 pub enum DataType {
     #[strum(serialize = "INTEGER")]
     Integer,
@@ -19,14 +22,60 @@ pub enum DataType {
     #[strum(serialize = "BOOLEAN")]
     Boolean,
 
+    /// タイムゾーンを持たない日時
     #[strum(serialize = "TIMESTANMP")]
     Timestamp,
 
+    /// タイムゾーン付きの日時（UTCの時点として保持する）
+    #[strum(serialize = "TIMESTAMPTZ")]
+    TimestampTz,
+
+    #[strum(serialize = "DATE")]
+    Date,
+
+    #[strum(serialize = "TIME")]
+    Time,
+
+    #[strum(serialize = "INTERVAL")]
+    Interval,
+
+    /// JSONの値（JSON と JSONB を区別しない）
+    #[strum(serialize = "JSON")]
+    Json,
+
+    /// バイト列
+    #[strum(serialize = "BLOB")]
+    Blob,
+
+    #[strum(serialize = "UUID")]
+    Uuid,
+
+    /// 10進数の固定小数点数（精度、位取り）
+    #[strum(disabled)]
+    #[display(fmt = "Decimal({}, {})", _0, _1)]
+    Decimal(u8, u8),
+
+    /// 配列（要素の型）
+    #[strum(disabled)]
+    #[display(fmt = "{}[]", _0)]
+    Array(Box<DataType>),
+
+    /// 利用者定義の列挙型（型名で表示する）
+    #[strum(disabled)]
+    #[display(fmt = "{}", "_0.name")]
+    Enum(Arc<EnumType>),
+
     #[strum(serialize = "NULL")]
     Null,
 }
 
 impl DataType {
+    /// DECIMAL の精度の上限
+    pub const MAX_DECIMAL_PRECISION: u8 = 28;
+
+    /// DECIMAL 同士の除算や平均で、少なくとも確保する小数部の桁数
+    pub const DECIMAL_DIVISION_SCALE: u8 = 16;
+
     pub fn is_nullable(&self) -> bool {
         // 今はすべての型でNULLを許容することにします
         // 後で NOT NULL 制約を実装する際に変更します
@@ -53,10 +102,101 @@ impl DataType {
         matches!(self, DataType::Timestamp)
     }
 
+    pub fn is_timestamptz(&self) -> bool {
+        matches!(self, DataType::TimestampTz)
+    }
+
+    pub fn is_date(&self) -> bool {
+        matches!(self, DataType::Date)
+    }
+
+    pub fn is_time(&self) -> bool {
+        matches!(self, DataType::Time)
+    }
+
+    pub fn is_interval(&self) -> bool {
+        matches!(self, DataType::Interval)
+    }
+
+    pub fn is_json(&self) -> bool {
+        matches!(self, DataType::Json)
+    }
+
+    pub fn is_blob(&self) -> bool {
+        matches!(self, DataType::Blob)
+    }
+
+    pub fn is_uuid(&self) -> bool {
+        matches!(self, DataType::Uuid)
+    }
+
+    pub fn is_decimal(&self) -> bool {
+        matches!(self, DataType::Decimal(..))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, DataType::Array(_))
+    }
+
+    pub fn is_enum(&self) -> bool {
+        matches!(self, DataType::Enum(_))
+    }
+
     pub fn is_null(&self) -> bool {
         matches!(self, DataType::Null)
     }
 
+    /// 同じ種類の型かどうか（DECIMAL は精度と位取りが異なっても同じ種類とみなす）
+    pub fn is_same_kind(&self, other: &DataType) -> bool {
+        match (self, other) {
+            (DataType::Decimal(..), DataType::Decimal(..)) => true,
+            // 要素がすべてNULLの配列は要素の型が決まらないので、どの配列とも同じ種類とみなす
+            (DataType::Array(a), DataType::Array(b)) => a.is_null() || b.is_null() || a.is_same_kind(b),
+            // ALTER TYPE でラベルが追加されても同じ型とみなす
+            (DataType::Enum(a), DataType::Enum(b)) => a.name == b.name,
+            (a, b) => a == b,
+        }
+    }
+
+    /// UNION などで2つの型の値を1つのカラムにまとめるときの型（まとめられない場合はNone）
+    pub fn common_type(&self, other: &DataType) -> Option<DataType> {
+        match (self, other) {
+            (a, b) if a == b => Some(a.clone()),
+            (DataType::Null, t) | (t, DataType::Null) => Some(t.clone()),
+            (DataType::Integer, DataType::Float) | (DataType::Float, DataType::Integer) => Some(DataType::Float),
+            // 整数部と小数部の桁数をそれぞれ大きい方に合わせる
+            (DataType::Decimal(p1, s1), DataType::Decimal(p2, s2)) => {
+                let scale = *s1.max(s2);
+                let precision = (p1 - s1).max(p2 - s2) + scale;
+                Some(DataType::Decimal(precision.min(Self::MAX_DECIMAL_PRECISION), scale))
+            },
+            (DataType::Decimal(_, scale), DataType::Integer) | (DataType::Integer, DataType::Decimal(_, scale)) => {
+                Some(DataType::Decimal(Self::MAX_DECIMAL_PRECISION, *scale))
+            },
+            (DataType::Decimal(..), DataType::Float) | (DataType::Float, DataType::Decimal(..)) => Some(DataType::Float),
+            // 日付はその日の0時の日時として扱い、タイムゾーン付きの日時と混ざればタイムゾーン付きにする
+            (DataType::Date, DataType::Timestamp) | (DataType::Timestamp, DataType::Date) => Some(DataType::Timestamp),
+            (DataType::Date | DataType::Timestamp, DataType::TimestampTz) |
+            (DataType::TimestampTz, DataType::Date | DataType::Timestamp) => Some(DataType::TimestampTz),
+            // 配列は要素の型をまとめる
+            (DataType::Array(a), DataType::Array(b)) => Some(DataType::Array(Box::new(a.common_type(b)?))),
+            // 同じ列挙型はラベルの多い（新しい）方の定義にまとめる
+            (DataType::Enum(a), DataType::Enum(b)) if a.name == b.name => {
+                Some(if b.labels.len() > a.labels.len() { other.clone() } else { self.clone() })
+            },
+            _ => None,
+        }
+    }
+
+    /// 配列の要素の型（配列でなければNone）
+    pub fn element_type(&self) -> Option<&DataType> {
+        match self {
+            DataType::Array(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self, String> {
         match s.to_uppercase().as_str() {
             "INTEGER" | "INT" => Ok(DataType::Integer),
@@ -64,13 +204,92 @@ impl DataType {
             "TEXT" | "VARCHAR" | "CHAR" | "STRING" => Ok(DataType::Text),
             "BOOLEAN" | "BOOL" => Ok(DataType::Boolean),
             "TIMESTAMP" | "DATETIME" => Ok(DataType::Timestamp),
+            "TIMESTAMPTZ" => Ok(DataType::TimestampTz),
+            "DATE" => Ok(DataType::Date),
+            "TIME" => Ok(DataType::Time),
+            "INTERVAL" => Ok(DataType::Interval),
+            "JSON" | "JSONB" => Ok(DataType::Json),
+            "BLOB" | "BYTEA" | "BINARY" | "VARBINARY" => Ok(DataType::Blob),
+            "UUID" => Ok(DataType::Uuid),
+            "DECIMAL" | "NUMERIC" => Ok(DataType::Decimal(Self::MAX_DECIMAL_PRECISION, 0)),
             "NULL" => Ok(DataType::Null),
+            // INTEGER[] のように末尾の [] で配列を表す
+            t if t.ends_with("[]") => Ok(DataType::Array(Box::new(Self::from_str(&t[..t.len() - 2])?))),
             _ => Err(format!("Unsupported data type: {}", s)),
         }
     }
     
 }
 
+/// カラムの宣言で指定された長さや整数の幅
+///
+/// データ型そのものは変えずにカラムに保持し、格納する値を検査するときに使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypeLimit {
+    /// VARCHAR(n)：n文字まで
+    VarChar(usize),
+    /// CHAR(n)：n文字まで（短い値は末尾を空白で埋めてn文字にする）
+    Char(usize),
+    /// SMALLINT（16ビット）
+    SmallInt,
+    /// INT / INTEGER（32ビット）
+    Int,
+    /// BIGINT（64ビット）
+    BigInt,
+}
+
+impl TypeLimit {
+    /// 文字列の最大の文字数（長さの指定でなければNone）
+    pub fn max_length(&self) -> Option<usize> {
+        match self {
+            TypeLimit::VarChar(n) | TypeLimit::Char(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// CHAR(n) に格納する文字列を末尾の空白で埋めてn文字にする（CHAR(n) でなければNone）
+    ///
+    /// n文字を超える部分が空白だけなら切り詰める。それ以外の長すぎる値はそのまま返し、長さの検査で拒否する。
+    pub fn blank_pad(&self, text: &str) -> Option<String> {
+        let TypeLimit::Char(n) = self else {
+            return None;
+        };
+        let length = text.chars().count();
+        if length > *n {
+            let trimmed = text.trim_end_matches(' ');
+            let trimmed_length = trimmed.chars().count();
+            return Some(if trimmed_length <= *n {
+                format!("{}{}", trimmed, " ".repeat(n - trimmed_length))
+            } else {
+                text.to_string()
+            });
+        }
+        Some(format!("{}{}", text, " ".repeat(n - length)))
+    }
+
+    /// 整数の値の範囲（整数の幅の指定でなければNone）
+    pub fn integer_range(&self) -> Option<(i64, i64)> {
+        match self {
+            TypeLimit::SmallInt => Some((i16::MIN as i64, i16::MAX as i64)),
+            TypeLimit::Int => Some((i32::MIN as i64, i32::MAX as i64)),
+            TypeLimit::BigInt => Some((i64::MIN, i64::MAX)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TypeLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeLimit::VarChar(n) => write!(f, "VARCHAR({})", n),
+            TypeLimit::Char(n) => write!(f, "CHAR({})", n),
+            TypeLimit::SmallInt => write!(f, "SMALLINT"),
+            TypeLimit::Int => write!(f, "INTEGER"),
+            TypeLimit::BigInt => write!(f, "BIGINT"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Constraint {
     // 主キー制約
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+/// CREATE TYPE ... AS ENUM で定義される列挙型（ラベルは宣言順に並べる）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumType {
+    pub name: String,
+    pub labels: Vec<String>,
+}
+
+/// ALTER TYPE ... ADD VALUE で追加するラベルの位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelPosition {
+    Before(String),
+    After(String),
+}
+
+impl EnumType {
+    pub fn new(name: impl Into<String>, labels: Vec<String>) -> Self {
+        Self { name: name.into(), labels }
+    }
+
+    /// 名前だけを持つ列挙型
+    ///
+    /// パーサーは登録済みの型を知らないので、カラム定義ではこれを使い、実行時に同名の型の定義で置き換える。
+    pub fn named(name: impl Into<String>) -> Self {
+        Self::new(name, Vec::new())
+    }
+
+    /// ラベルの宣言順の位置
+    pub fn position(&self, label: &str) -> Option<usize> {
+        self.labels.iter().position(|l| l == label)
+    }
+}
+
+/// 列挙型の値（型の定義を共有し、宣言順で比較する）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumValue {
+    pub enum_type: Arc<EnumType>,
+    pub label: String,
+}
+
+impl EnumValue {
+    /// 型のラベルであれば値を作る
+    pub fn new(enum_type: &Arc<EnumType>, label: &str) -> Option<Self> {
+        enum_type.position(label)?;
+        Some(Self { enum_type: Arc::clone(enum_type), label: label.to_string() })
+    }
+
+    /// 同じ型のラベル同士を宣言順で比較する（異なる型や型にないラベルとは比較できない）
+    ///
+    /// ALTER TYPE でラベルが追加されても既存のラベルの順序は変わらないので、ラベルの多い方の定義で比較する。
+    pub fn compare_label(&self, enum_type: &EnumType, label: &str) -> Option<Ordering> {
+        if self.enum_type.name != enum_type.name {
+            return None;
+        }
+        let definition = if enum_type.labels.len() > self.enum_type.labels.len() { enum_type } else { &self.enum_type };
+        Some(definition.position(&self.label)?.cmp(&definition.position(label)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mood(labels: &[&str]) -> Arc<EnumType> {
+        Arc::new(EnumType::new("mood", labels.iter().map(|l| l.to_string()).collect()))
+    }
+
+    #[test]
+    fn labels_compare_in_declaration_order() {
+        let old = mood(&["sad", "happy"]);
+        let value = EnumValue::new(&old, "happy").unwrap();
+        assert_eq!(value.compare_label(&old, "sad"), Some(Ordering::Greater));
+
+        // 値が追加前の定義を持っていても、追加されたラベルと比較できる
+        let altered = mood(&["sad", "ok", "happy"]);
+        assert_eq!(value.compare_label(&altered, "ok"), Some(Ordering::Greater));
+        assert_eq!(EnumValue::new(&altered, "ok").unwrap().compare_label(&old, "sad"), Some(Ordering::Greater));
+    }
+
+    #[test]
+    fn unknown_labels_and_other_types_do_not_compare() {
+        let mood = mood(&["sad", "happy"]);
+        assert!(EnumValue::new(&mood, "angry").is_none());
+
+        let value = EnumValue::new(&mood, "sad").unwrap();
+        assert_eq!(value.compare_label(&mood, "angry"), None);
+        let other = EnumType::new("color", vec!["sad".to_string()]);
+        assert_eq!(value.compare_label(&other, "sad"), None);
+    }
+}
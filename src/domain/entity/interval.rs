@@ -0,0 +1,227 @@
+use chrono::{Duration, Months, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 1日のマイクロ秒数
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// 時間間隔（PostgreSQL と同様に月・日・マイクロ秒を別々に保持する）
+///
+/// 月の日数や日の長さは加算する日時によって変わるため、正規化せずに保持する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub micros: i64,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, micros: i64) -> Self {
+        Self { months, days, micros }
+    }
+
+    /// 比較に使う近似的な長さ（1か月を30日、1日を24時間とみなす）
+    pub fn approximate_micros(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * MICROS_PER_DAY as i128 + self.micros as i128
+    }
+
+    /// '1 year 2 months', '3 days 04:05:06', '-1.5 hours' のような文字列を解析する
+    pub fn parse(text: &str) -> Option<Interval> {
+        let mut interval = Interval::default();
+        let mut tokens = text.split_whitespace().peekable();
+        tokens.peek()?;
+
+        while let Some(token) = tokens.next() {
+            // HH:MM[:SS[.ffffff]]
+            if token.contains(':') {
+                interval.micros = interval.micros.checked_add(parse_clock(token)?)?;
+                continue;
+            }
+
+            let amount: f64 = token.parse().ok()?;
+            let unit = tokens.next()?.to_lowercase();
+            let unit = match unit.as_str() {
+                "ms" | "us" => unit.as_str(),
+                _ => unit.trim_end_matches('s'),
+            };
+            let micros_per_unit = match unit {
+                "year" | "yr" | "y" => {
+                    interval.months = interval.months.checked_add(whole(amount * 12.0)?)?;
+                    continue;
+                },
+                "mon" | "month" => {
+                    interval.months = interval.months.checked_add(whole(amount)?)?;
+                    continue;
+                },
+                "week" | "w" => 7 * MICROS_PER_DAY,
+                "day" | "d" => MICROS_PER_DAY,
+                "hour" | "hr" | "h" => 3_600_000_000,
+                "minute" | "min" | "m" => 60_000_000,
+                "second" | "sec" | "" => 1_000_000,
+                "millisecond" | "ms" => 1_000,
+                "microsecond" | "us" => 1,
+                _ => return None,
+            };
+
+            // 日以上の単位は整数部分を日数に、端数を時間に加える
+            let total = amount * micros_per_unit as f64;
+            if micros_per_unit >= MICROS_PER_DAY {
+                let days = (total / MICROS_PER_DAY as f64).trunc();
+                interval.days = interval.days.checked_add(days as i32)?;
+                interval.micros = interval.micros.checked_add((total - days * MICROS_PER_DAY as f64).round() as i64)?;
+            } else {
+                interval.micros = interval.micros.checked_add(total.round() as i64)?;
+            }
+        }
+
+        Some(interval)
+    }
+
+    pub fn checked_add(&self, other: &Interval) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_add(other.months)?,
+            days: self.days.checked_add(other.days)?,
+            micros: self.micros.checked_add(other.micros)?,
+        })
+    }
+
+    pub fn checked_neg(&self) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_neg()?,
+            days: self.days.checked_neg()?,
+            micros: self.micros.checked_neg()?,
+        })
+    }
+
+    /// 間隔を数倍する（月や日の端数は下の単位に繰り下げる）
+    pub fn checked_mul(&self, factor: f64) -> Option<Interval> {
+        let months = self.months as f64 * factor;
+        let days = self.days as f64 * factor + months.fract() * 30.0;
+        let micros = self.micros as f64 * factor + days.fract() * MICROS_PER_DAY as f64;
+        if !micros.is_finite() || months.abs() > i32::MAX as f64 || days.abs() > i32::MAX as f64 || micros.abs() > i64::MAX as f64 {
+            return None;
+        }
+        Some(Interval::new(months.trunc() as i32, days.trunc() as i32, micros.round() as i64))
+    }
+
+    /// 日時に間隔を加える（月、日、時間の順に加算する）
+    pub fn add_to(&self, timestamp: NaiveDateTime) -> Option<NaiveDateTime> {
+        let timestamp = match self.months {
+            0 => timestamp,
+            m if m > 0 => timestamp.checked_add_months(Months::new(m as u32))?,
+            m => timestamp.checked_sub_months(Months::new(m.unsigned_abs()))?,
+        };
+        timestamp
+            .checked_add_signed(Duration::days(self.days as i64))?
+            .checked_add_signed(Duration::microseconds(self.micros))
+    }
+
+    /// 時刻に間隔を加える（月と日は無視し、24時間を超えた分は翌日に回り込む）
+    pub fn add_to_time(&self, time: NaiveTime) -> NaiveTime {
+        let micros = self.micros.rem_euclid(MICROS_PER_DAY);
+        time.overflowing_add_signed(Duration::microseconds(micros)).0
+    }
+
+    /// 0時からの経過時間
+    pub fn since_midnight(time: NaiveTime) -> Interval {
+        let micros = time.num_seconds_from_midnight() as i64 * 1_000_000 + (time.nanosecond() / 1_000) as i64;
+        Interval::new(0, 0, micros)
+    }
+
+    /// ISO 8601 の期間の形式（'P1Y2M3DT4H5M6.5S'）で書き出す
+    ///
+    /// PostgreSQL の iso_8601 形式と同様に、負の量は各要素に符号を付ける。
+    pub fn to_iso8601(&self) -> String {
+        let mut text = String::from("P");
+        let (years, months) = (self.months / 12, self.months % 12);
+        for (amount, unit) in [(years, 'Y'), (months, 'M'), (self.days, 'D')] {
+            if amount != 0 {
+                text.push_str(&format!("{}{}", amount, unit));
+            }
+        }
+
+        let micros = self.micros;
+        let (hours, minutes) = (micros / 3_600_000_000, micros / 60_000_000 % 60);
+        let seconds = micros % 60_000_000;
+        if micros != 0 || text.len() == 1 {
+            text.push('T');
+            if hours != 0 {
+                text.push_str(&format!("{}H", hours));
+            }
+            if minutes != 0 {
+                text.push_str(&format!("{}M", minutes));
+            }
+            if seconds != 0 || text.ends_with('T') {
+                let sign = if seconds < 0 { "-" } else { "" };
+                let seconds = seconds.unsigned_abs();
+                text.push_str(&format!("{}{}", sign, seconds / 1_000_000));
+                if !seconds.is_multiple_of(1_000_000) {
+                    text.push_str(format!(".{:06}", seconds % 1_000_000).trim_end_matches('0'));
+                }
+                text.push('S');
+            }
+        }
+        text
+    }
+
+    /// 2つの日時の差を日数と時間の間隔として求める
+    pub fn between(start: NaiveDateTime, end: NaiveDateTime) -> Option<Interval> {
+        let micros = end.signed_duration_since(start).num_microseconds()?;
+        Some(Interval::new(0, i32::try_from(micros / MICROS_PER_DAY).ok()?, micros % MICROS_PER_DAY))
+    }
+}
+
+/// 整数でなければならない量（月数）を取り出す
+fn whole(amount: f64) -> Option<i32> {
+    let rounded = amount.round();
+    ((amount - rounded).abs() < 1e-9 && rounded.abs() <= i32::MAX as f64).then_some(rounded as i32)
+}
+
+/// HH:MM[:SS[.ffffff]] をマイクロ秒に変換する
+fn parse_clock(token: &str) -> Option<i64> {
+    let (negative, token) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token),
+    };
+    let mut parts = token.split(':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = match parts.next() {
+        Some(seconds) => seconds.parse().ok()?,
+        None => 0.0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let micros = (hours * 3600 + minutes * 60) * 1_000_000 + (seconds * 1_000_000.0).round() as i64;
+    Some(if negative { -micros } else { micros })
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        let (years, months) = (self.months / 12, self.months % 12);
+        let plural = |n: i32, unit: &str| format!("{} {}{}", n, unit, if n.abs() == 1 { "" } else { "s" });
+        if years != 0 {
+            parts.push(plural(years, "year"));
+        }
+        if months != 0 {
+            parts.push(plural(months, "mon"));
+        }
+        if self.days != 0 {
+            parts.push(plural(self.days, "day"));
+        }
+        if self.micros != 0 || parts.is_empty() {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            let micros = self.micros.unsigned_abs();
+            let seconds = micros / 1_000_000;
+            let mut clock = format!("{}{:02}:{:02}:{:02}", sign, seconds / 3600, seconds / 60 % 60, seconds % 60);
+            if !micros.is_multiple_of(1_000_000) {
+                clock.push_str(format!(".{:06}", micros % 1_000_000).trim_end_matches('0'));
+            }
+            parts.push(clock);
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
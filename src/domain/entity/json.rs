@@ -0,0 +1,147 @@
+use std::cmp::Ordering;
+
+use serde_json::Value as JsonValue;
+
+/// JSONのオブジェクトからキーの要素を、配列から位置の要素を取り出す
+///
+/// 配列の位置は0から数え、負の値は末尾から数える。
+pub fn json_get<'a>(json: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    match json {
+        JsonValue::Object(map) => map.get(key),
+        JsonValue::Array(items) => {
+            let index: i64 = key.trim().parse().ok()?;
+            let index = if index < 0 { index + items.len() as i64 } else { index };
+            items.get(usize::try_from(index).ok()?)
+        },
+        _ => None,
+    }
+}
+
+/// パスに沿って要素を順にたどる（空のパスはJSON全体を指す）
+pub fn json_path<'a>(json: &'a JsonValue, path: &[String]) -> Option<&'a JsonValue> {
+    path.iter().try_fold(json, |json, key| json_get(json, key))
+}
+
+/// '{a,b,0}' のような配列リテラルの形式のパスを解析する（要素は二重引用符で囲んでもよい）
+pub fn parse_json_path(text: &str) -> Option<Vec<String>> {
+    let inner = text.trim().strip_prefix('{')?.strip_suffix('}')?;
+    if inner.trim().is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut path = Vec::new();
+    let mut chars = inner.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut key = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => key.push(chars.next()?),
+                    c => key.push(c),
+                }
+            }
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                key.push(c);
+            }
+            key.truncate(key.trim_end().len());
+        }
+        path.push(key);
+
+        match chars.next() {
+            Some(',') => continue,
+            None => return Some(path),
+            Some(_) => return None,
+        }
+    }
+}
+
+/// `container` が `contained` を含むかどうか（@> 演算子）
+///
+/// オブジェクトは `contained` のすべてのキーについて値を含む場合、配列は `contained` の各要素を
+/// いずれかの要素が含む場合に含むとみなす。配列はその要素であるスカラー値も含む。
+pub fn json_contains(container: &JsonValue, contained: &JsonValue) -> bool {
+    match (container, contained) {
+        (JsonValue::Object(outer), JsonValue::Object(inner)) => inner.iter()
+            .all(|(key, value)| outer.get(key).is_some_and(|outer| json_contains(outer, value))),
+        (JsonValue::Array(outer), JsonValue::Array(inner)) => inner.iter()
+            .all(|value| outer.iter().any(|outer| json_contains(outer, value))),
+        (JsonValue::Array(outer), scalar) if !scalar.is_object() => outer.contains(scalar),
+        (a, b) => a == b,
+    }
+}
+
+/// JSONの値の種類の名前（'object', 'array', 'string', 'number', 'boolean', 'null'）
+pub fn json_type_name(json: &JsonValue) -> &'static str {
+    match json {
+        JsonValue::Object(_) => "object",
+        JsonValue::Array(_) => "array",
+        JsonValue::String(_) => "string",
+        JsonValue::Number(_) => "number",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Null => "null",
+    }
+}
+
+/// JSONの値を比較する
+///
+/// 種類の異なる値は null < 文字列 < 数値 < 真偽値 < 配列 < オブジェクト の順とし、
+/// 配列とオブジェクトは要素の数が多い方を大きいとみなしてから要素を順に比較する。
+pub fn json_compare(a: &JsonValue, b: &JsonValue) -> Ordering {
+    let rank = |json: &JsonValue| match json {
+        JsonValue::Null => 0,
+        JsonValue::String(_) => 1,
+        JsonValue::Number(_) => 2,
+        JsonValue::Bool(_) => 3,
+        JsonValue::Array(_) => 4,
+        JsonValue::Object(_) => 5,
+    };
+    match (a, b) {
+        (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+        (JsonValue::Number(a), JsonValue::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => a.as_f64().partial_cmp(&b.as_f64()).unwrap_or(Ordering::Equal),
+        },
+        (JsonValue::Bool(a), JsonValue::Bool(b)) => a.cmp(b),
+        (JsonValue::Array(a), JsonValue::Array(b)) => a.len().cmp(&b.len())
+            .then_with(|| a.iter().zip(b).map(|(a, b)| json_compare(a, b)).find(|o| o.is_ne()).unwrap_or(Ordering::Equal)),
+        (JsonValue::Object(a), JsonValue::Object(b)) => a.len().cmp(&b.len())
+            .then_with(|| a.iter().zip(b)
+                .map(|((ka, va), (kb, vb))| ka.cmp(kb).then_with(|| json_compare(va, vb)))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn json_get_reads_keys_and_array_positions() {
+        let doc = json!({"tags": ["x", "y"], "n": null});
+
+        assert_eq!(json_path(&doc, &["tags".to_string(), "-1".to_string()]), Some(&json!("y")));
+        assert_eq!(json_get(&doc, "n"), Some(&JsonValue::Null));
+        assert_eq!(json_get(&doc, "missing"), None);
+        assert_eq!(json_get(&doc["tags"], "2"), None);
+        assert_eq!(json_get(&doc["tags"], "x"), None);
+        assert_eq!(json_path(&doc, &[]), Some(&doc));
+    }
+
+    #[test]
+    fn parse_json_path_accepts_quoted_keys() {
+        let path = |keys: &[&str]| Some(keys.iter().map(|k| k.to_string()).collect::<Vec<_>>());
+
+        assert_eq!(parse_json_path("{tags, 0}"), path(&["tags", "0"]));
+        assert_eq!(parse_json_path(r#"{"a,b", "c\"d"}"#), path(&["a,b", "c\"d"]));
+        assert_eq!(parse_json_path("{}"), path(&[]));
+        assert_eq!(parse_json_path("tags,0"), None);
+        assert_eq!(parse_json_path(r#"{"a" b}"#), None);
+    }
+}
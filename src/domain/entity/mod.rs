@@ -1,10 +1,26 @@
+pub mod array;
+pub mod collation;
 pub mod data_type;
+pub mod enum_type;
+pub mod interval;
+pub mod json;
+pub mod time_zone;
+pub mod uuid;
 pub mod value;
 pub mod column;
 pub mod table;
+pub mod view;
 // src/domain/entity/mod.rs
 
-pub use data_type::{DataType, Constraint};
-pub use value::Value;
+pub use array::{array_element_type, parse_array};
+pub use collation::Collation;
+pub use data_type::{DataType, Constraint, TypeLimit};
+pub use enum_type::{EnumType, EnumValue, LabelPosition};
+pub use interval::Interval;
+pub use json::{json_compare, json_contains, json_get, json_path, json_type_name, parse_json_path};
+pub use time_zone::{format_time_zone, parse_time_zone};
+pub use uuid::Uuid;
+pub use value::{Value, ValueError, ValueKey};
 pub use column::Column;
-pub use table::{Table, Row, ResultSet, TableError};
\ No newline at end of file
+pub use table::{Table, Row, RowValues, ResultSet, TableError};
+pub use view::View;
\ No newline at end of file
@@ -1,4 +1,5 @@
 use crate::domain::entity::column::Column;
+use crate::domain::entity::value::Value;
 // use crate::domain::entity::data_type::{DataType, Constraint};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -90,7 +91,7 @@ impl  Table {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Row {
     /// カラム名と値のマッピング
-    pub values: HashMap<String, crate::domain::entity::value::Value>,
+    pub values: HashMap<String, Value>,
 }
 
 impl Row {
@@ -102,17 +103,17 @@ impl Row {
     }
 
     /// カラム名と値のペアから新しい行を作成する
-    pub fn from_values(values: HashMap<String, crate::domain::entity::value::Value>) -> Self {
+    pub fn from_values(values: HashMap<String, Value>) -> Self {
         Self { values }
     }
 
     /// 特定のカラムの値を取得する
-    pub fn get(&self, column_name: &str) -> Option<&crate::domain::entity::value::Value> {
+    pub fn get(&self, column_name: &str) -> Option<&Value> {
         self.values.get(column_name)
     }
 
     /// 特定のカラムの値を設定する
-    pub fn set(&mut self, column_name: impl Into<String>, value: crate::domain::entity::value::Value) {
+    pub fn set(&mut self, column_name: impl Into<String>, value: Value) {
         self.values.insert(column_name.into(), value);
     }
 }
@@ -123,6 +124,38 @@ impl Default for Row {
     }
 }
 
+/// 式の評価でカラムの値を取り出す行
+///
+/// カラム名で引く Row と、スキーマの順に値を並べたストレージ内の行を同じ式で評価するために使う。
+pub trait RowValues {
+    /// カラム名で値を取り出す
+    fn value(&self, column_name: &str) -> Option<&Value>;
+
+    /// カラムの位置で値を取り出す（位置を持たない行ではNone）
+    fn value_at(&self, index: usize) -> Option<&Value>;
+}
+
+impl RowValues for Row {
+    fn value(&self, column_name: &str) -> Option<&Value> {
+        self.values.get(column_name)
+    }
+
+    fn value_at(&self, _index: usize) -> Option<&Value> {
+        None
+    }
+}
+
+/// スキーマの順に値を並べた行（名前では引けないので、式は Table::get_column_index で位置に解決しておく）
+impl RowValues for [Value] {
+    fn value(&self, _column_name: &str) -> Option<&Value> {
+        None
+    }
+
+    fn value_at(&self, index: usize) -> Option<&Value> {
+        self.get(index)
+    }
+}
+
 /// クエリ結果セットを表現する
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ResultSet {
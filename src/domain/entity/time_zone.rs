@@ -0,0 +1,42 @@
+use chrono::FixedOffset;
+
+/// 'UTC', '+09:00', '-0530', '9' のようなタイムゾーンの指定を解析する
+///
+/// UTCからの時差だけを扱い、'Asia/Tokyo' のような地域名には対応しない。
+pub fn parse_time_zone(text: &str) -> Option<FixedOffset> {
+    let text = text.trim();
+    if ["UTC", "GMT", "Z"].iter().any(|name| text.eq_ignore_ascii_case(name)) {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, digits) = match text.as_bytes().first()? {
+        b'+' => (1, &text[1..]),
+        b'-' => (-1, &text[1..]),
+        _ => (1, text),
+    };
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if digits.len() == 4 => digits.split_at(2),
+        None => (digits, "0"),
+    };
+    if !hours.bytes().chain(minutes.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if hours > 15 || minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// タイムゾーンを '+09:00' の形式で書き出す（時差がなければ 'UTC'）
+pub fn format_time_zone(zone: FixedOffset) -> String {
+    let seconds = zone.local_minus_utc();
+    if seconds == 0 {
+        return "UTC".to_string();
+    }
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
@@ -0,0 +1,59 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// UUID（16バイトを上位から順に保持するので、バイト列の順序がそのまま値の順序になる）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// ランダムなバージョン4のUUIDを生成する
+    pub fn new_v4() -> Self {
+        let mut bytes: [u8; 16] = rand::thread_rng().gen();
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        Self(bytes)
+    }
+
+    /// 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11' のような文字列を解析する
+    ///
+    /// 大文字小文字は区別せず、ハイフンのない32桁の形式や波括弧で囲んだ形式も受け付ける。
+    pub fn parse(text: &str) -> Option<Uuid> {
+        let text = text.trim();
+        let text = text.strip_prefix('{').and_then(|t| t.strip_suffix('}')).unwrap_or(text);
+        let hex = match text.len() {
+            32 => text.to_string(),
+            36 if [8, 13, 18, 23].iter().all(|&i| text.as_bytes()[i] == b'-') => text.replace('-', ""),
+            _ => return None,
+        };
+        if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let mut bytes = [0u8; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                write!(f, "-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
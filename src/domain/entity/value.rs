@@ -1,7 +1,18 @@
-use chrono::{DateTime, Utc};
+use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use crate::domain::entity::array::{array_element_type, format_array, parse_array};
+use crate::domain::entity::collation::Collation;
 use crate::domain::entity::data_type::DataType;
+use crate::domain::entity::enum_type::EnumValue;
+use crate::domain::entity::interval::Interval;
+use crate::domain::entity::json::json_compare;
+use crate::domain::entity::uuid::Uuid;
 use thiserror::Error;
 
 // 値型エラーの定義
@@ -24,7 +35,19 @@ pub enum Value {
     Float(f64),
     Text(String),
     Boolean(bool),
-    Timestamp(DateTime<Utc>),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Interval(Interval),
+    Decimal(Decimal),
+    Json(serde_json::Value),
+    Bytes(Bytes),
+    Uuid(Uuid),
+    /// 配列（要素はNULLを含んでもよい）
+    Array(Vec<Value>),
+    /// 利用者定義の列挙型のラベル
+    Enum(EnumValue),
     Null,
 }
 
@@ -36,23 +59,49 @@ impl Value {
             Value::Text(_) => DataType::Text,
             Value::Boolean(_) => DataType::Boolean,
             Value::Timestamp(_) => DataType::Timestamp,
+            Value::TimestampTz(_) => DataType::TimestampTz,
+            Value::Date(_) => DataType::Date,
+            Value::Time(_) => DataType::Time,
+            Value::Interval(_) => DataType::Interval,
+            Value::Decimal(d) => DataType::Decimal(DataType::MAX_DECIMAL_PRECISION, d.scale() as u8),
+            Value::Json(_) => DataType::Json,
+            Value::Bytes(_) => DataType::Blob,
+            Value::Uuid(_) => DataType::Uuid,
+            Value::Array(items) => DataType::Array(Box::new(array_element_type(items))),
+            Value::Enum(e) => DataType::Enum(e.enum_type.clone()),
             Value::Null => DataType::Null,
         }
     }
 
     //指定したデータ型に変換する
-    pub fn cast_to(&self, target_type: DataType) -> Result<Value, ValueError> {
+    pub fn cast_to(&self, target_type: &DataType) -> Result<Value, ValueError> {
         match (self, target_type) {
             //NUllはどの型にも変換できる
             (Value::Null, _) => Ok(Value::Null),
 
+            //数値と文字列は位取りに合わせて丸めた10進数に変換する
+            (Value::Integer(_) | Value::Float(_) | Value::Text(_) | Value::Decimal(_) | Value::Json(_), DataType::Decimal(precision, scale)) => {
+                self.to_decimal()
+                    .and_then(|d| fit_decimal(d, *precision, *scale))
+                    .map(Value::Decimal)
+                    .ok_or_else(|| ValueError::ConversionError(
+                        self.to_string(), format!("DECIMAL({},{})", precision, scale)))
+            },
+
             //同じ型への変換はそのまま返す
-            (v, t) if v.data_type() == t => Ok(v.clone()),
+            (v, t) if v.data_type() == *t => Ok(v.clone()),
 
             //整数から他の型への変換
             (Value::Integer(i), DataType::Float) => Ok(Value::Float(*i as f64)),
             (Value::Integer(i), DataType::Text) => Ok(Value::Text(i.to_string())),
             (Value::Integer(i), DataType::Boolean) => Ok(Value::Boolean(*i != 0)),
+            // 整数は UNIX エポックからの秒数として扱う（タイムゾーンを持たない日時はUTCの日時とする）
+            (Value::Integer(i), DataType::Timestamp) => DateTime::from_timestamp(*i, 0)
+                .map(|dt| Value::Timestamp(dt.naive_utc()))
+                .ok_or_else(|| ValueError::ConversionError(i.to_string(), "TIMESTAMP".to_string())),
+            (Value::Integer(i), DataType::TimestampTz) => DateTime::from_timestamp(*i, 0)
+                .map(Value::TimestampTz)
+                .ok_or_else(|| ValueError::ConversionError(i.to_string(), "TIMESTAMPTZ".to_string())),
 
             //浮動小数点数から他の型への変換
             (Value::Float(f), DataType::Integer) => Ok(Value::Integer(*f as i64)),
@@ -73,16 +122,408 @@ impl Value {
                 "false" | "0" | "no" | "n" => Ok(Value::Boolean(false)),
                 _ => Err(ValueError::ConversionError(s.to_string(), "BOOLEAN".to_string())),
             },
+            (Value::Text(s), DataType::Interval) => Interval::parse(s)
+                .map(Value::Interval)
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), "INTERVAL".to_string())),
+            // タイムゾーンを持たない日時では文字列中のタイムゾーンを無視する
+            (Value::Text(s), DataType::Timestamp) => parse_timestamp(s)
+                .map(|(dt, _)| Value::Timestamp(dt))
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), "TIMESTAMP".to_string())),
+            (Value::Text(s), DataType::TimestampTz) => Value::parse_timestamptz(s, Utc.fix())
+                .map(Value::TimestampTz)
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), "TIMESTAMPTZ".to_string())),
+            // 日付は日時の文字列からも取り出せる
+            (Value::Text(s), DataType::Date) => NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok()
+                .or_else(|| parse_timestamp(s).map(|(dt, _)| dt.date()))
+                .map(Value::Date)
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), "DATE".to_string())),
+            (Value::Text(s), DataType::Time) => parse_time(s)
+                .map(Value::Time)
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), "TIME".to_string())),
+            (Value::Text(s), DataType::Json) => serde_json::from_str(s)
+                .map(Value::Json)
+                .map_err(|_| ValueError::ConversionError(s.to_string(), "JSON".to_string())),
+            (Value::Text(s), DataType::Uuid) => Uuid::parse(s)
+                .map(Value::Uuid)
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), "UUID".to_string())),
+            // '\x' で始まる文字列は16進数として、それ以外はUTF-8のバイト列として扱う
+            (Value::Text(s), DataType::Blob) => match s.strip_prefix("\\x") {
+                Some(hex) => Value::parse_hex(hex)
+                    .map(Value::Bytes)
+                    .ok_or_else(|| ValueError::ConversionError(s.to_string(), "BLOB".to_string())),
+                None => Ok(Value::Bytes(Bytes::copy_from_slice(s.as_bytes()))),
+            },
+
+            //日時から他の型への変換（タイムゾーンを持たない日時とタイムゾーン付きの日時はUTCで対応させる）
+            (Value::Timestamp(dt), DataType::Integer) => Ok(Value::Integer(dt.and_utc().timestamp())),
+            (Value::Timestamp(dt), DataType::Text) => Ok(Value::Text(dt.to_string())),
+            (Value::Timestamp(dt), DataType::Date) => Ok(Value::Date(dt.date())),
+            (Value::Timestamp(dt), DataType::Time) => Ok(Value::Time(dt.time())),
+            (Value::Timestamp(dt), DataType::TimestampTz) => Ok(Value::TimestampTz(dt.and_utc())),
+            (Value::TimestampTz(dt), DataType::Integer) => Ok(Value::Integer(dt.timestamp())),
+            (Value::TimestampTz(dt), DataType::Text) => Ok(Value::Text(dt.to_string())),
+            (Value::TimestampTz(dt), DataType::Date) => Ok(Value::Date(dt.date_naive())),
+            (Value::TimestampTz(dt), DataType::Time) => Ok(Value::Time(dt.time())),
+            (Value::TimestampTz(dt), DataType::Timestamp) => Ok(Value::Timestamp(dt.naive_utc())),
+
+            //日付と時刻から他の型への変換（日付はその日の0時とする）
+            (Value::Date(d), DataType::Timestamp) => Ok(Value::Timestamp(d.and_time(NaiveTime::MIN))),
+            (Value::Date(d), DataType::TimestampTz) => Ok(Value::TimestampTz(d.and_time(NaiveTime::MIN).and_utc())),
+            (Value::Date(d), DataType::Text) => Ok(Value::Text(d.to_string())),
+            (Value::Time(t), DataType::Text) => Ok(Value::Text(t.to_string())),
+            (Value::Time(t), DataType::Interval) => Ok(Value::Interval(Interval::since_midnight(*t))),
+
+            //10進数から他の型への変換（整数へは四捨五入する）
+            (Value::Decimal(d), DataType::Integer) => d
+                .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+                .to_i64()
+                .map(Value::Integer)
+                .ok_or_else(|| ValueError::ConversionError(d.to_string(), "INTEGER".to_string())),
+            (Value::Decimal(d), DataType::Float) => d
+                .to_f64()
+                .map(Value::Float)
+                .ok_or_else(|| ValueError::ConversionError(d.to_string(), "FLOAT".to_string())),
+            (Value::Decimal(d), DataType::Text) => Ok(Value::Text(d.to_string())),
+            (Value::Decimal(d), DataType::Boolean) => Ok(Value::Boolean(!d.is_zero())),
+
+            //時間間隔から文字列への変換
+            (Value::Interval(i), DataType::Text) => Ok(Value::Text(i.to_string())),
+
+            //JSONから他の型への変換（数値と真偽値はスカラー値の場合だけ変換できる）
+            (Value::Json(json), DataType::Text) => Ok(Value::Text(json.to_string())),
+            (Value::Json(serde_json::Value::Number(n)), DataType::Float) => n.as_f64()
+                .map(Value::Float)
+                .ok_or_else(|| ValueError::ConversionError(n.to_string(), "FLOAT".to_string())),
+            (Value::Json(serde_json::Value::Number(n)), DataType::Integer) => Value::Text(n.to_string())
+                .to_decimal()
+                .ok_or_else(|| ValueError::ConversionError(n.to_string(), "INTEGER".to_string()))
+                .and_then(|d| Value::Decimal(d).cast_to(&DataType::Integer)),
+            (Value::Json(serde_json::Value::Bool(b)), DataType::Boolean) => Ok(Value::Boolean(*b)),
+
+            //バイト列から文字列への変換（'\x' に続く16進数で表す）
+            (Value::Bytes(_), DataType::Text) => Ok(Value::Text(self.to_string())),
+
+            //UUIDから文字列への変換
+            (Value::Uuid(u), DataType::Text) => Ok(Value::Text(u.to_string())),
+
+            //配列は要素ごとに変換する（文字列は '{1,2,3}' の形式として解析する）
+            (Value::Array(items), DataType::Array(element)) => items.iter()
+                .map(|item| item.cast_to(element))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            (Value::Text(s), DataType::Array(_)) => parse_array(s)
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), target_type.to_string()))
+                .and_then(|items| Value::Array(items).cast_to(target_type)),
+            (Value::Array(_), DataType::Text) => Ok(Value::Text(self.to_string())),
+
+            //列挙型は型に定義されたラベルの文字列とだけ相互に変換する（同じ型の別の定義へはラベルで対応させる）
+            (Value::Text(label), DataType::Enum(enum_type)) |
+            (Value::Enum(EnumValue { label, .. }), DataType::Enum(enum_type)) => EnumValue::new(enum_type, label)
+                .map(Value::Enum)
+                .ok_or_else(|| ValueError::ConversionError(label.to_string(), enum_type.name.clone())),
+            (Value::Enum(e), DataType::Text) => Ok(Value::Text(e.label.clone())),
 
             // その他の変換はエラー
             (value, target) => Err(ValueError::TypeMismatch {
-                expected: target,
+                expected: target.clone(),
                 actual: value.data_type(),
             }),
         }
     }
 }
 
+impl Value {
+    /// 指定したデータ型に変換する（タイムゾーン付きの日時と他の日時や文字列は `time_zone` の日時として対応させる）
+    pub fn cast_in_time_zone(&self, target_type: &DataType, time_zone: FixedOffset) -> Result<Value, ValueError> {
+        if time_zone.local_minus_utc() == 0 {
+            return self.cast_to(target_type);
+        }
+        let local = |dt: &DateTime<Utc>| dt.with_timezone(&time_zone).naive_local();
+        let at_zone = |dt: NaiveDateTime| time_zone.from_local_datetime(&dt).single()
+            .map(|dt| Value::TimestampTz(dt.with_timezone(&Utc)))
+            .ok_or_else(|| ValueError::ConversionError(dt.to_string(), "TIMESTAMPTZ".to_string()));
+        match (self, target_type) {
+            (Value::TimestampTz(dt), DataType::Timestamp) => Ok(Value::Timestamp(local(dt))),
+            (Value::TimestampTz(dt), DataType::Date) => Ok(Value::Date(local(dt).date())),
+            (Value::TimestampTz(dt), DataType::Time) => Ok(Value::Time(local(dt).time())),
+            (Value::TimestampTz(dt), DataType::Text) => Ok(Value::Text(dt.with_timezone(&time_zone).to_string())),
+            (Value::Timestamp(dt), DataType::TimestampTz) => at_zone(*dt),
+            (Value::Date(d), DataType::TimestampTz) => at_zone(d.and_time(NaiveTime::MIN)),
+            (Value::Text(s), DataType::TimestampTz) => Value::parse_timestamptz(s, time_zone)
+                .map(Value::TimestampTz)
+                .ok_or_else(|| ValueError::ConversionError(s.to_string(), "TIMESTAMPTZ".to_string())),
+            (Value::Array(items), DataType::Array(element)) => items.iter()
+                .map(|item| item.cast_in_time_zone(element, time_zone))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            _ => self.cast_to(target_type),
+        }
+    }
+
+    /// 数値や数値を表す文字列を10進数として取り出す（浮動小数点数は最短の10進表記を使う）
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Integer(i) => Some(Decimal::from(*i)),
+            Value::Float(f) if f.is_finite() => f.to_string().parse().ok().or_else(|| Decimal::from_f64(*f)),
+            Value::Text(s) => s.trim().parse().ok().or_else(|| Decimal::from_scientific(s.trim()).ok()),
+            Value::Decimal(d) => Some(*d),
+            Value::Json(serde_json::Value::Number(n)) => Value::Text(n.to_string()).to_decimal(),
+            _ => None,
+        }
+    }
+}
+
+/// 10進数を位取りに合わせて丸め、精度に収まるかを確認する
+fn fit_decimal(value: Decimal, precision: u8, scale: u8) -> Option<Decimal> {
+    let mut rounded = value.round_dp_with_strategy(scale as u32, RoundingStrategy::MidpointAwayFromZero);
+    rounded.rescale(scale as u32);
+    // 整数部の桁数は精度から位取りを引いた桁数まで
+    let integer_digits = (precision - scale) as u32;
+    let limit = Decimal::from_i128_with_scale(10i128.pow(integer_digits), 0);
+    (rounded.scale() == scale as u32 && rounded.abs().trunc() < limit).then_some(rounded)
+}
+
+impl Value {
+    /// タイムゾーン付きの日時の文字列を解析する（タイムゾーンの指定がなければ `zone` の日時とみなす）
+    pub fn parse_timestamptz(text: &str, zone: FixedOffset) -> Option<DateTime<Utc>> {
+        let (dt, offset) = parse_timestamp(text)?;
+        offset.unwrap_or(zone)
+            .from_local_datetime(&dt)
+            .single()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+/// '2024-01-01 12:34:56', '2024-01-01T12:34:56+09:00', '2024-01-01' のような日時の文字列を解析する
+///
+/// 日時とタイムゾーン（指定されていればその時差）を返す。日付だけの場合はその日の0時になる。
+fn parse_timestamp(text: &str) -> Option<(NaiveDateTime, Option<FixedOffset>)> {
+    let text = text.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some((dt.naive_local(), Some(*dt.offset())));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S%.f%#z", "%Y-%m-%dT%H:%M:%S%.f%#z", "%Y-%m-%d %H:%M:%S%.f %:z"] {
+        if let Ok(dt) = DateTime::parse_from_str(text, format) {
+            return Some((dt.naive_local(), Some(*dt.offset())));
+        }
+    }
+
+    // 値を文字列に変換した形式（末尾に UTC が付く）も受け付ける
+    let (text, offset) = match text.strip_suffix(" UTC") {
+        Some(text) => (text, Some(Utc.fix())),
+        None => (text, None),
+    };
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Some((dt, offset));
+        }
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+        .map(|date| (date.and_time(NaiveTime::MIN), offset))
+}
+
+/// '12:34', '12:34:56', '12:34:56.789' のような時刻の文字列を解析する
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.trim();
+    ["%H:%M:%S%.f", "%H:%M"].iter()
+        .find_map(|format| NaiveTime::parse_from_str(text, format).ok())
+}
+
+impl Value {
+    /// 16進数の文字列をバイト列に変換する（桁数が奇数の場合や16進数でない文字を含む場合はNone）
+    pub fn parse_hex(hex: &str) -> Option<Bytes> {
+        if !hex.len().is_multiple_of(2) {
+            return None;
+        }
+        (0..hex.len()).step_by(2)
+            .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+            .collect::<Option<Vec<_>>>()
+            .map(Bytes::from)
+    }
+}
+
+impl Value {
+    /// 2つの値を比較する（NULLや比較できない型の組み合わせの場合はNone）
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => Some(a.cmp(b)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Integer(a), Value::Float(b)) => (*a as f64).partial_cmp(b),
+            (Value::Float(a), Value::Integer(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+            (Value::Timestamp(a), Value::Timestamp(b)) => Some(a.cmp(b)),
+            (Value::TimestampTz(a), Value::TimestampTz(b)) => Some(a.cmp(b)),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+            (Value::Time(a), Value::Time(b)) => Some(a.cmp(b)),
+            // 日付やタイムゾーンを持たない日時はUTCの日時として比較する
+            (Value::Date(_) | Value::Timestamp(_), Value::Date(_) | Value::Timestamp(_) | Value::TimestampTz(_)) |
+            (Value::TimestampTz(_), Value::Date(_) | Value::Timestamp(_)) => {
+                self.as_utc()?.partial_cmp(&other.as_utc()?)
+            },
+            (Value::Interval(a), Value::Interval(b)) => Some(a.approximate_micros().cmp(&b.approximate_micros())),
+            (Value::Decimal(a), Value::Decimal(b)) => Some(a.cmp(b)),
+            (Value::Json(a), Value::Json(b)) => Some(json_compare(a, b)),
+            (Value::Bytes(a), Value::Bytes(b)) => Some(a.cmp(b)),
+            (Value::Uuid(a), Value::Uuid(b)) => Some(a.cmp(b)),
+            // 列挙型は宣言順で比較し、文字列はその型のラベルとして比較する
+            (Value::Enum(a), Value::Enum(b)) => a.compare_label(&b.enum_type, &b.label),
+            (Value::Enum(a), Value::Text(b)) => a.compare_label(&a.enum_type, b),
+            (Value::Text(a), Value::Enum(b)) => b.compare_label(&b.enum_type, a).map(Ordering::reverse),
+            // 配列は要素を順に比較し、すべて等しければ要素の少ない方を小さいとする（NULLの要素は他の値より大きい）
+            (Value::Array(a), Value::Array(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    let ordering = match (a, b) {
+                        (Value::Null, Value::Null) => Ordering::Equal,
+                        (Value::Null, _) => Ordering::Greater,
+                        (_, Value::Null) => Ordering::Less,
+                        (a, b) => a.compare(b)?,
+                    };
+                    if ordering.is_ne() {
+                        return Some(ordering);
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            },
+            (Value::Decimal(_), Value::Integer(_) | Value::Float(_)) |
+            (Value::Integer(_) | Value::Float(_), Value::Decimal(_)) => match (self.to_decimal(), other.to_decimal()) {
+                (Some(a), Some(b)) => Some(a.cmp(&b)),
+                // 10進数で表せない浮動小数点数（無限大など）は浮動小数点数として比較する
+                _ => self.as_f64()?.partial_cmp(&other.as_f64()?),
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    fn as_utc(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Value::Timestamp(dt) => Some(dt.and_utc()),
+            Value::TimestampTz(dt) => Some(*dt),
+            Value::Date(d) => Some(d.and_time(NaiveTime::MIN).and_utc()),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            Value::Decimal(d) => d.to_f64(),
+            _ => None,
+        }
+    }
+}
+
+/// 値の組をハッシュキーとして扱うためのラッパー（GROUP BY などで使用）
+///
+/// 浮動小数点数はビット列で比較するため、NaN同士や 0.0 と -0.0 も同じキーになる。
+/// 整数、浮動小数点数、10進数は Value::compare で等しければ型が違っても同じキーになる。
+#[derive(Debug, Clone)]
+pub struct ValueKey(pub Vec<Value>);
+
+impl ValueKey {
+    /// 値ごとの照合順序で比較に使う形にしたキーを作る（照合順序のない値はそのまま）
+    pub fn collated(values: &[Value], collations: &[Option<Collation>]) -> ValueKey {
+        ValueKey(values.iter().zip(collations.iter().chain(std::iter::repeat(&None)))
+            .map(|(value, collation)| match collation {
+                Some(collation) => collation.normalize(value),
+                None => value.clone(),
+            })
+            .collect())
+    }
+
+    fn float_bits(f: f64) -> u64 {
+        if f == 0.0 {
+            0
+        } else if f.is_nan() {
+            f64::NAN.to_bits()
+        } else {
+            f.to_bits()
+        }
+    }
+
+    /// 数値をハッシュに使う10進数にする（10進数で表せない浮動小数点数は None）
+    fn numeric_key(value: &Value) -> Option<Decimal> {
+        let decimal = match value {
+            Value::Integer(i) => Decimal::from(*i),
+            // 整数値の浮動小数点数は文字列を介さずに変換する
+            Value::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Decimal::from(*f as i64),
+            Value::Float(_) => value.to_decimal()?,
+            Value::Decimal(d) => *d,
+            _ => return None,
+        };
+        // 1.0 と 1.00 は同じキーになる
+        Some(decimal.normalize())
+    }
+
+    /// 2つの値がキーとして等しいかどうか
+    fn value_eq(a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Float(x), Value::Float(y)) => Self::float_bits(*x) == Self::float_bits(*y),
+            (Value::Integer(_) | Value::Float(_) | Value::Decimal(_), Value::Integer(_) | Value::Float(_) | Value::Decimal(_)) => {
+                a.compare(b) == Some(Ordering::Equal)
+            },
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| Self::value_eq(a, b))
+            },
+            _ => a == b,
+        }
+    }
+
+    /// 値をハッシュする（配列は要素ごとに同じ規則でハッシュする）
+    fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+        // 型の違う数値も等しければ同じハッシュにする
+        if let Some(d) = Self::numeric_key(value) {
+            0u8.hash(state);
+            return d.hash(state);
+        }
+        std::mem::discriminant(value).hash(state);
+        match value {
+            Value::Integer(i) => i.hash(state),
+            Value::Float(f) => Self::float_bits(*f).hash(state),
+            Value::Text(s) => s.hash(state),
+            Value::Boolean(b) => b.hash(state),
+            Value::Timestamp(dt) => dt.hash(state),
+            Value::TimestampTz(dt) => dt.hash(state),
+            Value::Date(d) => d.hash(state),
+            Value::Time(t) => t.hash(state),
+            Value::Interval(i) => i.hash(state),
+            Value::Decimal(d) => d.hash(state),
+            // オブジェクトのキーは整列されているので、同じ値は同じ文字列になる
+            Value::Json(json) => json.to_string().hash(state),
+            Value::Bytes(b) => b.hash(state),
+            Value::Uuid(u) => u.hash(state),
+            Value::Enum(e) => {
+                e.enum_type.name.hash(state);
+                e.label.hash(state);
+            },
+            Value::Array(items) => {
+                items.len().hash(state);
+                items.iter().for_each(|item| Self::hash_value(item, state));
+            },
+            Value::Null => {},
+        }
+    }
+}
+
+impl PartialEq for ValueKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| Self::value_eq(a, b))
+    }
+}
+
+impl Eq for ValueKey {}
+
+impl Hash for ValueKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for value in &self.0 {
+            Self::hash_value(value, state);
+        }
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -91,6 +532,19 @@ impl fmt::Display for Value {
             Value::Text(s) => write!(f, "{}", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Timestamp(dt) => write!(f, "{}", dt),
+            Value::TimestampTz(dt) => write!(f, "{}", dt),
+            Value::Date(d) => write!(f, "{}", d),
+            Value::Time(t) => write!(f, "{}", t),
+            Value::Interval(i) => write!(f, "{}", i),
+            Value::Decimal(d) => write!(f, "{}", d),
+            Value::Json(json) => write!(f, "{}", json),
+            Value::Uuid(u) => write!(f, "{}", u),
+            Value::Array(items) => format_array(f, items),
+            Value::Enum(e) => write!(f, "{}", e.label),
+            Value::Bytes(b) => {
+                write!(f, "\\x")?;
+                b.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+            },
             Value::Null => write!(f, "NULL"),
         }
     }
@@ -116,8 +570,129 @@ impl From<bool> for Value {
         Value::Boolean(val)
     }
 }
+impl From<NaiveDateTime> for Value {
+    fn from(val: NaiveDateTime) -> Self {
+        Value::Timestamp(val)
+    }
+}
 impl From<DateTime<Utc>> for Value {
     fn from(val: DateTime<Utc>) -> Self {
-        Value::Timestamp(val)
+        Value::TimestampTz(val)
+    }
+}
+impl From<NaiveDate> for Value {
+    fn from(val: NaiveDate) -> Self {
+        Value::Date(val)
+    }
+}
+impl From<NaiveTime> for Value {
+    fn from(val: NaiveTime) -> Self {
+        Value::Time(val)
+    }
+}
+impl From<Interval> for Value {
+    fn from(val: Interval) -> Self {
+        Value::Interval(val)
+    }
+}
+impl From<serde_json::Value> for Value {
+    fn from(val: serde_json::Value) -> Self {
+        Value::Json(val)
+    }
+}
+impl From<Bytes> for Value {
+    fn from(val: Bytes) -> Self {
+        Value::Bytes(val)
+    }
+}
+impl From<Vec<u8>> for Value {
+    fn from(val: Vec<u8>) -> Self {
+        Value::Bytes(Bytes::from(val))
+    }
+}
+impl From<Vec<Value>> for Value {
+    fn from(val: Vec<Value>) -> Self {
+        Value::Array(val)
+    }
+}
+impl From<Uuid> for Value {
+    fn from(val: Uuid) -> Self {
+        Value::Uuid(val)
+    }
+}
+
+impl From<EnumValue> for Value {
+    fn from(val: EnumValue) -> Self {
+        Value::Enum(val)
+    }
+}
+impl From<Decimal> for Value {
+    fn from(val: Decimal) -> Self {
+        Value::Decimal(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn key(values: Vec<Value>) -> ValueKey {
+        ValueKey(values)
+    }
+
+    #[test]
+    fn numeric_keys_match_across_types() {
+        let set: HashSet<ValueKey> = [key(vec![Value::Integer(2)]), key(vec![Value::Decimal(Decimal::new(25, 1))])]
+            .into_iter()
+            .collect();
+        assert!(set.contains(&key(vec![Value::Float(2.0)])));
+        assert!(set.contains(&key(vec![Value::Decimal(Decimal::new(200, 2))])));
+        assert!(set.contains(&key(vec![Value::Float(2.5)])));
+        assert!(!set.contains(&key(vec![Value::Float(2.25)])));
+        assert!(!set.contains(&key(vec![Value::Text("2".to_string())])));
+    }
+
+    #[test]
+    fn float_keys_compare_by_bits() {
+        assert_eq!(key(vec![Value::Float(f64::NAN)]), key(vec![Value::Float(f64::NAN)]));
+        assert_eq!(key(vec![Value::Float(0.0)]), key(vec![Value::Float(-0.0)]));
+        assert_ne!(key(vec![Value::Float(f64::INFINITY)]), key(vec![Value::Integer(i64::MAX)]));
+    }
+
+    #[test]
+    fn array_keys_compare_elements() {
+        let a = key(vec![Value::Array(vec![Value::Integer(1), Value::Null])]);
+        let b = key(vec![Value::Array(vec![Value::Float(1.0), Value::Null])]);
+        assert_eq!(a, b);
+        assert_ne!(a, key(vec![Value::Array(vec![Value::Integer(1)])]));
+    }
+
+    #[test]
+    fn cast_to_converts_between_types() {
+        let text = |s: &str| Value::Text(s.to_string());
+        assert_eq!(text("42").cast_to(&DataType::Integer).unwrap(), Value::Integer(42));
+        assert_eq!(Value::Decimal(Decimal::new(37, 1)).cast_to(&DataType::Integer).unwrap(), Value::Integer(4));
+        assert_eq!(Value::Integer(1).cast_to(&DataType::Text).unwrap(), text("1"));
+        assert_eq!(text("true").cast_to(&DataType::Boolean).unwrap(), Value::Boolean(true));
+        assert_eq!(Value::Null.cast_to(&DataType::Integer).unwrap(), Value::Null);
+
+        assert!(text("abc").cast_to(&DataType::Integer).is_err());
+        assert!(text("2023-02-29").cast_to(&DataType::Timestamp).is_err());
+    }
+
+    #[test]
+    fn collated_keys_use_collation_per_value() {
+        let text = |s: &str| Value::Text(s.to_string());
+        let collations = [Some(Collation::CaseInsensitive), None];
+        let a = ValueKey::collated(&[text("Bob"), text("x")], &collations);
+        let b = ValueKey::collated(&[text("BOB"), text("x")], &collations);
+        assert_eq!(a, b);
+        assert_ne!(a, ValueKey::collated(&[text("Bob"), text("X")], &collations));
+
+        // 配列の要素の文字列も正規化し、文字列以外の値とNULLはそのまま
+        let a = ValueKey::collated(&[Value::Array(vec![text("A"), Value::Null]), Value::Integer(1)], &collations);
+        assert_eq!(a, key(vec![Value::Array(vec![text("a"), Value::Null]), Value::Integer(1)]));
+        assert_eq!(ValueKey::collated(&[text("B")], &[Some(Collation::Binary)]), key(vec![text("B")]));
     }
 }
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// 名前付きのクエリを表すビュー定義
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct View {
+    /// ビュー名
+    pub name: String,
+
+    /// ビューを定義するSELECT文（SQLテキスト）
+    pub query: String,
+
+    /// 結果カラムの別名（指定されていない場合は空）
+    pub columns: Vec<String>,
+
+    /// マテリアライズドビューかどうか（結果は同名のテーブルとして保持される）
+    pub materialized: bool,
+
+    /// ベーステーブルの変更に合わせて差分更新するかどうか
+    pub incremental: bool,
+}
+
+impl View {
+    pub fn new(name: impl Into<String>, query: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            query: query.into(),
+            columns: Vec::new(),
+            materialized: false,
+            incremental: false,
+        }
+    }
+
+    /// 結果カラムの別名を設定する
+    pub fn with_columns(mut self, columns: Vec<String>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// マテリアライズドビューとして扱う
+    pub fn materialized(mut self) -> Self {
+        self.materialized = true;
+        self
+    }
+
+    /// 差分更新するマテリアライズドビューとして扱う
+    pub fn incremental(mut self) -> Self {
+        self.materialized = true;
+        self.incremental = true;
+        self
+    }
+}
@@ -0,0 +1,1690 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, OnceLock};
+use chrono::{Days, FixedOffset, NaiveDate, NaiveTime};
+use rust_decimal::prelude::ToPrimitive;
+use thiserror::Error;
+
+use crate::domain::entity::{
+    json_contains, json_get, json_path, parse_json_path, Collation, Column, DataType, Interval, RowValues, Table, Value,
+    ValueKey,
+};
+use crate::domain::function::{ScalarFunctionRef, UserAggregateRef};
+
+/// 式の評価エラー
+#[derive(Error, Debug, PartialEq)]
+pub enum ExpressionError {
+    #[error("Column {0} not found")]
+    ColumnNotFound(String),
+
+    #[error("Aggregate function {0} is not allowed here")]
+    MisplacedAggregate(String),
+
+    #[error("Invalid operation: {0}")]
+    InvalidOperation(String),
+
+    #[error("Unknown function: {0}")]
+    UnknownFunction(String),
+}
+
+/// 集約関数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// 関数名から集約関数を取得する（大文字小文字は区別しない）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "COUNT" => Some(AggregateFunction::Count),
+            "SUM" => Some(AggregateFunction::Sum),
+            "AVG" => Some(AggregateFunction::Avg),
+            "MIN" => Some(AggregateFunction::Min),
+            "MAX" => Some(AggregateFunction::Max),
+            _ => None,
+        }
+    }
+
+    /// 引数の型から結果の型を求める
+    pub fn return_type(&self, argument_type: DataType) -> DataType {
+        match (self, &argument_type) {
+            (AggregateFunction::Count, _) => DataType::Integer,
+            // 10進数の合計と平均は10進数のまま求める
+            (AggregateFunction::Sum, &DataType::Decimal(_, scale)) => {
+                DataType::Decimal(DataType::MAX_DECIMAL_PRECISION, scale)
+            },
+            (AggregateFunction::Avg, &DataType::Decimal(_, scale)) => {
+                DataType::Decimal(DataType::MAX_DECIMAL_PRECISION, scale.max(DataType::DECIMAL_DIVISION_SCALE))
+            },
+            (AggregateFunction::Avg, _) => DataType::Float,
+            (AggregateFunction::Sum | AggregateFunction::Min | AggregateFunction::Max, _) => argument_type,
+        }
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateFunction::Count => write!(f, "COUNT"),
+            AggregateFunction::Sum => write!(f, "SUM"),
+            AggregateFunction::Avg => write!(f, "AVG"),
+            AggregateFunction::Min => write!(f, "MIN"),
+            AggregateFunction::Max => write!(f, "MAX"),
+        }
+    }
+}
+
+/// 二項演算子
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    /// 文字列の連結（||）
+    Concat,
+    /// JSONの要素をJSONとして取り出す（->）
+    JsonGet,
+    /// JSONの要素を文字列として取り出す（->>）
+    JsonGetText,
+    /// JSONのパスに沿った要素をJSONとして取り出す（#>）
+    JsonPath,
+    /// JSONのパスに沿った要素を文字列として取り出す（#>>）
+    JsonPathText,
+    /// 左のJSONが右のJSONを含むかどうか（@>）
+    JsonContains,
+    /// 左のJSONが右のJSONに含まれるかどうか（<@）
+    JsonContainedBy,
+}
+
+impl BinaryOperator {
+    /// 比較演算子の場合、比較結果が条件を満たすかどうかを返す
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            BinaryOperator::Eq => ordering == Ordering::Equal,
+            BinaryOperator::NotEq => ordering != Ordering::Equal,
+            BinaryOperator::Lt => ordering == Ordering::Less,
+            BinaryOperator::LtEq => ordering != Ordering::Greater,
+            BinaryOperator::Gt => ordering == Ordering::Greater,
+            BinaryOperator::GtEq => ordering != Ordering::Less,
+            _ => false,
+        }
+    }
+
+    /// 算術演算子（文字列の連結を含む）かどうか
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(self,
+            BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply |
+            BinaryOperator::Divide | BinaryOperator::Modulo | BinaryOperator::Concat)
+    }
+
+    /// JSONの演算子かどうか
+    pub fn is_json(&self) -> bool {
+        matches!(self,
+            BinaryOperator::JsonGet | BinaryOperator::JsonGetText | BinaryOperator::JsonPath |
+            BinaryOperator::JsonPathText | BinaryOperator::JsonContains | BinaryOperator::JsonContainedBy)
+    }
+
+    /// 結合の強さ（式を書き出すときの括弧の判定に使う）
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Eq | BinaryOperator::NotEq | BinaryOperator::Lt |
+            BinaryOperator::LtEq | BinaryOperator::Gt | BinaryOperator::GtEq => 3,
+            BinaryOperator::Concat => 4,
+            BinaryOperator::Plus | BinaryOperator::Minus => 5,
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 6,
+            // sqlparser と同じく、JSONの演算子は他のどの二項演算子よりも強く結合する
+            BinaryOperator::JsonGet | BinaryOperator::JsonGetText | BinaryOperator::JsonPath |
+            BinaryOperator::JsonPathText | BinaryOperator::JsonContains | BinaryOperator::JsonContainedBy => 7,
+        }
+    }
+
+    /// 算術演算を行う（どちらかがNULLならNULL）
+    ///
+    /// 整数同士の演算は整数（除算は切り捨て）、浮動小数点数を含む場合は浮動小数点数になる。
+    /// 10進数と整数の演算は10進数のまま誤差なく計算する（除算は小数部を丸める）。
+    /// タイムスタンプと時間間隔の加減算、時間間隔の定数倍も扱う。
+    /// 日付と整数の加減算は日数として扱い、日付に時間間隔や時刻を加えるとタイムスタンプになる。
+    /// バイト列同士の連結はバイト列になる。
+    fn apply(&self, left: &Value, right: &Value) -> Result<Value, ExpressionError> {
+        if *left == Value::Null || *right == Value::Null {
+            return Ok(Value::Null);
+        }
+
+        let out_of_range = || ExpressionError::InvalidOperation(
+            format!("Result of {} {} {} is out of range", left, self, right));
+        let division_by_zero = || ExpressionError::InvalidOperation("Division by zero".to_string());
+
+        match (self, left, right) {
+            (BinaryOperator::Concat, Value::Bytes(a), Value::Bytes(b)) => Ok(Value::Bytes([a.as_ref(), b.as_ref()].concat().into())),
+            (BinaryOperator::Concat, l, r) => Ok(Value::Text(format!("{}{}", l, r))),
+            (op, Value::Integer(a), Value::Integer(b)) => {
+                let result = match op {
+                    BinaryOperator::Plus => a.checked_add(*b),
+                    BinaryOperator::Minus => a.checked_sub(*b),
+                    BinaryOperator::Multiply => a.checked_mul(*b),
+                    BinaryOperator::Divide | BinaryOperator::Modulo if *b == 0 => return Err(division_by_zero()),
+                    BinaryOperator::Divide => a.checked_div(*b),
+                    _ => a.checked_rem(*b),
+                };
+                result.map(Value::Integer).ok_or_else(out_of_range)
+            },
+            (op, Value::Integer(_) | Value::Decimal(_), Value::Integer(_) | Value::Decimal(_)) => {
+                let (a, b) = (left.to_decimal().ok_or_else(out_of_range)?, right.to_decimal().ok_or_else(out_of_range)?);
+                let result = match op {
+                    BinaryOperator::Plus => a.checked_add(b),
+                    BinaryOperator::Minus => a.checked_sub(b),
+                    BinaryOperator::Multiply => a.checked_mul(b),
+                    BinaryOperator::Divide | BinaryOperator::Modulo if b.is_zero() => return Err(division_by_zero()),
+                    BinaryOperator::Divide => {
+                        let scale = a.scale().max(b.scale()).max(DataType::DECIMAL_DIVISION_SCALE as u32);
+                        a.checked_div(b).map(|d| d.round_dp(scale))
+                    },
+                    _ => a.checked_rem(b),
+                };
+                result.map(Value::Decimal).ok_or_else(out_of_range)
+            },
+            (op, Value::Integer(_) | Value::Float(_) | Value::Decimal(_), Value::Integer(_) | Value::Float(_) | Value::Decimal(_)) => {
+                let (a, b) = (as_f64(left), as_f64(right));
+                Ok(Value::Float(match op {
+                    BinaryOperator::Plus => a + b,
+                    BinaryOperator::Minus => a - b,
+                    BinaryOperator::Multiply => a * b,
+                    BinaryOperator::Divide | BinaryOperator::Modulo if b == 0.0 => return Err(division_by_zero()),
+                    BinaryOperator::Divide => a / b,
+                    _ => a % b,
+                }))
+            },
+            (BinaryOperator::Plus, Value::Timestamp(t), Value::Interval(i)) |
+            (BinaryOperator::Plus, Value::Interval(i), Value::Timestamp(t)) => {
+                i.add_to(*t).map(Value::Timestamp).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Plus, Value::TimestampTz(t), Value::Interval(i)) |
+            (BinaryOperator::Plus, Value::Interval(i), Value::TimestampTz(t)) => {
+                i.add_to(t.naive_utc()).map(|dt| Value::TimestampTz(dt.and_utc())).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Plus, Value::Date(d), Value::Integer(n)) |
+            (BinaryOperator::Plus, Value::Integer(n), Value::Date(d)) => {
+                add_days(*d, *n).map(Value::Date).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::Date(d), Value::Integer(n)) => {
+                n.checked_neg().and_then(|n| add_days(*d, n)).map(Value::Date).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::Date(a), Value::Date(b)) => Ok(Value::Integer((*a - *b).num_days())),
+            (BinaryOperator::Plus, Value::Date(d), Value::Interval(i)) |
+            (BinaryOperator::Plus, Value::Interval(i), Value::Date(d)) => {
+                i.add_to(d.and_time(NaiveTime::MIN)).map(Value::Timestamp).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::Date(d), Value::Interval(i)) => {
+                i.checked_neg()
+                    .and_then(|i| i.add_to(d.and_time(NaiveTime::MIN)))
+                    .map(Value::Timestamp)
+                    .ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Plus, Value::Date(d), Value::Time(t)) |
+            (BinaryOperator::Plus, Value::Time(t), Value::Date(d)) => Ok(Value::Timestamp(d.and_time(*t))),
+            (BinaryOperator::Plus, Value::Time(t), Value::Interval(i)) |
+            (BinaryOperator::Plus, Value::Interval(i), Value::Time(t)) => Ok(Value::Time(i.add_to_time(*t))),
+            (BinaryOperator::Minus, Value::Time(t), Value::Interval(i)) => {
+                i.checked_neg().map(|i| Value::Time(i.add_to_time(*t))).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::Time(a), Value::Time(b)) => {
+                let (a, b) = (Interval::since_midnight(*a), Interval::since_midnight(*b));
+                Ok(Value::Interval(Interval::new(0, 0, a.micros - b.micros)))
+            },
+            (BinaryOperator::Minus, Value::Timestamp(t), Value::Interval(i)) => {
+                i.checked_neg().and_then(|i| i.add_to(*t)).map(Value::Timestamp).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::TimestampTz(t), Value::Interval(i)) => {
+                i.checked_neg()
+                    .and_then(|i| i.add_to(t.naive_utc()))
+                    .map(|dt| Value::TimestampTz(dt.and_utc()))
+                    .ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::Timestamp(a), Value::Timestamp(b)) => {
+                Interval::between(*b, *a).map(Value::Interval).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::TimestampTz(a), Value::TimestampTz(b)) => {
+                Interval::between(b.naive_utc(), a.naive_utc()).map(Value::Interval).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Plus, Value::Interval(a), Value::Interval(b)) => {
+                a.checked_add(b).map(Value::Interval).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Minus, Value::Interval(a), Value::Interval(b)) => {
+                b.checked_neg().and_then(|b| a.checked_add(&b)).map(Value::Interval).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Multiply, Value::Interval(i), n @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_))) |
+            (BinaryOperator::Multiply, n @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_)), Value::Interval(i)) => {
+                i.checked_mul(as_f64(n)).map(Value::Interval).ok_or_else(out_of_range)
+            },
+            (BinaryOperator::Divide, Value::Interval(i), n @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_))) => {
+                if as_f64(n) == 0.0 {
+                    return Err(division_by_zero());
+                }
+                i.checked_mul(1.0 / as_f64(n)).map(Value::Interval).ok_or_else(out_of_range)
+            },
+            _ => Err(self.type_mismatch(&left.data_type(), &right.data_type())),
+        }
+    }
+
+    /// 算術演算の結果の型を求める
+    fn result_type(&self, left: DataType, right: DataType) -> Result<DataType, ExpressionError> {
+        use DataType::{Date, Decimal, Float, Integer, Null, Time, Timestamp, TimestampTz};
+        let scale = |t: &DataType| match t {
+            Decimal(_, scale) => *scale,
+            _ => 0,
+        };
+        match (self, &left, &right) {
+            (BinaryOperator::Concat, DataType::Blob, DataType::Blob) => Ok(DataType::Blob),
+            (BinaryOperator::Concat, ..) => Ok(DataType::Text),
+            (_, Null, t) | (_, t, Null) => Ok(t.clone()),
+            (_, Integer, Integer) => Ok(Integer),
+            (op, Integer | Decimal(..), Integer | Decimal(..)) => {
+                let (l, r) = (scale(&left), scale(&right));
+                Ok(Decimal(DataType::MAX_DECIMAL_PRECISION, match op {
+                    BinaryOperator::Multiply => (l + r).min(DataType::MAX_DECIMAL_PRECISION),
+                    BinaryOperator::Divide => l.max(r).max(DataType::DECIMAL_DIVISION_SCALE),
+                    _ => l.max(r),
+                }))
+            },
+            (_, Integer | Float | Decimal(..), Integer | Float | Decimal(..)) => Ok(Float),
+            (BinaryOperator::Plus, Timestamp, DataType::Interval) |
+            (BinaryOperator::Plus, DataType::Interval, Timestamp) |
+            (BinaryOperator::Minus, Timestamp, DataType::Interval) |
+            (BinaryOperator::Plus, Date, DataType::Interval | Time) |
+            (BinaryOperator::Plus, DataType::Interval | Time, Date) |
+            (BinaryOperator::Minus, Date, DataType::Interval) => Ok(Timestamp),
+            (BinaryOperator::Plus, TimestampTz, DataType::Interval) |
+            (BinaryOperator::Plus, DataType::Interval, TimestampTz) |
+            (BinaryOperator::Minus, TimestampTz, DataType::Interval) => Ok(TimestampTz),
+            (BinaryOperator::Plus, Date, Integer) |
+            (BinaryOperator::Plus, Integer, Date) |
+            (BinaryOperator::Minus, Date, Integer) => Ok(Date),
+            (BinaryOperator::Minus, Date, Date) => Ok(Integer),
+            (BinaryOperator::Plus, Time, DataType::Interval) |
+            (BinaryOperator::Plus, DataType::Interval, Time) |
+            (BinaryOperator::Minus, Time, DataType::Interval) => Ok(Time),
+            (BinaryOperator::Minus, Timestamp, Timestamp) |
+            (BinaryOperator::Minus, TimestampTz, TimestampTz) |
+            (BinaryOperator::Minus, Time, Time) |
+            (BinaryOperator::Plus | BinaryOperator::Minus, DataType::Interval, DataType::Interval) |
+            (BinaryOperator::Multiply | BinaryOperator::Divide, DataType::Interval, Integer | Float | Decimal(..)) |
+            (BinaryOperator::Multiply, Integer | Float | Decimal(..), DataType::Interval) => Ok(DataType::Interval),
+            _ => Err(self.type_mismatch(&left, &right)),
+        }
+    }
+
+    /// JSONの演算を行う（どちらかがNULLならNULL）
+    ///
+    /// -> と ->> は文字列でオブジェクトのキーを、整数で配列の位置を指定する（見つからなければNULL）。
+    /// #> と #>> のパスは '{a,0,b}' の形式で指定する。@> と <@ は文字列をJSONとして解析して比較する。
+    fn apply_json(&self, left: &Value, right: &Value) -> Result<Value, ExpressionError> {
+        if *left == Value::Null || *right == Value::Null {
+            return Ok(Value::Null);
+        }
+
+        let mismatch = || self.type_mismatch(&left.data_type(), &right.data_type());
+        let found = match (self, left, right) {
+            (BinaryOperator::JsonContains | BinaryOperator::JsonContainedBy, ..) => {
+                let (left, right) = (as_json(left)?, as_json(right)?);
+                return Ok(Value::Boolean(match self {
+                    BinaryOperator::JsonContains => json_contains(&left, &right),
+                    _ => json_contains(&right, &left),
+                }));
+            },
+            (BinaryOperator::JsonGet | BinaryOperator::JsonGetText, Value::Json(json), Value::Text(key)) => {
+                json.as_object().and_then(|object| object.get(key))
+            },
+            (BinaryOperator::JsonGet | BinaryOperator::JsonGetText, Value::Json(json), Value::Integer(index)) => {
+                json.as_array().and_then(|_| json_get(json, &index.to_string()))
+            },
+            (BinaryOperator::JsonPath | BinaryOperator::JsonPathText, Value::Json(json), Value::Text(path)) => {
+                let path = parse_json_path(path).ok_or_else(|| ExpressionError::InvalidOperation(
+                    format!("Invalid JSON path: {}", path)))?;
+                json_path(json, &path)
+            },
+            _ => return Err(mismatch()),
+        };
+
+        Ok(match (self, found) {
+            (_, None) => Value::Null,
+            (BinaryOperator::JsonGet | BinaryOperator::JsonPath, Some(json)) => Value::Json(json.clone()),
+            // 文字列は引用符を外し、JSONの null はNULLにする
+            (_, Some(serde_json::Value::String(s))) => Value::Text(s.clone()),
+            (_, Some(serde_json::Value::Null)) => Value::Null,
+            (_, Some(json)) => Value::Text(json.to_string()),
+        })
+    }
+
+    /// JSONの演算の結果の型を求める
+    fn json_result_type(&self, left: DataType, right: DataType) -> Result<DataType, ExpressionError> {
+        use DataType::{Integer, Json, Null, Text};
+        match (self, &left, &right) {
+            (BinaryOperator::JsonContains | BinaryOperator::JsonContainedBy, Json | Text | Null, Json | Text | Null) => {
+                Ok(DataType::Boolean)
+            },
+            (BinaryOperator::JsonGet, Json | Null, Text | Integer | Null) |
+            (BinaryOperator::JsonPath, Json | Null, Text | Null) => Ok(Json),
+            (BinaryOperator::JsonGetText, Json | Null, Text | Integer | Null) |
+            (BinaryOperator::JsonPathText, Json | Null, Text | Null) => Ok(Text),
+            _ => Err(self.type_mismatch(&left, &right)),
+        }
+    }
+
+    fn type_mismatch(&self, left: &DataType, right: &DataType) -> ExpressionError {
+        ExpressionError::InvalidOperation(format!("Operator {} cannot be applied to {} and {}", self, left, right))
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryOperator::Eq => write!(f, "="),
+            BinaryOperator::NotEq => write!(f, "<>"),
+            BinaryOperator::Lt => write!(f, "<"),
+            BinaryOperator::LtEq => write!(f, "<="),
+            BinaryOperator::Gt => write!(f, ">"),
+            BinaryOperator::GtEq => write!(f, ">="),
+            BinaryOperator::And => write!(f, "AND"),
+            BinaryOperator::Or => write!(f, "OR"),
+            BinaryOperator::Plus => write!(f, "+"),
+            BinaryOperator::Minus => write!(f, "-"),
+            BinaryOperator::Multiply => write!(f, "*"),
+            BinaryOperator::Divide => write!(f, "/"),
+            BinaryOperator::Modulo => write!(f, "%"),
+            BinaryOperator::Concat => write!(f, "||"),
+            BinaryOperator::JsonGet => write!(f, "->"),
+            BinaryOperator::JsonGetText => write!(f, "->>"),
+            BinaryOperator::JsonPath => write!(f, "#>"),
+            BinaryOperator::JsonPathText => write!(f, "#>>"),
+            BinaryOperator::JsonContains => write!(f, "@>"),
+            BinaryOperator::JsonContainedBy => write!(f, "<@"),
+        }
+    }
+}
+
+/// ウィンドウ関数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFunction {
+    RowNumber,
+    Rank,
+    DenseRank,
+    Lag,
+    Lead,
+    FirstValue,
+    LastValue,
+    /// OVER句を伴う集約関数（SUM(x) OVER (...) など）
+    Aggregate(AggregateFunction),
+}
+
+impl WindowFunction {
+    /// 関数名からウィンドウ関数を取得する（大文字小文字は区別しない）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "ROW_NUMBER" => Some(WindowFunction::RowNumber),
+            "RANK" => Some(WindowFunction::Rank),
+            "DENSE_RANK" => Some(WindowFunction::DenseRank),
+            "LAG" => Some(WindowFunction::Lag),
+            "LEAD" => Some(WindowFunction::Lead),
+            "FIRST_VALUE" => Some(WindowFunction::FirstValue),
+            "LAST_VALUE" => Some(WindowFunction::LastValue),
+            _ => AggregateFunction::from_name(name).map(WindowFunction::Aggregate),
+        }
+    }
+}
+
+impl fmt::Display for WindowFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowFunction::RowNumber => write!(f, "ROW_NUMBER"),
+            WindowFunction::Rank => write!(f, "RANK"),
+            WindowFunction::DenseRank => write!(f, "DENSE_RANK"),
+            WindowFunction::Lag => write!(f, "LAG"),
+            WindowFunction::Lead => write!(f, "LEAD"),
+            WindowFunction::FirstValue => write!(f, "FIRST_VALUE"),
+            WindowFunction::LastValue => write!(f, "LAST_VALUE"),
+            WindowFunction::Aggregate(function) => write!(f, "{}", function),
+        }
+    }
+}
+
+/// ORDER BY の項目
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderByExpr {
+    pub expr: Expression,
+    pub descending: bool,
+    /// NULLS FIRST / NULLS LAST（指定がなければNULLを最大の値として扱う）
+    pub nulls_first: Option<bool>,
+}
+
+impl OrderByExpr {
+    /// この項目の並び順で2つの値を比較する（比較できない型の組み合わせは等しいとみなす）
+    ///
+    /// 式に照合順序が指定されていれば、文字列はその照合順序で比較する。
+    pub fn compare(&self, a: &Value, b: &Value) -> Ordering {
+        let nulls_first = self.nulls_first.unwrap_or(self.descending);
+        match (a, b) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Null, _) if nulls_first => Ordering::Less,
+            (Value::Null, _) => Ordering::Greater,
+            (_, Value::Null) if nulls_first => Ordering::Greater,
+            (_, Value::Null) => Ordering::Less,
+            _ => {
+                let ordering = match self.expr.collation() {
+                    Some(collation) => collation.compare_values(a, b),
+                    None => a.compare(b),
+                }.unwrap_or(Ordering::Equal);
+                if self.descending { ordering.reverse() } else { ordering }
+            },
+        }
+    }
+}
+
+impl fmt::Display for OrderByExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)?;
+        if self.descending {
+            write!(f, " DESC")?;
+        }
+        match self.nulls_first {
+            Some(true) => write!(f, " NULLS FIRST"),
+            Some(false) => write!(f, " NULLS LAST"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// ORDER BY の各項目について求めた値の組を比較する
+pub fn compare_sort_keys(order_by: &[OrderByExpr], a: &[Value], b: &[Value]) -> Ordering {
+    order_by.iter()
+        .zip(a.iter().zip(b))
+        .map(|(item, (x, y))| item.compare(x, y))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// ウィンドウフレームの単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameUnits {
+    Rows,
+    Range,
+}
+
+/// ウィンドウフレームの境界
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+impl fmt::Display for FrameBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameBound::UnboundedPreceding => write!(f, "UNBOUNDED PRECEDING"),
+            FrameBound::Preceding(n) => write!(f, "{} PRECEDING", n),
+            FrameBound::CurrentRow => write!(f, "CURRENT ROW"),
+            FrameBound::Following(n) => write!(f, "{} FOLLOWING", n),
+            FrameBound::UnboundedFollowing => write!(f, "UNBOUNDED FOLLOWING"),
+        }
+    }
+}
+
+/// ROWS / RANGE BETWEEN ... AND ...
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowFrame {
+    pub units: FrameUnits,
+    pub start: FrameBound,
+    pub end: FrameBound,
+}
+
+impl fmt::Display for WindowFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = match self.units {
+            FrameUnits::Rows => "ROWS",
+            FrameUnits::Range => "RANGE",
+        };
+        write!(f, "{} BETWEEN {} AND {}", units, self.start, self.end)
+    }
+}
+
+/// OVER句の内容
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSpec {
+    pub partition_by: Vec<Expression>,
+    pub order_by: Vec<OrderByExpr>,
+    /// 指定がなければ、ORDER BY がある場合は先頭から現在の行（と同順位の行）まで、
+    /// ない場合はパーティション全体
+    pub frame: Option<WindowFrame>,
+}
+
+impl fmt::Display for WindowSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut clauses = Vec::new();
+        if !self.partition_by.is_empty() {
+            let items: Vec<String> = self.partition_by.iter().map(|e| e.to_string()).collect();
+            clauses.push(format!("PARTITION BY {}", items.join(", ")));
+        }
+        if !self.order_by.is_empty() {
+            let items: Vec<String> = self.order_by.iter().map(|o| o.to_string()).collect();
+            clauses.push(format!("ORDER BY {}", items.join(", ")));
+        }
+        if let Some(frame) = &self.frame {
+            clauses.push(frame.to_string());
+        }
+        write!(f, "{}", clauses.join(" "))
+    }
+}
+
+/// 文字列の定数を比較するカラムの型の値に変換する関数（Expression::coerce_literals で使う）
+pub type LiteralConverter<'a> = dyn FnMut(&str, &Column) -> Result<Option<Value>, ExpressionError> + 'a;
+
+/// IN (SELECT ...) やセミ結合の結果をハッシュ化した値（の組）の集合
+///
+/// 照合順序を指定して調べる場合は、最初に調べるときに集合の値をその照合順序で正規化したキーを作って使う。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ValueSet {
+    values: HashSet<ValueKey>,
+    has_null: bool,
+    /// 照合順序とその照合順序で正規化したキーの集合
+    collated: OnceLock<(Vec<Option<Collation>>, HashSet<ValueKey>)>,
+}
+
+impl ValueSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, value: Value) {
+        if value == Value::Null {
+            self.has_null = true;
+        } else {
+            self.insert_key(vec![value]);
+        }
+    }
+
+    /// 値の組を加える
+    pub fn insert_key(&mut self, key: Vec<Value>) {
+        self.values.insert(ValueKey(key));
+        self.collated = OnceLock::new();
+    }
+
+    /// 値が集合に含まれるかどうか（SQLの規則に従い、判定できない場合はNULL）
+    pub fn contains(&self, value: &Value, collation: Option<Collation>) -> Value {
+        if *value == Value::Null {
+            return Value::Null;
+        }
+        if self.contains_key(std::slice::from_ref(value), &[collation]) {
+            Value::Boolean(true)
+        } else if self.has_null {
+            Value::Null
+        } else {
+            Value::Boolean(false)
+        }
+    }
+
+    /// 値の組が集合に含まれるかどうか（collations は値ごとの照合順序）
+    pub fn contains_key(&self, key: &[Value], collations: &[Option<Collation>]) -> bool {
+        if collations.iter().all(|c| matches!(c, None | Some(Collation::Binary))) {
+            return self.values.contains(&ValueKey(key.to_vec()));
+        }
+        let normalized = ValueKey::collated(key, collations);
+        let collate = |values: &HashSet<ValueKey>| values.iter()
+            .map(|ValueKey(values)| ValueKey::collated(values, collations))
+            .collect::<HashSet<_>>();
+        let (cached, keys) = self.collated.get_or_init(|| (collations.to_vec(), collate(&self.values)));
+        if cached == collations {
+            keys.contains(&normalized)
+        } else {
+            collate(&self.values).contains(&normalized)
+        }
+    }
+}
+
+impl FromIterator<Value> for ValueSet {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        let mut set = ValueSet::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// ON CONFLICT DO UPDATE で挿入しようとした行を指す修飾子
+pub const EXCLUDED: &str = "excluded";
+
+/// カラム名を解決する（"テーブル名.カラム名" の形式で見つからなければ修飾子を外して探す）
+pub fn resolve_column<'a>(columns: &'a [Column], name: &str) -> Option<&'a Column> {
+    columns.iter().find(|c| c.name == name).or_else(|| {
+        let (_, bare) = name.split_once('.')?;
+        columns.iter().find(|c| c.name == bare)
+    })
+}
+
+/// SELECT句などに現れる式
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    /// カラム参照（修飾されている場合は "テーブル名.カラム名"）
+    Column(String),
+
+    /// 位置に解決済みのカラム参照（ストレージ内の行を評価するために bind で Column から置き換える）
+    ColumnAt {
+        index: usize,
+        name: String,
+    },
+
+    /// リテラル値
+    Literal(Value),
+
+    /// 集約関数の呼び出し（引数がNoneの場合は COUNT(*)）
+    Aggregate {
+        function: AggregateFunction,
+        argument: Option<Box<Expression>>,
+    },
+
+    /// 利用者定義の集約関数の呼び出し（関数レジストリでの解決時に Function から置き換える）
+    UserAggregate {
+        function: UserAggregateRef,
+        args: Vec<Expression>,
+    },
+
+    /// 比較演算・論理演算・算術演算
+    BinaryOp {
+        left: Box<Expression>,
+        op: BinaryOperator,
+        right: Box<Expression>,
+    },
+
+    /// 論理否定
+    Not(Box<Expression>),
+
+    /// 符号の反転
+    Negate(Box<Expression>),
+
+    /// CAST(expr AS type) または expr::type
+    Cast {
+        expr: Box<Expression>,
+        data_type: DataType,
+        /// タイムゾーン付きの日時と他の日時を対応させるタイムゾーン（実行時にセッションのタイムゾーンにする）
+        time_zone: FixedOffset,
+    },
+
+    /// CASE [operand] WHEN ... THEN ... [ELSE ...] END
+    /// （operand がある場合は各 WHEN の値と等しいかどうか、ない場合は各 WHEN の条件で分岐する）
+    Case {
+        operand: Option<Box<Expression>>,
+        branches: Vec<(Expression, Expression)>,
+        else_result: Option<Box<Expression>>,
+    },
+
+    /// スカラー関数の呼び出し（function は実行前に関数レジストリから名前で解決する）
+    Function {
+        name: String,
+        args: Vec<Expression>,
+        function: Option<ScalarFunctionRef>,
+    },
+
+    /// IS [NOT] NULL
+    IsNull {
+        expr: Box<Expression>,
+        negated: bool,
+    },
+
+    /// [NOT] IN (値のリスト)
+    InList {
+        expr: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+
+    /// スカラーサブクエリ（SQLテキスト。評価前に実行して値に置き換える）
+    ScalarSubquery(String),
+
+    /// [NOT] IN (SELECT ...)（評価前に実行して InSet に置き換える）
+    InSubquery {
+        expr: Box<Expression>,
+        query: String,
+        negated: bool,
+    },
+
+    /// [NOT] EXISTS (SELECT ...)（評価前に実行して値に置き換える）
+    Exists {
+        query: String,
+        negated: bool,
+    },
+
+    /// 実行済みのサブクエリの結果に含まれるかどうか
+    InSet {
+        expr: Box<Expression>,
+        set: Arc<ValueSet>,
+        negated: bool,
+    },
+
+    /// 相関サブクエリを変換したセミ結合（negated の場合はアンチ結合）
+    /// キーの値の組がサブクエリ側の集合に含まれるかどうかを判定する
+    SemiJoin {
+        keys: Vec<Expression>,
+        set: Arc<ValueSet>,
+        negated: bool,
+    },
+
+    /// ウィンドウ関数の呼び出し（評価前に行ごとの値を計算して置き換える）
+    Window {
+        function: WindowFunction,
+        args: Vec<Expression>,
+        spec: Box<WindowSpec>,
+    },
+
+    /// ARRAY[...]（要素は共通の型に揃える）
+    Array(Vec<Expression>),
+
+    /// 配列の要素の取り出し（expr[index]。位置は1から数え、範囲外ならNULL）
+    Subscript {
+        expr: Box<Expression>,
+        index: Box<Expression>,
+    },
+
+    /// expr op ANY (配列) / expr op ALL (配列)
+    ArrayComparison {
+        left: Box<Expression>,
+        op: BinaryOperator,
+        right: Box<Expression>,
+        all: bool,
+    },
+
+    /// expr COLLATE "名前"
+    /// （implicit はカラム定義の照合順序を実行時に付けたもので、明示的な指定より優先度が低い）
+    Collate {
+        expr: Box<Expression>,
+        collation: Collation,
+        implicit: bool,
+    },
+
+    /// expr [NOT] LIKE pattern [ESCAPE 'c'] / expr [NOT] ILIKE pattern
+    /// （% は任意の文字列、_ は任意の1文字に一致する。ESCAPE の指定がなければ \ でエスケープする。
+    /// ILIKE は大文字小文字を区別しない）
+    Like {
+        expr: Box<Expression>,
+        pattern: Box<Expression>,
+        negated: bool,
+        case_insensitive: bool,
+        escape: Option<char>,
+    },
+}
+
+impl Expression {
+    /// 行に対して式を評価する
+    pub fn evaluate<R: RowValues + ?Sized>(&self, row: &R) -> Result<Value, ExpressionError> {
+        match self {
+            Expression::Column(name) => {
+                let value = row.value(name).or_else(|| {
+                    let (_, bare) = name.split_once('.')?;
+                    row.value(bare)
+                });
+                Ok(value.cloned().unwrap_or(Value::Null))
+            },
+            Expression::ColumnAt { index, name } => {
+                Ok(row.value_at(*index).or_else(|| row.value(name)).cloned().unwrap_or(Value::Null))
+            },
+            Expression::Literal(value) => Ok(value.clone()),
+            Expression::Aggregate { function, .. } => {
+                Err(ExpressionError::MisplacedAggregate(function.to_string()))
+            },
+            Expression::UserAggregate { function, .. } => {
+                Err(ExpressionError::MisplacedAggregate(function.name().to_string()))
+            },
+            Expression::BinaryOp { left, op: BinaryOperator::And, right } => {
+                // 一方が偽なら偽、そうでなく一方がNULLならNULL
+                match (as_bool(left.evaluate(row)?)?, as_bool(right.evaluate(row)?)?) {
+                    (Some(false), _) | (_, Some(false)) => Ok(Value::Boolean(false)),
+                    (Some(true), Some(true)) => Ok(Value::Boolean(true)),
+                    _ => Ok(Value::Null),
+                }
+            },
+            Expression::BinaryOp { left, op: BinaryOperator::Or, right } => {
+                // 一方が真なら真、そうでなく一方がNULLならNULL
+                match (as_bool(left.evaluate(row)?)?, as_bool(right.evaluate(row)?)?) {
+                    (Some(true), _) | (_, Some(true)) => Ok(Value::Boolean(true)),
+                    (Some(false), Some(false)) => Ok(Value::Boolean(false)),
+                    _ => Ok(Value::Null),
+                }
+            },
+            Expression::BinaryOp { left, op, right } if op.is_arithmetic() => {
+                op.apply(&left.evaluate(row)?, &right.evaluate(row)?)
+            },
+            Expression::BinaryOp { left, op, right } if op.is_json() => {
+                op.apply_json(&left.evaluate(row)?, &right.evaluate(row)?)
+            },
+            Expression::BinaryOp { left: left_expr, op, right: right_expr } => {
+                let left = left_expr.evaluate(row)?;
+                let right = right_expr.evaluate(row)?;
+                if left == Value::Null || right == Value::Null {
+                    return Ok(Value::Null);
+                }
+                let ordering = compare_collated(derived_collation(left_expr, right_expr), &left, &right)?;
+                Ok(Value::Boolean(op.accepts(ordering)))
+            },
+            Expression::Not(expr) => {
+                Ok(as_bool(expr.evaluate(row)?)?.map_or(Value::Null, |b| Value::Boolean(!b)))
+            },
+            Expression::Cast { expr, data_type, time_zone } => expr.evaluate(row)?
+                .cast_in_time_zone(data_type, *time_zone)
+                .map_err(|e| ExpressionError::InvalidOperation(e.to_string())),
+            Expression::Negate(expr) => match expr.evaluate(row)? {
+                Value::Integer(i) => i.checked_neg().map(Value::Integer).ok_or_else(|| {
+                    ExpressionError::InvalidOperation(format!("Result of -{} is out of range", i))
+                }),
+                Value::Float(f) => Ok(Value::Float(-f)),
+                Value::Decimal(d) => Ok(Value::Decimal(-d)),
+                Value::Interval(i) => i.checked_neg().map(Value::Interval).ok_or_else(|| {
+                    ExpressionError::InvalidOperation(format!("Result of -{} is out of range", i))
+                }),
+                Value::Null => Ok(Value::Null),
+                other => Err(ExpressionError::InvalidOperation(
+                    format!("Cannot negate {}", other.data_type()))),
+            },
+            Expression::Case { operand, branches, else_result } => {
+                let operand = operand.as_ref().map(|operand| operand.evaluate(row)).transpose()?;
+                for (condition, result) in branches {
+                    let value = condition.evaluate(row)?;
+                    let matched = match &operand {
+                        // NULLはどの値とも等しくない
+                        Some(Value::Null) => false,
+                        Some(_) if value == Value::Null => false,
+                        Some(operand) => operand.compare(&value).ok_or_else(|| ExpressionError::InvalidOperation(
+                            format!("Cannot compare {} with {}", operand.data_type(), value.data_type())))?.is_eq(),
+                        None => as_bool(value)? == Some(true),
+                    };
+                    if matched {
+                        return result.evaluate(row);
+                    }
+                }
+                match else_result {
+                    Some(else_result) => else_result.evaluate(row),
+                    None => Ok(Value::Null),
+                }
+            },
+            Expression::Function { name, args, function } => {
+                let function = function.as_ref().ok_or_else(|| ExpressionError::InvalidOperation(
+                    format!("Function {} has not been resolved", name)))?;
+                let args = args.iter()
+                    .map(|arg| arg.evaluate(row))
+                    .collect::<Result<Vec<_>, _>>()?;
+                function.invoke(&args)
+            },
+            Expression::IsNull { expr, negated } => {
+                Ok(Value::Boolean((expr.evaluate(row)? == Value::Null) != *negated))
+            },
+            Expression::InList { expr, list, negated } => {
+                let value = expr.evaluate(row)?;
+                let set = list.iter()
+                    .map(|item| item.evaluate(row))
+                    .collect::<Result<ValueSet, _>>()?;
+                Ok(negate_if(set.contains(&value, list_collation(expr, list)), *negated))
+            },
+            Expression::InSet { expr, set, negated } => {
+                Ok(negate_if(set.contains(&expr.evaluate(row)?, expr.collation()), *negated))
+            },
+            Expression::SemiJoin { keys, set, negated } => {
+                let key = keys.iter()
+                    .map(|key| key.evaluate(row))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let collations: Vec<_> = keys.iter().map(Expression::collation).collect();
+                // NULLを含むキーはどの行とも一致しない
+                let found = !key.contains(&Value::Null) && set.contains_key(&key, &collations);
+                Ok(Value::Boolean(found != *negated))
+            },
+            Expression::ScalarSubquery(_) | Expression::InSubquery { .. } | Expression::Exists { .. } => {
+                Err(ExpressionError::InvalidOperation("Subquery has not been executed".to_string()))
+            },
+            Expression::Window { function, .. } => Err(ExpressionError::InvalidOperation(
+                format!("Window function {} is not allowed here", function))),
+            Expression::Array(items) => {
+                let values = items.iter()
+                    .map(|item| item.evaluate(row))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let element_type = common_element_type(values.iter().map(Value::data_type))?;
+                values.iter()
+                    .map(|value| value.cast_to(&element_type))
+                    .collect::<Result<_, _>>()
+                    .map(Value::Array)
+                    .map_err(|e| ExpressionError::InvalidOperation(e.to_string()))
+            },
+            Expression::Subscript { expr, index } => match (expr.evaluate(row)?, index.evaluate(row)?) {
+                (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+                (Value::Array(items), Value::Integer(i)) => Ok(usize::try_from(i - 1).ok()
+                    .and_then(|i| items.get(i).cloned())
+                    .unwrap_or(Value::Null)),
+                (Value::Array(_), index) => Err(ExpressionError::InvalidOperation(
+                    format!("Array index must be INTEGER, got {}", index.data_type()))),
+                (other, _) => Err(ExpressionError::InvalidOperation(
+                    format!("Cannot subscript {}", other.data_type()))),
+            },
+            Expression::ArrayComparison { left: left_expr, op, right, all } => {
+                let collation = left_expr.collation();
+                let left = left_expr.evaluate(row)?;
+                let items = match right.evaluate(row)? {
+                    Value::Array(items) => items,
+                    Value::Null => return Ok(Value::Null),
+                    other => return Err(ExpressionError::InvalidOperation(
+                        format!("{} requires an array, got {}", if *all { "ALL" } else { "ANY" }, other.data_type()))),
+                };
+
+                // ANY は1つでも真なら真、ALL は1つでも偽なら偽。決まらずにNULLとの比較があればNULL
+                let mut unknown = false;
+                for item in &items {
+                    if left == Value::Null || *item == Value::Null {
+                        unknown = true;
+                        continue;
+                    }
+                    let ordering = compare_collated(collation, &left, item)?;
+                    if op.accepts(ordering) != *all {
+                        return Ok(Value::Boolean(!*all));
+                    }
+                }
+                Ok(if unknown { Value::Null } else { Value::Boolean(*all) })
+            },
+            Expression::Collate { expr, .. } => expr.evaluate(row),
+            Expression::Like { expr, pattern, negated, case_insensitive, escape } => {
+                let collation = if *case_insensitive {
+                    Some(Collation::CaseInsensitive)
+                } else {
+                    derived_collation(expr, pattern)
+                };
+                match (expr.evaluate(row)?, pattern.evaluate(row)?) {
+                    (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+                    (Value::Text(text), Value::Text(pattern)) => {
+                        let matched = like_match(&text, &pattern, *escape, collation.unwrap_or(Collation::Binary));
+                        Ok(Value::Boolean(matched != *negated))
+                    },
+                    (text, pattern) => Err(ExpressionError::InvalidOperation(format!(
+                        "{} cannot be applied to {} and {}",
+                        if *case_insensitive { "ILIKE" } else { "LIKE" }, text.data_type(), pattern.data_type()))),
+                }
+            },
+        }
+    }
+
+    /// カラム参照をテーブルのカラムの位置に解決する（テーブルにないカラムを参照していればエラー）
+    pub fn bind(&self, table: &Table) -> Result<Expression, ExpressionError> {
+        let index_of = |name: &str| table.get_column_index(name).or_else(|| {
+            let (_, bare) = name.split_once('.')?;
+            table.get_column_index(bare)
+        });
+        if let Some(missing) = self.referenced_columns().into_iter().find(|name| index_of(name).is_none()) {
+            return Err(ExpressionError::ColumnNotFound(missing.to_string()));
+        }
+
+        Ok(self.transform(&mut |expr| match expr {
+            Expression::Column(name) => Some(Expression::ColumnAt { index: index_of(name)?, name: name.clone() }),
+            _ => None,
+        }))
+    }
+
+    /// 式の最上位に指定された照合順序
+    pub fn collation(&self) -> Option<Collation> {
+        match self {
+            Expression::Collate { collation, .. } => Some(*collation),
+            _ => None,
+        }
+    }
+
+    /// 照合順序が指定されたカラムへの参照を、その照合順序を付けた式に置き換える
+    pub fn with_collations(&self, columns: &[Column]) -> Expression {
+        if columns.iter().all(|c| c.collation.is_none()) {
+            return self.clone();
+        }
+        self.transform(&mut |expr| match expr {
+            Expression::Collate { expr: inner, .. } if matches!(inner.as_ref(), Expression::Column(_)) => Some(expr.clone()),
+            Expression::Column(name) => {
+                let collation = resolve_column(columns, name)?.collation?;
+                Some(Expression::Collate { expr: Box::new(expr.clone()), collation, implicit: true })
+            },
+            _ => None,
+        })
+    }
+
+    /// カラムとの比較や IN のリストに現れる文字列の定数を、convert でカラムの型の値に置き換える
+    ///
+    /// convert は文字列のまま比較すればよい型では None を返す。
+    pub fn coerce_literals(
+        &self,
+        columns: &[Column],
+        convert: &mut LiteralConverter<'_>
+    ) -> Result<Expression, ExpressionError> {
+        let mut error = None;
+        let mut coerce = |literal: &Expression, column: &Column| match literal {
+            Expression::Literal(Value::Text(text)) => match convert(text, column) {
+                Ok(value) => value.map(Expression::Literal),
+                Err(e) => {
+                    error.get_or_insert(e);
+                    None
+                },
+            },
+            _ => None,
+        };
+
+        let coerced = self.transform(&mut |expr| match expr {
+            Expression::BinaryOp { left, op, right }
+                if matches!(op, BinaryOperator::Eq | BinaryOperator::NotEq | BinaryOperator::Lt |
+                    BinaryOperator::LtEq | BinaryOperator::Gt | BinaryOperator::GtEq) => {
+                let (left, right) = match (referenced_column(left, columns), referenced_column(right, columns)) {
+                    (Some(column), None) => (left.as_ref().clone(), coerce(right, column)?),
+                    (None, Some(column)) => (coerce(left, column)?, right.as_ref().clone()),
+                    _ => return None,
+                };
+                Some(Expression::BinaryOp { left: Box::new(left), op: *op, right: Box::new(right) })
+            },
+            Expression::InList { expr, list, negated } => {
+                let column = referenced_column(expr, columns)?;
+                let list = list.iter()
+                    .map(|item| coerce(item, column).unwrap_or_else(|| item.clone()))
+                    .collect();
+                Some(Expression::InList { expr: expr.clone(), list, negated: *negated })
+            },
+            _ => None,
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(coerced),
+        }
+    }
+
+    /// 式に集約関数が含まれるかどうか
+    pub fn contains_aggregate(&self) -> bool {
+        match self {
+            Expression::Aggregate { .. } | Expression::UserAggregate { .. } => true,
+            _ => self.children().iter().any(|child| child.contains_aggregate()),
+        }
+    }
+
+    /// 式にサブクエリが含まれるかどうか
+    pub fn contains_subquery(&self) -> bool {
+        match self {
+            Expression::ScalarSubquery(_) | Expression::InSubquery { .. } | Expression::Exists { .. } => true,
+            _ => self.children().iter().any(|child| child.contains_subquery()),
+        }
+    }
+
+    /// 式にウィンドウ関数が含まれるかどうか
+    pub fn contains_window(&self) -> bool {
+        match self {
+            Expression::Window { .. } => true,
+            _ => self.children().iter().any(|child| child.contains_window()),
+        }
+    }
+
+    /// 行や関数に依存せず、それ自体で値が決まる式かどうか
+    pub fn is_constant(&self) -> bool {
+        match self {
+            Expression::Literal(_) => true,
+            Expression::Column(_) | Expression::ColumnAt { .. } | Expression::Aggregate { .. } | Expression::UserAggregate { .. } |
+            Expression::Function { .. } | Expression::ScalarSubquery(_) | Expression::InSubquery { .. } |
+            Expression::Exists { .. } | Expression::InSet { .. } | Expression::SemiJoin { .. } |
+            Expression::Window { .. } => false,
+            _ => self.children().iter().all(|child| child.is_constant()),
+        }
+    }
+
+    /// 式が参照するカラム名を収集する（サブクエリの内部は含まない）
+    pub fn referenced_columns(&self) -> Vec<&str> {
+        match self {
+            Expression::Column(name) | Expression::ColumnAt { name, .. } => vec![name.as_str()],
+            _ => self.children().into_iter().flat_map(|child| child.referenced_columns()).collect(),
+        }
+    }
+
+    /// 直下の子となる式
+    fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Column(_) | Expression::ColumnAt { .. } | Expression::Literal(_) | Expression::ScalarSubquery(_) |
+            Expression::Exists { .. } => Vec::new(),
+            Expression::Aggregate { argument, .. } => argument.iter().map(|arg| arg.as_ref()).collect(),
+            Expression::BinaryOp { left, right, .. } => vec![left, right],
+            Expression::Not(expr) | Expression::Negate(expr) | Expression::Cast { expr, .. } | Expression::IsNull { expr, .. } |
+            Expression::InSubquery { expr, .. } | Expression::InSet { expr, .. } => vec![expr],
+            Expression::Case { operand, branches, else_result } => operand.iter().map(|o| o.as_ref())
+                .chain(branches.iter().flat_map(|(condition, result)| [condition, result]))
+                .chain(else_result.iter().map(|e| e.as_ref()))
+                .collect(),
+            Expression::Function { args, .. } | Expression::UserAggregate { args, .. } => args.iter().collect(),
+            Expression::InList { expr, list, .. } => std::iter::once(expr.as_ref()).chain(list).collect(),
+            Expression::SemiJoin { keys, .. } => keys.iter().collect(),
+            Expression::Window { args, spec, .. } => args.iter()
+                .chain(&spec.partition_by)
+                .chain(spec.order_by.iter().map(|item| &item.expr))
+                .collect(),
+            Expression::Array(items) => items.iter().collect(),
+            Expression::Subscript { expr, index } => vec![expr, index],
+            Expression::ArrayComparison { left, right, .. } => vec![left, right],
+            Expression::Collate { expr, .. } => vec![expr],
+            Expression::Like { expr, pattern, .. } => vec![expr, pattern],
+        }
+    }
+
+    /// 式を書き換える
+    ///
+    /// 各ノードに対して外側から順に `f` を適用し、`Some` が返された場合はそのノードを置き換える
+    /// （置き換えたノードの内部には適用しない）。
+    pub fn transform(&self, f: &mut dyn FnMut(&Expression) -> Option<Expression>) -> Expression {
+        if let Some(replaced) = f(self) {
+            return replaced;
+        }
+
+        let mut transform = |expr: &Expression| Box::new(expr.transform(f));
+        match self {
+            Expression::Column(_) | Expression::ColumnAt { .. } | Expression::Literal(_) | Expression::ScalarSubquery(_) |
+            Expression::Exists { .. } => self.clone(),
+            Expression::Aggregate { function, argument } => Expression::Aggregate {
+                function: *function,
+                argument: argument.as_ref().map(|arg| transform(arg)),
+            },
+            Expression::UserAggregate { function, args } => Expression::UserAggregate {
+                function: function.clone(),
+                args: args.iter().map(|arg| *transform(arg)).collect(),
+            },
+            Expression::BinaryOp { left, op, right } => Expression::BinaryOp {
+                left: transform(left),
+                op: *op,
+                right: transform(right),
+            },
+            Expression::Not(expr) => Expression::Not(transform(expr)),
+            Expression::Negate(expr) => Expression::Negate(transform(expr)),
+            Expression::Cast { expr, data_type, time_zone } => Expression::Cast {
+                expr: transform(expr),
+                data_type: data_type.clone(),
+                time_zone: *time_zone,
+            },
+            Expression::Case { operand, branches, else_result } => Expression::Case {
+                operand: operand.as_ref().map(|operand| transform(operand)),
+                branches: branches.iter()
+                    .map(|(condition, result)| (*transform(condition), *transform(result)))
+                    .collect(),
+                else_result: else_result.as_ref().map(|else_result| transform(else_result)),
+            },
+            Expression::Function { name, args, function } => Expression::Function {
+                name: name.clone(),
+                args: args.iter().map(|arg| *transform(arg)).collect(),
+                function: function.clone(),
+            },
+            Expression::IsNull { expr, negated } => Expression::IsNull {
+                expr: transform(expr),
+                negated: *negated,
+            },
+            Expression::InList { expr, list, negated } => Expression::InList {
+                expr: transform(expr),
+                list: list.iter().map(|item| *transform(item)).collect(),
+                negated: *negated,
+            },
+            Expression::InSubquery { expr, query, negated } => Expression::InSubquery {
+                expr: transform(expr),
+                query: query.clone(),
+                negated: *negated,
+            },
+            Expression::InSet { expr, set, negated } => Expression::InSet {
+                expr: transform(expr),
+                set: set.clone(),
+                negated: *negated,
+            },
+            Expression::SemiJoin { keys, set, negated } => Expression::SemiJoin {
+                keys: keys.iter().map(|key| *transform(key)).collect(),
+                set: set.clone(),
+                negated: *negated,
+            },
+            Expression::Window { function, args, spec } => Expression::Window {
+                function: *function,
+                args: args.iter().map(|arg| *transform(arg)).collect(),
+                spec: Box::new(WindowSpec {
+                    partition_by: spec.partition_by.iter().map(|expr| *transform(expr)).collect(),
+                    order_by: spec.order_by.iter().map(|item| OrderByExpr {
+                        expr: *transform(&item.expr),
+                        ..item.clone()
+                    }).collect(),
+                    frame: spec.frame,
+                }),
+            },
+            Expression::Array(items) => Expression::Array(items.iter().map(|item| *transform(item)).collect()),
+            Expression::Subscript { expr, index } => Expression::Subscript {
+                expr: transform(expr),
+                index: transform(index),
+            },
+            Expression::ArrayComparison { left, op, right, all } => Expression::ArrayComparison {
+                left: transform(left),
+                op: *op,
+                right: transform(right),
+                all: *all,
+            },
+            Expression::Collate { expr, collation, implicit } => Expression::Collate {
+                expr: transform(expr),
+                collation: *collation,
+                implicit: *implicit,
+            },
+            Expression::Like { expr, pattern, negated, case_insensitive, escape } => Expression::Like {
+                expr: transform(expr),
+                pattern: transform(pattern),
+                negated: *negated,
+                case_insensitive: *case_insensitive,
+                escape: *escape,
+            },
+        }
+    }
+
+    /// 入力カラムに対する式の結果型を求める
+    pub fn data_type(&self, columns: &[Column]) -> Result<DataType, ExpressionError> {
+        match self {
+            Expression::Column(name) | Expression::ColumnAt { name, .. } => resolve_column(columns, name)
+                .map(|c| c.data_type.clone())
+                .ok_or_else(|| ExpressionError::ColumnNotFound(name.clone())),
+            Expression::Literal(value) => Ok(value.data_type()),
+            Expression::Aggregate { function, argument } => {
+                let argument_type = match argument {
+                    Some(arg) => arg.data_type(columns)?,
+                    None => DataType::Integer,
+                };
+                Ok(function.return_type(argument_type))
+            },
+            Expression::ScalarSubquery(_) => {
+                Err(ExpressionError::InvalidOperation("Subquery has not been executed".to_string()))
+            },
+            Expression::BinaryOp { left, op, right } if op.is_arithmetic() => {
+                op.result_type(left.data_type(columns)?, right.data_type(columns)?)
+            },
+            Expression::BinaryOp { left, op, right } if op.is_json() => {
+                op.json_result_type(left.data_type(columns)?, right.data_type(columns)?)
+            },
+            Expression::Cast { expr, data_type, .. } => {
+                expr.data_type(columns)?;
+                Ok(data_type.clone())
+            },
+            Expression::Negate(expr) => match expr.data_type(columns)? {
+                t @ (DataType::Integer | DataType::Float | DataType::Decimal(..) | DataType::Interval | DataType::Null) => Ok(t),
+                other => Err(ExpressionError::InvalidOperation(format!("Cannot negate {}", other))),
+            },
+            Expression::Case { operand, branches, else_result } => {
+                if let Some(operand) = operand {
+                    operand.data_type(columns)?;
+                }
+                let mut result_type = DataType::Null;
+                for (condition, result) in branches {
+                    condition.data_type(columns)?;
+                    let branch_type = result.data_type(columns)?;
+                    result_type = result_type.common_type(&branch_type).ok_or_else(|| ExpressionError::InvalidOperation(
+                        format!("CASE types {} and {} cannot be matched", result_type, branch_type)))?;
+                }
+                if let Some(else_result) = else_result {
+                    let else_type = else_result.data_type(columns)?;
+                    result_type = result_type.common_type(&else_type).ok_or_else(|| ExpressionError::InvalidOperation(
+                        format!("CASE types {} and {} cannot be matched", result_type, else_type)))?;
+                }
+                Ok(result_type)
+            },
+            Expression::Function { name, args, function } => {
+                let function = function.as_ref().ok_or_else(|| ExpressionError::UnknownFunction(name.clone()))?;
+                let arg_types = args.iter()
+                    .map(|arg| arg.data_type(columns))
+                    .collect::<Result<Vec<_>, _>>()?;
+                function.return_type(&arg_types)
+            },
+            Expression::UserAggregate { function, args } => {
+                let arg_types = args.iter()
+                    .map(|arg| arg.data_type(columns))
+                    .collect::<Result<Vec<_>, _>>()?;
+                function.return_type(&arg_types)
+            },
+            Expression::Window { function, args, .. } => {
+                for child in self.children() {
+                    child.data_type(columns)?;
+                }
+                let argument_type = match args.first() {
+                    Some(arg) => arg.data_type(columns)?,
+                    None => DataType::Integer,
+                };
+                Ok(match function {
+                    WindowFunction::RowNumber | WindowFunction::Rank | WindowFunction::DenseRank => DataType::Integer,
+                    WindowFunction::Aggregate(aggregate) => aggregate.return_type(argument_type),
+                    _ => argument_type,
+                })
+            },
+            Expression::Array(items) => {
+                let element_types = items.iter()
+                    .map(|item| item.data_type(columns))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(DataType::Array(Box::new(common_element_type(element_types.into_iter())?)))
+            },
+            Expression::Subscript { expr, index } => {
+                let index_type = index.data_type(columns)?;
+                if !matches!(index_type, DataType::Integer | DataType::Null) {
+                    return Err(ExpressionError::InvalidOperation(
+                        format!("Array index must be INTEGER, got {}", index_type)));
+                }
+                match expr.data_type(columns)? {
+                    DataType::Array(element) => Ok(*element),
+                    DataType::Null => Ok(DataType::Null),
+                    other => Err(ExpressionError::InvalidOperation(format!("Cannot subscript {}", other))),
+                }
+            },
+            Expression::ArrayComparison { left, right, all, .. } => {
+                left.data_type(columns)?;
+                match right.data_type(columns)? {
+                    DataType::Array(_) | DataType::Null => Ok(DataType::Boolean),
+                    other => Err(ExpressionError::InvalidOperation(
+                        format!("{} requires an array, got {}", if *all { "ALL" } else { "ANY" }, other))),
+                }
+            },
+            Expression::Collate { expr, collation, .. } => match expr.data_type(columns)? {
+                t @ (DataType::Text | DataType::Null) => Ok(t),
+                other => Err(ExpressionError::InvalidOperation(
+                    format!("Collation {} cannot be applied to {}", collation, other))),
+            },
+            _ => {
+                for child in self.children() {
+                    child.data_type(columns)?;
+                }
+                Ok(DataType::Boolean)
+            },
+        }
+    }
+}
+
+/// 論理演算の値を取り出す（NULLはNone）
+fn as_bool(value: Value) -> Result<Option<bool>, ExpressionError> {
+    match value {
+        Value::Boolean(b) => Ok(Some(b)),
+        Value::Null => Ok(None),
+        other => Err(ExpressionError::InvalidOperation(
+            format!("Expected BOOLEAN, got {}", other.data_type()))),
+    }
+}
+
+/// JSONの値を取り出す（文字列はJSONとして解析する）
+fn as_json(value: &Value) -> Result<Cow<'_, serde_json::Value>, ExpressionError> {
+    match value {
+        Value::Json(json) => Ok(Cow::Borrowed(json)),
+        Value::Text(s) => serde_json::from_str(s).map(Cow::Owned).map_err(|_| ExpressionError::InvalidOperation(
+            format!("Invalid JSON: {}", s))),
+        other => Err(ExpressionError::InvalidOperation(format!("Expected JSON, got {}", other.data_type()))),
+    }
+}
+
+/// 数値を浮動小数点数として取り出す
+fn as_f64(value: &Value) -> f64 {
+    match value {
+        Value::Integer(i) => *i as f64,
+        Value::Float(f) => *f,
+        Value::Decimal(d) => d.to_f64().unwrap_or(f64::NAN),
+        _ => f64::NAN,
+    }
+}
+
+/// 日付に日数を加える（負の日数も扱う）
+fn add_days(date: NaiveDate, days: i64) -> Option<NaiveDate> {
+    if days >= 0 {
+        date.checked_add_days(Days::new(days as u64))
+    } else {
+        date.checked_sub_days(Days::new(days.unsigned_abs()))
+    }
+}
+
+/// カラムを参照する式（照合順序の指定を含む）が参照するカラム
+fn referenced_column<'a>(expr: &Expression, columns: &'a [Column]) -> Option<&'a Column> {
+    match expr {
+        Expression::Column(name) => resolve_column(columns, name),
+        Expression::ColumnAt { index, .. } => columns.get(*index),
+        Expression::Collate { expr, .. } => referenced_column(expr, columns),
+        _ => None,
+    }
+}
+
+/// 2つの被演算子から比較に使う照合順序を決める（明示的な指定を優先し、なければカラムの照合順序を使う）
+fn derived_collation(left: &Expression, right: &Expression) -> Option<Collation> {
+    let explicit = |expr: &Expression| match expr {
+        Expression::Collate { collation, implicit: false, .. } => Some(*collation),
+        _ => None,
+    };
+    explicit(left).or_else(|| explicit(right)).or_else(|| left.collation()).or_else(|| right.collation())
+}
+
+/// IN (値のリスト) の比較に使う照合順序（明示的な指定を、次にカラムの照合順序を優先する）
+fn list_collation(expr: &Expression, list: &[Expression]) -> Option<Collation> {
+    let explicit = |e: &Expression| match e {
+        Expression::Collate { collation, implicit: false, .. } => Some(*collation),
+        _ => None,
+    };
+    std::iter::once(expr).chain(list).find_map(explicit)
+        .or_else(|| std::iter::once(expr).chain(list).find_map(Expression::collation))
+}
+
+/// 照合順序に従って2つの値を比較する
+fn compare_collated(collation: Option<Collation>, left: &Value, right: &Value) -> Result<Ordering, ExpressionError> {
+    let ordering = match collation {
+        Some(collation) => collation.compare_values(left, right),
+        None => left.compare(right),
+    };
+    ordering.ok_or_else(|| ExpressionError::InvalidOperation(
+        format!("Cannot compare {} with {}", left.data_type(), right.data_type())))
+}
+
+/// LIKE のパターンに一致するかどうか（照合順序に従って正規化してから比較する）
+fn like_match(text: &str, pattern: &str, escape: Option<char>, collation: Collation) -> bool {
+    let text: Vec<char> = collation.key(text).chars().collect();
+    let pattern: Vec<char> = collation.key(pattern).chars().collect();
+    let escape = escape.unwrap_or('\\');
+    let escape = collation.key(&escape.to_string()).chars().next().unwrap_or(escape);
+
+    // パターンを (ワイルドカードかどうか, 文字) の列にする
+    let mut tokens = Vec::with_capacity(pattern.len());
+    let mut chars = pattern.into_iter();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c == escape => tokens.push((false, chars.next().unwrap_or(c))),
+            '%' | '_' => tokens.push((true, c)),
+            c => tokens.push((false, c)),
+        }
+    }
+
+    // % の直後の位置を覚えておき、一致しなければそこからやり直す
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some((true, '%')) => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            },
+            Some((true, _)) => {
+                t += 1;
+                p += 1;
+            },
+            Some((false, c)) if *c == text[t] => {
+                t += 1;
+                p += 1;
+            },
+            _ => match backtrack {
+                Some((resume, start)) => {
+                    backtrack = Some((resume, start + 1));
+                    p = resume;
+                    t = start + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    tokens[p..].iter().all(|token| *token == (true, '%'))
+}
+
+/// 配列の要素の型をまとめる（NULLの要素は除く。要素がなければNULL）
+fn common_element_type(mut types: impl Iterator<Item = DataType>) -> Result<DataType, ExpressionError> {
+    types.try_fold(DataType::Null, |result, t| result.common_type(&t).ok_or_else(|| {
+        ExpressionError::InvalidOperation(format!("ARRAY types {} and {} cannot be matched", result, t))
+    }))
+}
+
+/// NOT IN などのために論理値を反転する（NULLはそのまま）
+fn negate_if(value: Value, negated: bool) -> Value {
+    match value {
+        Value::Boolean(b) => Value::Boolean(b != negated),
+        other => other,
+    }
+}
+
+/// 式のリストをカンマ区切りで書き出す
+fn write_list(f: &mut fmt::Formatter<'_>, items: &[Expression]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// 二項演算の被演算子を書き出す（指定した強さより弱い演算は括弧で囲む）
+fn write_operand(f: &mut fmt::Formatter<'_>, expr: &Expression, precedence: u8) -> fmt::Result {
+    match expr {
+        Expression::BinaryOp { op, .. } if op.precedence() < precedence => write!(f, "({})", expr),
+        _ => write!(f, "{}", expr),
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Column(name) | Expression::ColumnAt { name, .. } => write!(f, "{}", name),
+            Expression::Literal(Value::Text(s)) => write!(f, "'{}'", s),
+            Expression::Literal(Value::Interval(i)) => write!(f, "INTERVAL '{}'", i),
+            Expression::Literal(Value::Timestamp(dt)) => write!(f, "TIMESTAMP '{}'", dt),
+            Expression::Literal(Value::TimestampTz(dt)) => write!(f, "TIMESTAMPTZ '{}'", dt),
+            Expression::Literal(Value::Date(d)) => write!(f, "DATE '{}'", d),
+            Expression::Literal(Value::Time(t)) => write!(f, "TIME '{}'", t),
+            Expression::Literal(Value::Uuid(u)) => write!(f, "UUID '{}'", u),
+            Expression::Literal(Value::Bytes(b)) => {
+                write!(f, "X'")?;
+                b.iter().try_for_each(|byte| write!(f, "{:02X}", byte))?;
+                write!(f, "'")
+            },
+            Expression::Literal(Value::Array(items)) => {
+                write!(f, "ARRAY[")?;
+                write_list(f, &items.iter().cloned().map(Expression::Literal).collect::<Vec<_>>())?;
+                write!(f, "]")
+            },
+            Expression::Literal(value) => write!(f, "{}", value),
+            Expression::Aggregate { function, argument: Some(arg) } => write!(f, "{}({})", function, arg),
+            Expression::Aggregate { function, argument: None } => write!(f, "{}(*)", function),
+            Expression::UserAggregate { function, args } => {
+                write!(f, "{}(", function.name())?;
+                write_list(f, args)?;
+                write!(f, ")")
+            },
+            Expression::BinaryOp { left, op, right } => {
+                write_operand(f, left, op.precedence())?;
+                write!(f, " {} ", op)?;
+                // 右側は同じ強さの演算子でも括弧が必要（a - (b - c) など）
+                write_operand(f, right, op.precedence() + 1)
+            },
+            Expression::Not(expr) => write!(f, "NOT {}", expr),
+            Expression::Cast { expr, data_type, .. } => {
+                write!(f, "CAST({} AS {})", expr, data_type.to_string().to_uppercase())
+            },
+            Expression::Negate(expr) => {
+                write!(f, "-")?;
+                write_operand(f, expr, u8::MAX)
+            },
+            Expression::Case { operand, branches, else_result } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (condition, result) in branches {
+                    write!(f, " WHEN {} THEN {}", condition, result)?;
+                }
+                if let Some(else_result) = else_result {
+                    write!(f, " ELSE {}", else_result)?;
+                }
+                write!(f, " END")
+            },
+            Expression::Function { name, args, .. } => {
+                write!(f, "{}(", name)?;
+                write_list(f, args)?;
+                write!(f, ")")
+            },
+            Expression::IsNull { expr, negated } => {
+                write!(f, "{} IS {}NULL", expr, if *negated { "NOT " } else { "" })
+            },
+            Expression::InList { expr, list, negated } => {
+                write!(f, "{} {}IN (", expr, if *negated { "NOT " } else { "" })?;
+                write_list(f, list)?;
+                write!(f, ")")
+            },
+            Expression::ScalarSubquery(query) => write!(f, "({})", query),
+            Expression::InSubquery { expr, query, negated } => {
+                write!(f, "{} {}IN ({})", expr, if *negated { "NOT " } else { "" }, query)
+            },
+            Expression::Exists { query, negated } => {
+                write!(f, "{}EXISTS ({})", if *negated { "NOT " } else { "" }, query)
+            },
+            Expression::InSet { expr, negated, .. } => {
+                write!(f, "{} {}IN (<subquery>)", expr, if *negated { "NOT " } else { "" })
+            },
+            Expression::SemiJoin { keys, negated, .. } => {
+                write!(f, "{}EXISTS (<subquery> ON ", if *negated { "NOT " } else { "" })?;
+                write_list(f, keys)?;
+                write!(f, ")")
+            },
+            Expression::Window { function: WindowFunction::Aggregate(AggregateFunction::Count), args, spec } if args.is_empty() => {
+                write!(f, "COUNT(*) OVER ({})", spec)
+            },
+            Expression::Window { function, args, spec } => {
+                write!(f, "{}(", function)?;
+                write_list(f, args)?;
+                write!(f, ") OVER ({})", spec)
+            },
+            Expression::Array(items) => {
+                write!(f, "ARRAY[")?;
+                write_list(f, items)?;
+                write!(f, "]")
+            },
+            Expression::Subscript { expr, index } => {
+                write_operand(f, expr, u8::MAX)?;
+                write!(f, "[{}]", index)
+            },
+            Expression::ArrayComparison { left, op, right, all } => {
+                write_operand(f, left, op.precedence())?;
+                write!(f, " {} {}({})", op, if *all { "ALL" } else { "ANY" }, right)
+            },
+            Expression::Collate { expr, implicit: true, .. } => write!(f, "{}", expr),
+            Expression::Collate { expr, collation, .. } => {
+                write_operand(f, expr, u8::MAX)?;
+                write!(f, " COLLATE \"{}\"", collation)
+            },
+            Expression::Like { expr, pattern, negated, case_insensitive, escape } => {
+                write!(f, "{} {}{} {}", expr, if *negated { "NOT " } else { "" },
+                    if *case_insensitive { "ILIKE" } else { "LIKE" }, pattern)?;
+                match escape {
+                    Some(escape) => write!(f, " ESCAPE '{}'", escape),
+                    None => Ok(()),
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::FixedOffset;
+
+use crate::domain::entity::{DataType, Value};
+use crate::domain::expression::ExpressionError;
+
+/// SQLから呼び出せるスカラー関数
+pub trait ScalarFunction: Send + Sync {
+    /// 関数名（大文字）
+    fn name(&self) -> &str;
+
+    /// 引数の型から結果の型を求める（引数の数や型が合わない場合はエラー）
+    fn return_type(&self, args: &[DataType]) -> Result<DataType, ExpressionError>;
+
+    /// 引数の値に対して関数を呼び出す
+    fn invoke(&self, args: &[Value]) -> Result<Value, ExpressionError>;
+
+    /// 指定したタイムゾーンで日時を扱う関数を返す（タイムゾーンによらない関数は None）
+    fn in_time_zone(&self, _time_zone: FixedOffset) -> Option<Arc<dyn ScalarFunction>> {
+        None
+    }
+}
+
+/// 式の中で保持する関数への参照（同じ名前の関数は等しいとみなす）
+#[derive(Clone)]
+pub struct ScalarFunctionRef(pub Arc<dyn ScalarFunction>);
+
+impl ScalarFunctionRef {
+    pub fn new(function: Arc<dyn ScalarFunction>) -> Self {
+        Self(function)
+    }
+}
+
+impl std::ops::Deref for ScalarFunctionRef {
+    type Target = dyn ScalarFunction;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl PartialEq for ScalarFunctionRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl fmt::Debug for ScalarFunctionRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ScalarFunction({})", self.0.name())
+    }
+}
+
+/// 集約関数の途中状態（状態の型は関数ごとに異なる）
+pub type AggregateState = Box<dyn Any + Send>;
+
+/// SQLから呼び出せる利用者定義の集約関数
+///
+/// グループごとに `init` で状態を作成し、各行の引数の値を `update` で取り込み、
+/// `finalize` で結果を求める。行の多い集約では一定の行数ごとに並行して集約し、その状態を `merge` でまとめる。
+/// 組み込みの集約関数と異なり、NULLを含む引数もそのまま `update` に渡される。
+pub trait UserAggregate: Send + Sync {
+    /// 関数名（大文字）
+    fn name(&self) -> &str;
+
+    /// 引数の型から結果の型を求める（引数の数や型が合わない場合はエラー）
+    fn return_type(&self, args: &[DataType]) -> Result<DataType, ExpressionError>;
+
+    /// 空の状態を作成する
+    fn init(&self) -> AggregateState;
+
+    /// 1行分の引数の値を状態に取り込む
+    fn update(&self, state: &mut AggregateState, args: &[Value]) -> Result<(), ExpressionError>;
+
+    /// 別の状態を取り込む
+    fn merge(&self, state: &mut AggregateState, other: AggregateState) -> Result<(), ExpressionError>;
+
+    /// 状態から集約結果を求める
+    fn finalize(&self, state: &AggregateState) -> Result<Value, ExpressionError>;
+}
+
+/// 式の中で保持する利用者定義の集約関数への参照（同じ名前の関数は等しいとみなす）
+#[derive(Clone)]
+pub struct UserAggregateRef(pub Arc<dyn UserAggregate>);
+
+impl UserAggregateRef {
+    pub fn new(function: Arc<dyn UserAggregate>) -> Self {
+        Self(function)
+    }
+}
+
+impl std::ops::Deref for UserAggregateRef {
+    type Target = dyn UserAggregate;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl PartialEq for UserAggregateRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.name() == other.0.name()
+    }
+}
+
+impl fmt::Debug for UserAggregateRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "UserAggregate({})", self.0.name())
+    }
+}
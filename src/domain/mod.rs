@@ -1,2 +1,4 @@
 pub mod entity;
+pub mod expression;
+pub mod function;
 pub mod repository;
\ No newline at end of file
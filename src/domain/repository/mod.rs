@@ -2,5 +2,5 @@ pub mod table_repository;
 
 pub use table_repository::{
     TableRepository, RepositoryError, RepositoryFactory,
-    FilterCondition, FilterOperator
+    FilterCondition, FilterOperator, OnConflict, ConflictAction, UpsertOutcome
 };
\ No newline at end of file
@@ -1,8 +1,10 @@
 use async_trait::async_trait;
-use crate::domain::entity::{Table, Row, ResultSet};
+use crate::domain::entity::{Table, Row, RowValues, ResultSet, View, EnumType, LabelPosition, Column};
 use crate::domain::entity::value::Value;
+use crate::domain::expression::{resolve_column, BinaryOperator, Expression, ExpressionError, LiteralConverter};
 use crate::Error;
 use  std::sync::Arc;
+use std::cmp::Ordering;
 
 // テーブルリポジトリトレイト
 #[derive(thiserror::Error, Debug)]
@@ -16,12 +18,27 @@ pub enum RepositoryError {
     #[error("Column {0} not found in table {1}")]
     ColumnNotFound(String, String),
 
+    #[error("View {0} not found")]
+    ViewNotFound(String),
+
+    #[error("View {0} already exists")]
+    ViewAlreadyExists(String),
+
+    #[error("Type {0} not found")]
+    TypeNotFound(String),
+
+    #[error("Type {0} already exists")]
+    TypeAlreadyExists(String),
+
     #[error("Storage error: {0}")]
     StorageError(String),
 
     #[error("Data error: {0}")]
     DataError(String),
 
+    #[error("Transaction error: {0}")]
+    TransactionError(String),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 }
@@ -32,8 +49,13 @@ impl From<RepositoryError> for Error {
             RepositoryError::TableNotFound(name) => Error::Schema(format!("Table {} not found", name)),
             RepositoryError::TableAlreadyExists(name) => Error::Schema(format!("Table {} already exists", name)),
             RepositoryError::ColumnNotFound(column, table) => Error::Schema(format!("Column {} not found in table {}", column, table)),
+            RepositoryError::ViewNotFound(name) => Error::Schema(format!("View {} not found", name)),
+            RepositoryError::ViewAlreadyExists(name) => Error::Schema(format!("View {} already exists", name)),
+            RepositoryError::TypeNotFound(name) => Error::Schema(format!("Type {} not found", name)),
+            RepositoryError::TypeAlreadyExists(name) => Error::Schema(format!("Type {} already exists", name)),
             RepositoryError::StorageError(msg) => Error::Storage(msg),
             RepositoryError::DataError(msg) => Error::Execution(msg),
+            RepositoryError::TransactionError(msg) => Error::Execution(msg),
             RepositoryError::InternalError(msg) => Error::Internal(msg),
         }
     }
@@ -56,9 +78,48 @@ pub trait TableRepository: Send + Sync {
     
    /// すべてのテーブル名を取得する
    async fn get_table_names(&self) -> Result<Vec<String>, RepositoryError>;
+
+   /// ビューを作成する（or_replaceがtrueの場合は既存の定義を置き換える）
+   async fn create_view(&self, view: &View, or_replace: bool) -> Result<(), RepositoryError>;
+
+   /// マテリアライズドビューを作成し、初期データを同名のテーブルに格納する
+   async fn create_materialized_view(
+       &self,
+       view: &View,
+       table: &Table,
+       rows: &[Row],
+       or_replace: bool,
+   ) -> Result<(), RepositoryError>;
+
+   /// ビューが存在するかチェックする
+   async fn view_exists(&self, view_name: &str) -> Result<bool, RepositoryError>;
+
+   /// ビューを削除する
+   async fn drop_view(&self, view_name: &str, if_exists: bool) -> Result<(), RepositoryError>;
+
+   /// 名前でビューの定義を取得する
+   async fn get_view(&self, view_name: &str) -> Result<View, RepositoryError>;
+
+   /// すべてのビュー名を取得する
+   async fn get_view_names(&self) -> Result<Vec<String>, RepositoryError>;
    
-   /// テーブルに1行のデータを挿入する
-   async fn insert(&self, table_name: &str, row: &Row) -> Result<(), RepositoryError>;
+   /// 列挙型を作成する
+   async fn create_type(&self, enum_type: &EnumType) -> Result<(), RepositoryError>;
+
+   /// 名前で列挙型の定義を取得する
+   async fn get_type(&self, type_name: &str) -> Result<Arc<EnumType>, RepositoryError>;
+
+   /// 列挙型にラベルを追加する（その型を使うカラムと格納済みの値も新しい定義に置き換える）
+   async fn add_enum_label(
+       &self,
+       type_name: &str,
+       label: &str,
+       position: Option<&LabelPosition>,
+       if_not_exists: bool,
+   ) -> Result<(), RepositoryError>;
+   
+   /// テーブルに1行のデータを挿入し、カラムの型に揃えて格納した行を返す
+   async fn insert(&self, table_name: &str, row: &Row) -> Result<Row, RepositoryError>;
    
    /// 複数行のデータを一括挿入する
    async fn insert_many(&self, table_name: &str, rows: &[Row]) -> Result<(), RepositoryError>;
@@ -72,19 +133,43 @@ pub trait TableRepository: Send + Sync {
         filter: Option<&FilterCondition>
     ) -> Result<ResultSet, RepositoryError>;
 
-    /// 条件に合致する行を更新する
+    /// 条件に合致する行を更新し、格納されている更新前と更新後の行の組を返す（代入する式は行ごとに評価する）
     async fn update(
         &self,
         table_name: &str,
-        updates: &[(String, Value)],
+        updates: &[(String, Expression)],
         filter: Option<&FilterCondition>,
-    ) -> Result<usize, RepositoryError>;
+    ) -> Result<Vec<(Row, Row)>, RepositoryError>;
 
+    /// 条件に合致する行を削除し、削除した行を返す
     async fn delete(
         &self,
         table_name: &str,
         filter: Option<&FilterCondition>,
-    ) -> Result<usize, RepositoryError>;
+    ) -> Result<Vec<Row>, RepositoryError>;
+
+    /// 行を挿入し、主キー・一意制約と競合した場合は指定された方法で解決する
+    async fn upsert(
+        &self,
+        table_name: &str,
+        row: &Row,
+        on_conflict: &OnConflict,
+    ) -> Result<UpsertOutcome, RepositoryError>;
+
+    /// テーブルの全行を置き換える
+    async fn replace_rows(&self, table_name: &str, rows: &[Row]) -> Result<(), RepositoryError>;
+
+    /// マテリアライズドビューの結果から指定した行と等しい行を1つずつ削除し、行を追加する
+    async fn apply_view_delta(&self, view_name: &str, removed: &[Row], added: &[Row]) -> Result<(), RepositoryError>;
+
+    /// トランザクションを開始する（開始時点の状態を保存する）
+    async fn begin_transaction(&self) -> Result<(), RepositoryError>;
+
+    /// トランザクションを確定する
+    async fn commit_transaction(&self) -> Result<(), RepositoryError>;
+
+    /// トランザクション開始時点の状態に戻す
+    async fn rollback_transaction(&self) -> Result<(), RepositoryError>;
 }
 
 /// クエリフィルター条件
@@ -97,9 +182,181 @@ pub enum FilterCondition {
          value: Value,
    },
 
+   /// カラムを位置に解決済みの単一条件（ストレージ内の行を評価するために bind で Simple から置き換える）
+   SimpleAt {
+         column: String,
+         index: usize,
+         operator: FilterOperator,
+         value: Value,
+   },
+
    /// 複数条件（ANDまたはOR）
    And(Vec<FilterCondition>),
    Or(Vec<FilterCondition>),
+
+   /// 一般的な式による条件（式の値が真の行のみを満たす）
+   Expression(Expression),
+}
+
+impl FilterCondition {
+    /// 行がこの条件を満たすかどうかを評価する
+    pub fn matches<R: RowValues + ?Sized>(&self, row: &R) -> bool {
+        match self {
+            FilterCondition::Simple { column, operator, value } => {
+                row.value(column).is_some_and(|row_value| operator.accepts(row_value, value))
+            },
+            FilterCondition::SimpleAt { column, index, operator, value } => {
+                row.value_at(*index)
+                    .or_else(|| row.value(column))
+                    .is_some_and(|row_value| operator.accepts(row_value, value))
+            },
+            FilterCondition::And(conditions) => {
+                conditions.iter().all(|c| c.matches(row))
+            },
+            FilterCondition::Or(conditions) => {
+                conditions.iter().any(|c| c.matches(row))
+            },
+            FilterCondition::Expression(expr) => {
+                matches!(expr.evaluate(row), Ok(Value::Boolean(true)))
+            },
+        }
+    }
+
+    /// 条件にサブクエリが含まれるかどうか
+    pub fn contains_subquery(&self) -> bool {
+        match self {
+            FilterCondition::Simple { .. } | FilterCondition::SimpleAt { .. } => false,
+            FilterCondition::And(conditions) | FilterCondition::Or(conditions) => {
+                conditions.iter().any(|c| c.contains_subquery())
+            },
+            FilterCondition::Expression(expr) => expr.contains_subquery(),
+        }
+    }
+
+    /// 照合順序が指定されたカラムの比較を、その照合順序で行う条件に書き換える
+    pub fn with_collations(&self, columns: &[Column]) -> FilterCondition {
+        if columns.iter().all(|c| c.collation.is_none()) {
+            return self.clone();
+        }
+        match self {
+            FilterCondition::Simple { column, operator, value } | FilterCondition::SimpleAt { column, operator, value, .. } => {
+                let collation = match resolve_column(columns, column).and_then(|c| c.collation) {
+                    Some(collation) => collation,
+                    None => return self.clone(),
+                };
+                let column = match self {
+                    FilterCondition::SimpleAt { index, .. } => Expression::ColumnAt { index: *index, name: column.clone() },
+                    _ => Expression::Column(column.clone()),
+                };
+                let left = Box::new(Expression::Collate {
+                    expr: Box::new(column),
+                    collation,
+                    implicit: true,
+                });
+                let right = Box::new(Expression::Literal(value.clone()));
+                let op = match operator {
+                    FilterOperator::Like => return FilterCondition::Expression(Expression::Like {
+                        expr: left,
+                        pattern: right,
+                        negated: false,
+                        case_insensitive: false,
+                        escape: None,
+                    }),
+                    FilterOperator::Equal => BinaryOperator::Eq,
+                    FilterOperator::NotEqual => BinaryOperator::NotEq,
+                    FilterOperator::Greater => BinaryOperator::Gt,
+                    FilterOperator::GreaterOrEqual => BinaryOperator::GtEq,
+                    FilterOperator::Less => BinaryOperator::Lt,
+                    FilterOperator::LessOrEqual => BinaryOperator::LtEq,
+                };
+                FilterCondition::Expression(Expression::BinaryOp { left, op, right })
+            },
+            FilterCondition::And(conditions) => {
+                FilterCondition::And(conditions.iter().map(|c| c.with_collations(columns)).collect())
+            },
+            FilterCondition::Or(conditions) => {
+                FilterCondition::Or(conditions.iter().map(|c| c.with_collations(columns)).collect())
+            },
+            FilterCondition::Expression(expr) => FilterCondition::Expression(expr.with_collations(columns)),
+        }
+    }
+
+    /// カラムと比較する文字列の定数を、convert でカラムの型の値に置き換える（Expression::coerce_literals を参照）
+    pub fn coerce_literals(
+        &self,
+        columns: &[Column],
+        convert: &mut LiteralConverter<'_>
+    ) -> Result<FilterCondition, ExpressionError> {
+        match self {
+            FilterCondition::Simple { column, operator, value } | FilterCondition::SimpleAt { column, operator, value, .. } => {
+                let index = match self {
+                    FilterCondition::SimpleAt { index, .. } => Some(*index),
+                    _ => None,
+                };
+                let target = match index {
+                    Some(index) => columns.get(index),
+                    None => resolve_column(columns, column),
+                };
+                let converted = match (value, target) {
+                    (Value::Text(text), Some(target)) if *operator != FilterOperator::Like => convert(text, target)?,
+                    _ => None,
+                };
+                let Some(value) = converted else {
+                    return Ok(self.clone());
+                };
+                Ok(match index {
+                    Some(index) => FilterCondition::SimpleAt { column: column.clone(), index, operator: *operator, value },
+                    None => FilterCondition::Simple { column: column.clone(), operator: *operator, value },
+                })
+            },
+            FilterCondition::And(conditions) => Ok(FilterCondition::And(conditions.iter()
+                .map(|c| c.coerce_literals(columns, convert))
+                .collect::<Result<_, _>>()?)),
+            FilterCondition::Or(conditions) => Ok(FilterCondition::Or(conditions.iter()
+                .map(|c| c.coerce_literals(columns, convert))
+                .collect::<Result<_, _>>()?)),
+            FilterCondition::Expression(expr) => Ok(FilterCondition::Expression(expr.coerce_literals(columns, convert)?)),
+        }
+    }
+
+    /// カラム名をテーブルのカラムの位置に解決する（修飾されたカラム名は修飾子を外して探し、テーブルにないカラムはエラー）
+    pub fn bind(&self, table: &Table) -> Result<FilterCondition, ExpressionError> {
+        Ok(match self {
+            FilterCondition::Simple { column, operator, value } => {
+                let index = table.get_column_index(column)
+                    .or_else(|| {
+                        let (_, bare) = column.split_once('.')?;
+                        table.get_column_index(bare)
+                    })
+                    .ok_or_else(|| ExpressionError::ColumnNotFound(column.clone()))?;
+                FilterCondition::SimpleAt {
+                    column: column.clone(),
+                    index,
+                    operator: *operator,
+                    value: value.clone(),
+                }
+            },
+            FilterCondition::SimpleAt { .. } => self.clone(),
+            FilterCondition::And(conditions) => FilterCondition::And(conditions.iter()
+                .map(|c| c.bind(table))
+                .collect::<Result<_, _>>()?),
+            FilterCondition::Or(conditions) => FilterCondition::Or(conditions.iter()
+                .map(|c| c.bind(table))
+                .collect::<Result<_, _>>()?),
+            FilterCondition::Expression(expr) => FilterCondition::Expression(expr.bind(table)?),
+        })
+    }
+
+    /// 条件が参照するカラム名を収集する
+    pub fn referenced_columns(&self) -> Vec<&str> {
+        match self {
+            FilterCondition::Simple { column, .. } | FilterCondition::SimpleAt { column, .. } => vec![column.as_str()],
+            FilterCondition::And(conditions) | FilterCondition::Or(conditions) => {
+                conditions.iter().flat_map(|c| c.referenced_columns()).collect()
+            },
+            FilterCondition::Expression(expr) => expr.referenced_columns(),
+        }
+    }
 }
 
 /// フィルター演算子
@@ -114,9 +371,151 @@ pub enum FilterOperator {
     Like,
 }
 
+impl FilterOperator {
+    /// カラムの値と条件の値がこの演算子の条件を満たすかどうか（NULLのカラムはどの条件も満たさない）
+    fn accepts(&self, row_value: &Value, value: &Value) -> bool {
+        if *row_value == Value::Null {
+            return false;
+        }
+        match self {
+            // 10進数と整数のように型が異なっても数値として等しければ一致とみなす
+            FilterOperator::Equal => row_value == value || row_value.compare(value) == Some(Ordering::Equal),
+            FilterOperator::NotEqual => row_value != value && row_value.compare(value) != Some(Ordering::Equal),
+            FilterOperator::Greater => row_value.compare(value) == Some(Ordering::Greater),
+            FilterOperator::GreaterOrEqual => matches!(row_value.compare(value), Some(Ordering::Greater | Ordering::Equal)),
+            FilterOperator::Less => row_value.compare(value) == Some(Ordering::Less),
+            FilterOperator::LessOrEqual => matches!(row_value.compare(value), Some(Ordering::Less | Ordering::Equal)),
+            FilterOperator::Like => {
+                // シンプルなLIKE演算子の実装（%のみサポート）
+                if let (Value::Text(text), Value::Text(pattern)) = (row_value, value) {
+                    if pattern.starts_with('%') && pattern.ends_with('%') {
+                        let search = &pattern[1..pattern.len()-1];
+                        text.contains(search)
+                    } else if let Some(search) = pattern.strip_prefix('%') {
+                        text.ends_with(search)
+                    } else if let Some(search) = pattern.strip_suffix('%') {
+                        text.starts_with(search)
+                    } else {
+                        text == pattern
+                    }
+                } else {
+                    false
+                }
+            },
+        }
+    }
+}
+
+/// INSERT時の競合（主キー・一意制約違反）の扱い
+#[derive(Debug, Clone)]
+pub struct OnConflict {
+    /// 競合を判定するカラム（空の場合はすべての主キー・一意制約カラム）
+    pub columns: Vec<String>,
+
+    /// 競合した場合の動作
+    pub action: ConflictAction,
+}
+
+/// 競合した場合の動作
+#[derive(Debug, Clone)]
+pub enum ConflictAction {
+    /// 何もしない
+    DoNothing,
+
+    /// 既存の行を更新する
+    /// （式中の `excluded.カラム名` は挿入しようとした行の値を指す）
+    DoUpdate(Vec<(String, Expression)>),
+}
+
+/// UPSERTの結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpsertOutcome {
+    /// 新しい行として挿入された（格納した行）
+    Inserted(Row),
+
+    /// 既存の行が更新された（更新前の行、更新後の行）
+    Updated(Row, Row),
+
+    /// 競合したため何もしなかった
+    Skipped,
+}
+
 /// リポジトリファクトリトレイト
 /// 様々なリポジトリ実装を生成する責任を持つ
 pub trait  RepositoryFactory: Send + Sync {
     /// テーブルリポジトリを取得する
     fn table_repository(&self) -> Arc<dyn TableRepository>;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::DataType;
+
+    fn table() -> Table {
+        Table::new("t")
+            .with_column(Column::new("id", DataType::Integer)).unwrap()
+            .with_column(Column::new("name", DataType::Text)).unwrap()
+    }
+
+    fn name_is(column: &str, name: &str) -> FilterCondition {
+        FilterCondition::Simple {
+            column: column.to_string(),
+            operator: FilterOperator::Equal,
+            value: Value::Text(name.to_string()),
+        }
+    }
+
+    #[test]
+    fn bind_resolves_plain_and_qualified_columns_to_positions() {
+        let row = [Value::Integer(1), Value::Text("alice".to_string())];
+
+        for column in ["name", "t.name", "alias.name"] {
+            let bound = name_is(column, "alice").bind(&table()).unwrap();
+            assert!(matches!(bound, FilterCondition::SimpleAt { index: 1, .. }), "{}", column);
+            assert!(bound.matches(&row[..]));
+            assert!(!name_is(column, "bob").bind(&table()).unwrap().matches(&row[..]));
+        }
+
+        let expr = FilterCondition::Expression(Expression::BinaryOp {
+            left: Box::new(Expression::Column("alias.id".to_string())),
+            op: BinaryOperator::Gt,
+            right: Box::new(Expression::Literal(Value::Integer(0))),
+        });
+        assert!(expr.bind(&table()).unwrap().matches(&row[..]));
+    }
+
+    #[test]
+    fn bind_follows_the_current_column_order() {
+        // 同じ条件でも、解決先はその時点のスキーマでの位置になる
+        let reordered = Table::new("t")
+            .with_column(Column::new("name", DataType::Text)).unwrap()
+            .with_column(Column::new("id", DataType::Integer)).unwrap();
+        let filter = FilterCondition::And(vec![name_is("name", "alice"), name_is("t.id", "x")]);
+
+        let FilterCondition::And(bound) = filter.bind(&reordered).unwrap() else {
+            panic!("bind must keep the AND");
+        };
+        assert!(matches!(bound[0], FilterCondition::SimpleAt { index: 0, .. }));
+        assert!(matches!(bound[1], FilterCondition::SimpleAt { index: 1, .. }));
+    }
+
+    #[test]
+    fn unknown_columns_fail_to_bind() {
+        assert_eq!(name_is("missing", "alice").bind(&table()).unwrap_err(), ExpressionError::ColumnNotFound("missing".to_string()));
+
+        // AND や式の中のカラムも解決できなければエラーになる
+        let filter = FilterCondition::And(vec![
+            name_is("name", "alice"),
+            FilterCondition::Expression(Expression::BinaryOp {
+                left: Box::new(Expression::Column("t.missing".to_string())),
+                op: BinaryOperator::Gt,
+                right: Box::new(Expression::Literal(Value::Integer(0))),
+            }),
+        ]);
+        assert_eq!(filter.bind(&table()).unwrap_err(), ExpressionError::ColumnNotFound("t.missing".to_string()));
+
+        // NULL は等号の条件に合致しない
+        assert!(!name_is("name", "alice").bind(&table()).unwrap().matches(&[Value::Integer(1), Value::Null][..]));
+    }
+}
@@ -1,7 +1,10 @@
 pub mod sql_parser;
 
 pub use sql_parser::{
-    SqlParser, ParseError, ParsedStatement,
-    CreateTableStatement, SelectStatement, InsertStatement,
-    UpdateStatement, DeleteStatement, DropTableStatement
+    SqlParser, ParseError, ParsedStatement, CommonTableExpression, RecursiveTerm,
+    SetOperation, SetOperator, Distinct, TableFunction,
+    CreateTableStatement, SelectStatement, SelectItem, InsertStatement, InsertSource,
+    UpdateStatement, DeleteStatement, DropTableStatement,
+    CreateViewStatement, DropViewStatement, RefreshMaterializedViewStatement, SetTimeZoneStatement,
+    CreateTypeStatement, AlterTypeStatement
 };
\ No newline at end of file
@@ -1,10 +1,27 @@
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{FixedOffset, Offset, Utc};
+use rust_decimal::Decimal;
+
 use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::{Parser, ParserError};
-use sqlparser::ast::{Statement, Query, SetExpr, TableFactor, Values, Expr, Value as SqlValue, 
-                     SelectItem, ObjectName, Ident, TableWithJoins};
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Token, Tokenizer};
+use sqlparser::ast::{Statement, Query, SetExpr, SetOperator as SqlSetOperator, SetQuantifier, With, TableFactor, Values, Expr, Value as SqlValue, 
+                     SelectItem as SqlSelectItem, ObjectName, Ident, TableAlias, TableWithJoins,
+                     Function, FunctionArg, FunctionArgExpr, SqlOption, OnInsert, ConflictTarget,
+                     OnConflictAction, Assignment, OrderByExpr as SqlOrderByExpr, WindowType,
+                     WindowFrame as SqlWindowFrame, WindowFrameBound, WindowFrameUnits,
+                     DateTimeField, TrimWhereField, Interval as SqlInterval, UnaryOperator, TimezoneInfo,
+                     JsonOperator};
 
-use crate::domain::entity::{DataType, Column, Value};
-use crate::domain::repository::{FilterCondition, FilterOperator};
+use crate::domain::entity::{parse_time_zone, Collation, DataType, Column, EnumType, Interval, LabelPosition, Row, TypeLimit, Value};
+use crate::domain::expression::{
+    Expression, AggregateFunction, BinaryOperator, WindowFunction, WindowSpec, WindowFrame,
+    FrameUnits, FrameBound, OrderByExpr, EXCLUDED
+};
+use crate::domain::repository::{FilterCondition, FilterOperator, OnConflict, ConflictAction};
 use thiserror::Error;
 
 /// SQL解析エラー
@@ -42,34 +59,198 @@ pub struct CreateTableStatement {
     pub table_name: String,
     pub columns: Vec<Column>,
     pub if_not_exists: bool,
+    /// CREATE TABLE ... AS SELECT の場合のクエリ
+    pub query: Option<SelectStatement>,
 }
 
 /// SELECT文からの解析結果
+#[derive(Debug, Clone)]
 pub struct SelectStatement {
     pub table_name: String,
-    pub columns: Option<Vec<String>>,
+    /// FROM句のテーブルの別名
+    pub table_alias: Option<String>,
+    /// FROM句のテーブル関数（指定されている場合、table_name はその別名）
+    pub table_function: Option<TableFunction>,
+    /// SELECT DISTINCT / DISTINCT ON (...)
+    pub distinct: Option<Distinct>,
+    pub projection: Vec<SelectItem>,
     pub filter: Option<FilterCondition>,
+    pub group_by: Vec<String>,
+    /// ORDER BY（ウィンドウ関数の評価後、LIMITの前に適用する）
+    pub order_by: Vec<OrderByExpr>,
     pub limit: Option<usize>,
+    /// WITH句で定義された共通テーブル式（定義順）
+    pub with: Vec<CommonTableExpression>,
+    /// UNION などの集合演算（指定されている場合、FROM句やSELECT句は使わずに
+    /// 両側のクエリの結果を組み合わせ、ORDER BY と LIMIT をその結果に適用する）
+    pub set_operation: Option<Box<SetOperation>>,
+}
+
+/// FROM句でテーブルの代わりに行を生成する関数
+#[derive(Debug, Clone)]
+pub enum TableFunction {
+    /// unnest(配列)（配列の要素ごとに、要素を column の値とする1行を生成する）
+    Unnest {
+        array: Expression,
+        column: String,
+    },
+    /// FROM句のないSELECT（カラムを持たない1行を生成する）
+    SingleRow,
+}
+
+/// SELECT DISTINCT の指定
+#[derive(Debug, Clone)]
+pub enum Distinct {
+    /// DISTINCT（結果の行全体が重複する行を取り除く）
+    Rows,
+    /// DISTINCT ON (...)（式の値の組ごとに、ORDER BY で最初になる行だけを残す）
+    On(Vec<Expression>),
+}
+
+/// 集合演算の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperator {
+    Union,
+    Intersect,
+    Except,
+}
+
+impl fmt::Display for SetOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetOperator::Union => write!(f, "UNION"),
+            SetOperator::Intersect => write!(f, "INTERSECT"),
+            SetOperator::Except => write!(f, "EXCEPT"),
+        }
+    }
+}
+
+/// UNION / INTERSECT / EXCEPT [ALL]
+#[derive(Debug, Clone)]
+pub struct SetOperation {
+    pub op: SetOperator,
+    /// ALL の場合は重複を取り除かない
+    pub all: bool,
+    pub left: SelectStatement,
+    pub right: SelectStatement,
+}
+
+/// WITH句で定義された共通テーブル式
+#[derive(Debug, Clone)]
+pub struct CommonTableExpression {
+    pub name: String,
+    /// 結果カラムの別名（指定されていない場合は空）
+    pub columns: Vec<String>,
+    /// 定義するクエリ（再帰的な場合は非再帰部分）
+    pub query: SelectStatement,
+    /// WITH RECURSIVE の再帰部分
+    pub recursive: Option<RecursiveTerm>,
+}
+
+/// WITH RECURSIVE の UNION [ALL] の右側
+#[derive(Debug, Clone)]
+pub struct RecursiveTerm {
+    pub query: SelectStatement,
+    /// UNION ALL の場合は重複を取り除かない
+    pub union_all: bool,
+}
+
+/// SELECT句の項目
+#[derive(Debug, Clone)]
+pub enum SelectItem {
+    /// '*'（すべてのカラム）
+    Wildcard,
+    /// 式と任意の別名
+    Expression {
+        expr: Expression,
+        alias: Option<String>,
+    },
+}
+
+impl SelectItem {
+    /// 結果カラムの名前（別名がなければ式の文字列表現）
+    pub fn output_name(&self) -> Option<String> {
+        match self {
+            SelectItem::Wildcard => None,
+            SelectItem::Expression { alias: Some(alias), .. } => Some(alias.clone()),
+            // 修飾されたカラム参照はカラム名のみを出力名とする
+            SelectItem::Expression { expr: Expression::Column(name), .. } => {
+                Some(name.rsplit_once('.').map_or(name.as_str(), |(_, bare)| bare).to_string())
+            },
+            SelectItem::Expression { expr, .. } => Some(expr.to_string()),
+        }
+    }
+}
+
+impl SelectStatement {
+    /// 射影が単純なカラム参照だけで構成される場合、そのカラム名を返す
+    /// （'*' を含む場合や式を含む場合は None）
+    pub fn column_names(&self) -> Option<Vec<String>> {
+        self.projection.iter().map(|item| match item {
+            SelectItem::Expression { expr: Expression::Column(name), .. } => Some(name.clone()),
+            _ => None,
+        }).collect()
+    }
+
+    /// WHERE句またはSELECT句にサブクエリを含むかどうか
+    pub fn contains_subquery(&self) -> bool {
+        self.filter.as_ref().is_some_and(|f| f.contains_subquery()) || self.projection.iter().any(|item| {
+            matches!(item, SelectItem::Expression { expr, .. } if expr.contains_subquery())
+        })
+    }
+
+    /// SELECT句またはORDER BYにウィンドウ関数を含むかどうか
+    pub fn contains_window(&self) -> bool {
+        self.order_by.iter().any(|item| item.expr.contains_window()) || self.projection.iter().any(|item| {
+            matches!(item, SelectItem::Expression { expr, .. } if expr.contains_window())
+        })
+    }
+
+    /// 集約を伴うクエリかどうか
+    pub fn is_aggregate(&self) -> bool {
+        !self.group_by.is_empty() || self.projection.iter().any(|item| {
+            matches!(item, SelectItem::Expression { expr, .. } if expr.contains_aggregate())
+        })
+    }
 }
 
 /// INSERT文からの解析結果
 pub struct InsertStatement {
     pub table_name: String,
     pub columns: Vec<String>,
-    pub values: Vec<Vec<Value>>,
+    pub source: InsertSource,
+    /// ON CONFLICT / ON DUPLICATE KEY UPDATE 句
+    pub on_conflict: Option<OnConflict>,
+    /// RETURNING句（指定されていない場合はNone）
+    pub returning: Option<Vec<SelectItem>>,
+}
+
+/// INSERTする行の供給元
+pub enum InsertSource {
+    /// VALUES句で指定された行（定数は解析時に値へ畳み込み、関数呼び出しは実行時に評価する）
+    Values(Vec<Vec<Expression>>),
+    /// SELECT文の結果
+    Select(Box<SelectStatement>),
 }
 
 /// UPDATE文からの解析結果
 pub struct UpdateStatement {
     pub table_name: String,
-    pub updates: Vec<(String, Value)>,
+    pub table_alias: Option<String>,
+    /// SET句の代入（式は行ごとに更新前の値で評価する）
+    pub updates: Vec<(String, Expression)>,
     pub filter: Option<FilterCondition>,
+    /// RETURNING句（指定されていない場合はNone）
+    pub returning: Option<Vec<SelectItem>>,
 }
 
 /// DELETE文からの解析結果
 pub struct DeleteStatement {
     pub table_name: String,
+    pub table_alias: Option<String>,
     pub filter: Option<FilterCondition>,
+    /// RETURNING句（指定されていない場合はNone）
+    pub returning: Option<Vec<SelectItem>>,
 }
 
 /// DROP TABLE文からの解析結果
@@ -78,6 +259,48 @@ pub struct DropTableStatement {
     pub if_exists: bool,
 }
 
+/// CREATE [MATERIALIZED] VIEW文からの解析結果
+pub struct CreateViewStatement {
+    pub view_name: String,
+    pub columns: Vec<String>,
+    pub query: String,
+    pub or_replace: bool,
+    pub materialized: bool,
+    /// WITH (incremental = true) が指定された場合
+    pub incremental: bool,
+}
+
+/// DROP [MATERIALIZED] VIEW文からの解析結果
+pub struct DropViewStatement {
+    pub view_name: String,
+    pub if_exists: bool,
+    pub materialized: bool,
+}
+
+/// REFRESH MATERIALIZED VIEW文からの解析結果
+pub struct RefreshMaterializedViewStatement {
+    pub view_name: String,
+}
+
+/// SET TIME ZONE文からの解析結果
+pub struct SetTimeZoneStatement {
+    pub time_zone: FixedOffset,
+}
+
+/// CREATE TYPE ... AS ENUM文からの解析結果
+pub struct CreateTypeStatement {
+    pub enum_type: EnumType,
+}
+
+/// ALTER TYPE ... ADD VALUE文からの解析結果
+pub struct AlterTypeStatement {
+    pub type_name: String,
+    pub label: String,
+    /// BEFORE / AFTER で指定された位置（省略時は最後に追加する）
+    pub position: Option<LabelPosition>,
+    pub if_not_exists: bool,
+}
+
 /// 解析されたSQL文
 pub enum ParsedStatement {
     CreateTable(CreateTableStatement),
@@ -86,6 +309,13 @@ pub enum ParsedStatement {
     Update(UpdateStatement),
     Delete(DeleteStatement),
     DropTable(DropTableStatement),
+    CreateView(CreateViewStatement),
+    DropView(DropViewStatement),
+    RefreshMaterializedView(RefreshMaterializedViewStatement),
+    SetTimeZone(SetTimeZoneStatement),
+    ShowTimeZone,
+    CreateType(CreateTypeStatement),
+    AlterType(AlterTypeStatement),
 }
 
 impl SqlParser {
@@ -98,77 +328,257 @@ impl SqlParser {
     
     /// SQL文を解析する
     pub fn parse(&self, sql: &str) -> Result<Vec<ParsedStatement>, ParseError> {
-        let statements = Parser::parse_sql(&self.dialect, sql)?;
+        let tokens = Tokenizer::new(&self.dialect, sql).tokenize().map_err(ParserError::from)?;
+        let mut parser = Parser::new(&self.dialect).with_tokens(merge_json_operators(tokens));
         
+        // Parser::parse_statements と同じ手順で文を読み進めるが、
+        // sqlparserが対応していない独自の文はここで解析する
         let mut parsed_statements = Vec::new();
-        for stmt in statements {
-            let parsed = self.parse_statement(stmt)?;
+        let mut expecting_statement_delimiter = false;
+        loop {
+            while parser.consume_token(&Token::SemiColon) {
+                expecting_statement_delimiter = false;
+            }
+            
+            if parser.peek_token().token == Token::EOF {
+                break;
+            }
+            
+            if expecting_statement_delimiter {
+                return Err(ParseError::SyntaxError(
+                    format!("Expected end of statement, found: {}", parser.peek_token())));
+            }
+            
+            let parsed = match self.parse_extension_statement(&mut parser)? {
+                Some(parsed) => parsed,
+                None => self.parse_statement(parser.parse_statement()?)?,
+            };
             parsed_statements.push(parsed);
+            expecting_statement_delimiter = true;
         }
         
         Ok(parsed_statements)
     }
     
+    /// sqlparserが対応していない文を解析する（該当しなければNoneを返す）
+    fn parse_extension_statement(&self, parser: &mut Parser) -> Result<Option<ParsedStatement>, ParseError> {
+        let is_word = |token: Token, word: &str| {
+            matches!(token, Token::Word(w) if w.value.eq_ignore_ascii_case(word))
+        };
+        
+        // REFRESH MATERIALIZED VIEW name
+        if is_word(parser.peek_token().token, "REFRESH") {
+            parser.next_token();
+            parser.expect_keywords(&[Keyword::MATERIALIZED, Keyword::VIEW])?;
+            let name = parser.parse_object_name()?;
+            return Ok(Some(ParsedStatement::RefreshMaterializedView(RefreshMaterializedViewStatement {
+                view_name: self.object_name_to_string(&name)?,
+            })));
+        }
+        
+        // DROP MATERIALIZED VIEW [IF EXISTS] name
+        if is_word(parser.peek_token().token, "DROP")
+            && is_word(parser.peek_nth_token(1).token, "MATERIALIZED") {
+            parser.next_token();
+            parser.expect_keywords(&[Keyword::MATERIALIZED, Keyword::VIEW])?;
+            let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+            let name = parser.parse_object_name()?;
+            return Ok(Some(ParsedStatement::DropView(DropViewStatement {
+                view_name: self.object_name_to_string(&name)?,
+                if_exists,
+                materialized: true,
+            })));
+        }
+        
+        // CREATE TYPE name AS ENUM ('label', ...)
+        if is_word(parser.peek_token().token, "CREATE") && is_word(parser.peek_nth_token(1).token, "TYPE") {
+            parser.next_token();
+            parser.next_token();
+            let name = parser.parse_object_name()?;
+            parser.expect_keyword(Keyword::AS)?;
+            if !parser.parse_keyword(Keyword::ENUM) {
+                return Err(ParseError::UnsupportedFeature(
+                    format!("Only ENUM types are supported, found: {}", parser.peek_token())));
+            }
+            parser.expect_token(&Token::LParen)?;
+            let labels = if parser.consume_token(&Token::RParen) {
+                Vec::new()
+            } else {
+                let labels = parser.parse_comma_separated(Parser::parse_literal_string)?;
+                parser.expect_token(&Token::RParen)?;
+                labels
+            };
+            return Ok(Some(ParsedStatement::CreateType(CreateTypeStatement {
+                enum_type: EnumType::new(self.object_name_to_string(&name)?, labels),
+            })));
+        }
+        
+        // ALTER TYPE name ADD VALUE [IF NOT EXISTS] 'label' [{BEFORE | AFTER} 'label']
+        if is_word(parser.peek_token().token, "ALTER") && is_word(parser.peek_nth_token(1).token, "TYPE") {
+            parser.next_token();
+            parser.next_token();
+            let name = parser.parse_object_name()?;
+            parser.expect_keywords(&[Keyword::ADD, Keyword::VALUE])?;
+            let if_not_exists = parser.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+            let label = parser.parse_literal_string()?;
+            let position = if is_word(parser.peek_token().token, "BEFORE") {
+                parser.next_token();
+                Some(LabelPosition::Before(parser.parse_literal_string()?))
+            } else if is_word(parser.peek_token().token, "AFTER") {
+                parser.next_token();
+                Some(LabelPosition::After(parser.parse_literal_string()?))
+            } else {
+                None
+            };
+            return Ok(Some(ParsedStatement::AlterType(AlterTypeStatement {
+                type_name: self.object_name_to_string(&name)?,
+                label,
+                position,
+                if_not_exists,
+            })));
+        }
+        
+        Ok(None)
+    }
+    
     /// 単一のSQL文を解析する
     fn parse_statement(&self, stmt: Statement) -> Result<ParsedStatement, ParseError> {
         match stmt {
-            Statement::CreateTable { name, columns, if_not_exists, .. } => {
-                self.parse_create_table(name, columns, if_not_exists)
+            Statement::CreateTable { name, columns, if_not_exists, query, .. } => {
+                self.parse_create_table(name, columns, if_not_exists, query.map(|q| *q))
             },
             Statement::Query(query) => {
                 self.parse_select(*query)
             },
-            Statement::Insert { table_name, columns, source, .. } => {
-                // source が Query 型の場合の対応
-                if let SetExpr::Values(values) = source.body.as_ref() {
-                    self.parse_insert(table_name, columns, values.clone())
-                } else {
-                    Err(ParseError::UnsupportedFeature("Only VALUES in INSERT are supported".to_string()))
-                }
+            Statement::Insert { table_name, columns, source, on, returning, .. } => {
+                // source が VALUES か SELECT かで処理を分ける
+                let source = match source.body.as_ref() {
+                    SetExpr::Values(values) => self.parse_insert_values(values.clone())?,
+                    _ => InsertSource::Select(Box::new(self.parse_query(*source)?)),
+                };
+                let on_conflict = on.map(|on| self.parse_on_insert(on)).transpose()?;
+                let returning = self.parse_returning(returning)?;
+                self.parse_insert(table_name, columns, source, on_conflict, returning)
             },
-            Statement::Update { table, assignments, selection, .. } => {
-                self.parse_update(table, assignments, selection)
+            Statement::Update { table, assignments, selection, returning, .. } => {
+                let returning = self.parse_returning(returning)?;
+                self.parse_update(table, assignments, selection, returning)
             },
-            Statement::Delete { from, selection, .. } => {
+            Statement::Delete { from, selection, returning, .. } => {
                 if from.len() != 1 {
                     return Err(ParseError::UnsupportedFeature("Multiple table delete not supported".to_string()));
                 }
                 let table_name = self.get_table_name(&from[0])?;
-                self.parse_delete(table_name, selection)
+                let table_alias = self.get_table_alias(&from[0]);
+                let returning = self.parse_returning(returning)?;
+                self.parse_delete(table_name, table_alias, selection, returning)
+            },
+            Statement::CreateView { or_replace, materialized, name, columns, query, with_options, .. } => {
+                self.parse_create_view(name, columns, *query, or_replace, materialized, with_options)
             },
             Statement::Drop { object_type, names, if_exists, .. } => {
                 // object_type が &str ではなく enum なのでマッチング方法を変更
-                if object_type != sqlparser::ast::ObjectType::Table {
-                    return Err(ParseError::UnsupportedFeature("Only DROP TABLE is supported".to_string()));
+                match object_type {
+                    sqlparser::ast::ObjectType::Table => {
+                        if names.len() != 1 {
+                            return Err(ParseError::UnsupportedFeature("Multiple table drop not supported".to_string()));
+                        }
+                        Ok(ParsedStatement::DropTable(DropTableStatement {
+                            table_name: self.object_name_to_string(&names[0])?,
+                            if_exists,
+                        }))
+                    },
+                    sqlparser::ast::ObjectType::View => {
+                        if names.len() != 1 {
+                            return Err(ParseError::UnsupportedFeature("Multiple view drop not supported".to_string()));
+                        }
+                        Ok(ParsedStatement::DropView(DropViewStatement {
+                            view_name: self.object_name_to_string(&names[0])?,
+                            if_exists,
+                            materialized: false,
+                        }))
+                    },
+                    _ => Err(ParseError::UnsupportedFeature("Only DROP TABLE and DROP VIEW are supported".to_string())),
                 }
-                
-                if names.len() != 1 {
-                    return Err(ParseError::UnsupportedFeature("Multiple table drop not supported".to_string()));
+            },
+            Statement::SetTimeZone { value, .. } => self.parse_set_time_zone(&value),
+            Statement::SetVariable { variable, value, .. }
+                if variable.to_string().eq_ignore_ascii_case("TIMEZONE") && value.len() == 1 => {
+                self.parse_set_time_zone(&value[0])
+            },
+            Statement::ShowVariable { variable } => {
+                let name = variable.iter().map(|ident| ident.value.to_uppercase()).collect::<Vec<_>>().join(" ");
+                match name.as_str() {
+                    "TIME ZONE" | "TIMEZONE" => Ok(ParsedStatement::ShowTimeZone),
+                    _ => Err(ParseError::UnsupportedFeature(format!("Unsupported SHOW variable: {}", name))),
                 }
-                Ok(ParsedStatement::DropTable(DropTableStatement {
-                    table_name: self.object_name_to_string(&names[0])?,
-                    if_exists,
-                }))
             },
             _ => Err(ParseError::UnsupportedFeature("Unsupported SQL statement type".to_string()))
         }
     }
+
+    /// SET TIME ZONE の値を解析する（LOCAL と DEFAULT はUTCとする）
+    fn parse_set_time_zone(&self, value: &Expr) -> Result<ParsedStatement, ParseError> {
+        let text = match value {
+            Expr::Value(SqlValue::SingleQuotedString(s)) | Expr::Value(SqlValue::Number(s, _)) => s.clone(),
+            Expr::Identifier(ident) if ["LOCAL", "DEFAULT"].iter().any(|w| ident.value.eq_ignore_ascii_case(w)) => {
+                "UTC".to_string()
+            },
+            Expr::UnaryOp { op: UnaryOperator::Minus, expr } => match expr.as_ref() {
+                Expr::Value(SqlValue::Number(s, _)) => format!("-{}", s),
+                _ => return Err(ParseError::InvalidValue(format!("Invalid time zone: {}", value))),
+            },
+            _ => return Err(ParseError::InvalidValue(format!("Invalid time zone: {}", value))),
+        };
+        // 'Asia/Tokyo' のような地域名には対応せず、UTCからの時差だけを受け付ける
+        let time_zone = parse_time_zone(&text).ok_or_else(|| ParseError::InvalidValue(
+            if text.chars().any(char::is_alphabetic) {
+                format!("Time zone names are not supported: {} (use a UTC offset such as '+09:00')", text)
+            } else {
+                format!("Invalid time zone: {}", text)
+            }
+        ))?;
+        Ok(ParsedStatement::SetTimeZone(SetTimeZoneStatement { time_zone }))
+    }
     
     /// CREATE TABLE文を解析する
     fn parse_create_table(
         &self, 
         name: ObjectName, 
         columns: Vec<sqlparser::ast::ColumnDef>,
-        if_not_exists: bool
+        if_not_exists: bool,
+        query: Option<Query>
     ) -> Result<ParsedStatement, ParseError> {
         let table_name = self.object_name_to_string(&name)?;
         
+        // CREATE TABLE ... AS SELECT の場合、カラム定義は結果から推論する
+        let query = match query {
+            Some(query) => {
+                if !columns.is_empty() {
+                    return Err(ParseError::UnsupportedFeature(
+                        "Column definitions are not supported with CREATE TABLE AS SELECT".to_string()));
+                }
+                Some(self.parse_query(query)?)
+            },
+            None => None,
+        };
+        
         let mut parsed_columns = Vec::new();
         for col in columns {
             let column_name = col.name.value.clone();
-            let data_type = self.parse_data_type(&col.data_type)?;
+            let data_type = self.parse_column_type(&col.data_type)?;
             
             let mut column = Column::new(column_name, data_type);
+            if let Some(type_limit) = self.parse_type_limit(&col.data_type)? {
+                column = column.with_type_limit(type_limit);
+            }
+            if let Some(name) = &col.collation {
+                if column.data_type != DataType::Text {
+                    return Err(ParseError::InvalidDataType(format!(
+                        "Collations are not supported by type {}", column.data_type)));
+                }
+                column = column.with_collation(self.parse_collation(name)?);
+            }
             
             // 制約の解析
             for constraint in &col.options {
@@ -205,35 +615,129 @@ impl SqlParser {
             table_name,
             columns: parsed_columns,
             if_not_exists,
+            query,
+        }))
+    }
+    
+    /// CREATE [MATERIALIZED] VIEW文を解析する
+    fn parse_create_view(
+        &self,
+        name: ObjectName,
+        columns: Vec<Ident>,
+        query: Query,
+        or_replace: bool,
+        materialized: bool,
+        with_options: Vec<SqlOption>
+    ) -> Result<ParsedStatement, ParseError> {
+        let view_name = self.object_name_to_string(&name)?;
+        
+        // WITH句のオプション（現在は incremental のみ）
+        let mut incremental = false;
+        for option in with_options {
+            match (option.name.value.to_lowercase().as_str(), &option.value) {
+                ("incremental", SqlValue::Boolean(b)) if materialized => incremental = *b,
+                _ => return Err(ParseError::UnsupportedFeature(
+                    format!("Unsupported view option: {}", option))),
+            }
+        }
+        
+        // 定義時点でクエリがサポート範囲内かを検証しておく
+        // （実行時にはSQLテキストから再解析して展開する）
+        let sql = query.to_string();
+        self.parse_select(query)?;
+        
+        Ok(ParsedStatement::CreateView(CreateViewStatement {
+            view_name,
+            columns: columns.into_iter().map(|ident| ident.value).collect(),
+            query: sql,
+            or_replace,
+            materialized,
+            incremental,
         }))
     }
     
     /// SELECT文を解析する
     fn parse_select(&self, query: Query) -> Result<ParsedStatement, ParseError> {
+        Ok(ParsedStatement::Select(self.parse_query(query)?))
+    }
+    
+    /// クエリ（SELECT）を解析する
+    fn parse_query(&self, query: Query) -> Result<SelectStatement, ParseError> {
+        let with = match query.with {
+            Some(with) => self.parse_with(with)?,
+            None => Vec::new(),
+        };
+        
+        let order_by = self.parse_order_by(&query.order_by)?;
+        
+        // LIMIT句の解析
+        let limit = query.limit.and_then(|expr| {
+            if let Expr::Value(SqlValue::Number(n, _)) = expr {
+                n.parse::<usize>().ok()
+            } else {
+                None
+            }
+        });
+        
+        if let SetExpr::Query(inner) = *query.body {
+            let mut stmt = self.parse_query(*inner)?;
+            stmt.with.splice(0..0, with);
+            if !order_by.is_empty() {
+                stmt.order_by = order_by;
+            }
+            if limit.is_some() {
+                stmt.limit = limit;
+            }
+            return Ok(stmt);
+        }
+        
+        // UNION / INTERSECT / EXCEPT は両側を別々のクエリとして解析する
+        if let SetExpr::SetOperation { op, set_quantifier, left, right } = *query.body {
+            let op = match op {
+                SqlSetOperator::Union => SetOperator::Union,
+                SqlSetOperator::Intersect => SetOperator::Intersect,
+                SqlSetOperator::Except => SetOperator::Except,
+            };
+            return Ok(SelectStatement {
+                table_name: String::new(),
+                table_alias: None,
+                table_function: None,
+                distinct: None,
+                projection: Vec::new(),
+                filter: None,
+                group_by: Vec::new(),
+                order_by,
+                limit,
+                with,
+                set_operation: Some(Box::new(SetOperation {
+                    op,
+                    all: set_quantifier == SetQuantifier::All,
+                    left: self.parse_set_expr(*left)?,
+                    right: self.parse_set_expr(*right)?,
+                })),
+            });
+        }
+        
         if let SetExpr::Select(select) = *query.body {
-            if select.from.len() != 1 {
-                return Err(ParseError::UnsupportedFeature("Joins are not supported yet".to_string()));
+            if select.from.len() > 1 {
+                return Err(ParseError::UnsupportedFeature(
+                    "Selecting from more than one table (joins) is not supported yet".to_string()));
             }
             
-            let table_name = self.get_table_name(&select.from[0])?;
+            let (table_name, table_alias, table_function) = match select.from.first().map(|from| &from.relation) {
+                None => (String::new(), None, Some(TableFunction::SingleRow)),
+                Some(TableFactor::UNNEST { alias, array_expr, with_offset, .. }) => {
+                    let (name, function) = self.parse_unnest(alias.as_ref(), array_expr, *with_offset)?;
+                    (name, alias.as_ref().map(|alias| alias.name.value.clone()), Some(function))
+                },
+                _ => (self.get_table_name(&select.from[0])?, self.get_table_alias(&select.from[0]), None),
+            };
             
             // カラムリストの解析
-            let columns = if select.projection.iter().any(|item| matches!(item, SelectItem::Wildcard(_))) {
-                None // '*' を使用した場合は全カラムを意味する
-            } else {
-                let mut col_names = Vec::new();
-                for item in &select.projection {
-                    if let SelectItem::UnnamedExpr(Expr::Identifier(ident)) = item {
-                        col_names.push(ident.value.clone());
-                    } else if let SelectItem::ExprWithAlias { expr: Expr::Identifier(ident), .. } = item {
-                        col_names.push(ident.value.clone());
-                    } else {
-                        return Err(ParseError::UnsupportedFeature(
-                            "Complex SELECT expressions not supported".to_string()));
-                    }
-                }
-                Some(col_names)
-            };
+            let projection = self.parse_select_items(&select.projection)?;
+            if select.from.is_empty() && projection.iter().any(|item| matches!(item, SelectItem::Wildcard)) {
+                return Err(ParseError::SyntaxError("SELECT * requires a FROM clause".to_string()));
+            }
             
             // WHERE句の解析
             let filter = match select.selection {
@@ -241,24 +745,569 @@ impl SqlParser {
                 None => None,
             };
             
-            // LIMIT句の解析
-            let limit = query.limit.and_then(|expr| {
-                if let Expr::Value(SqlValue::Number(n, _)) = expr {
-                    n.parse::<usize>().ok()
+            // GROUP BY句の解析（カラム名のみサポート）
+            let mut group_by = Vec::new();
+            for expr in &select.group_by {
+                if let Expr::Identifier(ident) = expr {
+                    group_by.push(ident.value.clone());
                 } else {
-                    None
+                    return Err(ParseError::UnsupportedFeature(
+                        "Only column names are supported in GROUP BY".to_string()));
                 }
-            });
+            }
+            
+            if select.having.is_some() {
+                return Err(ParseError::UnsupportedFeature("HAVING is not supported".to_string()));
+            }
+            
+            let distinct = match &select.distinct {
+                None => None,
+                Some(sqlparser::ast::Distinct::Distinct) => Some(Distinct::Rows),
+                Some(sqlparser::ast::Distinct::On(exprs)) => Some(Distinct::On(
+                    exprs.iter().map(|expr| self.parse_expression(expr)).collect::<Result<_, _>>()?)),
+            };
             
-            Ok(ParsedStatement::Select(SelectStatement {
+            Ok(SelectStatement {
                 table_name,
-                columns,
+                table_alias,
+                table_function,
+                distinct,
+                projection,
                 filter,
+                group_by,
+                order_by,
                 limit,
-            }))
+                with,
+                set_operation: None,
+            })
         } else {
-            Err(ParseError::UnsupportedFeature("Only simple SELECT queries are supported".to_string()))
+            Err(ParseError::UnsupportedFeature("Only SELECT queries are supported".to_string()))
+        }
+    }
+    
+    /// WITH句を解析する
+    fn parse_with(&self, with: With) -> Result<Vec<CommonTableExpression>, ParseError> {
+        let mut ctes = Vec::new();
+        for cte in with.cte_tables {
+            let name = cte.alias.name.value;
+            let columns = cte.alias.columns.into_iter().map(|ident| ident.value).collect();
+            let query = *cte.query;
+            
+            // 再帰的なCTEは「非再帰部分 UNION [ALL] 再帰部分」の形に分ける
+            let recursive_union = with.recursive && query.with.is_none() && matches!(
+                query.body.as_ref(),
+                SetExpr::SetOperation { op: SqlSetOperator::Union, .. }
+            );
+            if !recursive_union {
+                ctes.push(CommonTableExpression { name, columns, query: self.parse_query(query)?, recursive: None });
+                continue;
+            }
+            
+            let SetExpr::SetOperation { set_quantifier, left, right, .. } = *query.body else {
+                unreachable!("checked above");
+            };
+            ctes.push(CommonTableExpression {
+                name,
+                columns,
+                query: self.parse_set_expr(*left)?,
+                recursive: Some(RecursiveTerm {
+                    query: self.parse_set_expr(*right)?,
+                    union_all: set_quantifier == SetQuantifier::All,
+                }),
+            });
+        }
+        Ok(ctes)
+    }
+    
+    /// UNION などの片側を単独のクエリとして解析する
+    fn parse_set_expr(&self, body: SetExpr) -> Result<SelectStatement, ParseError> {
+        self.parse_query(Query {
+            with: None,
+            body: Box::new(body),
+            order_by: Vec::new(),
+            limit: None,
+            offset: None,
+            fetch: None,
+            locks: Vec::new(),
+        })
+    }
+    
+    /// SELECT句やRETURNING句の項目を解析する
+    fn parse_select_items(&self, items: &[SqlSelectItem]) -> Result<Vec<SelectItem>, ParseError> {
+        let mut projection = Vec::new();
+        for item in items {
+            match item {
+                SqlSelectItem::Wildcard(_) => projection.push(SelectItem::Wildcard),
+                SqlSelectItem::UnnamedExpr(expr) => projection.push(SelectItem::Expression {
+                    expr: self.parse_expression(expr)?,
+                    alias: None,
+                }),
+                SqlSelectItem::ExprWithAlias { expr, alias } => projection.push(SelectItem::Expression {
+                    expr: self.parse_expression(expr)?,
+                    alias: Some(alias.value.clone()),
+                }),
+                _ => return Err(ParseError::UnsupportedFeature(
+                    "Complex SELECT expressions not supported".to_string())),
+            }
+        }
+        Ok(projection)
+    }
+    
+    /// RETURNING句を解析する（集約関数は使用できない）
+    fn parse_returning(&self, returning: Option<Vec<SqlSelectItem>>) -> Result<Option<Vec<SelectItem>>, ParseError> {
+        let Some(items) = returning else {
+            return Ok(None);
+        };
+        
+        let items = self.parse_select_items(&items)?;
+        if items.iter().any(|item| matches!(item, SelectItem::Expression { expr, .. } if expr.contains_aggregate())) {
+            return Err(ParseError::UnsupportedFeature(
+                "Aggregate functions are not allowed in RETURNING".to_string()));
+        }
+        
+        Ok(Some(items))
+    }
+    
+    /// SELECT句の式を解析する
+    fn parse_expression(&self, expr: &Expr) -> Result<Expression, ParseError> {
+        match expr {
+            Expr::Identifier(ident) => Ok(Expression::Column(ident.value.clone())),
+            Expr::CompoundIdentifier(idents) => match idents.as_slice() {
+                [qualifier, column] => {
+                    // EXCLUDED は大文字小文字を区別せずに扱う
+                    let qualifier = if qualifier.value.eq_ignore_ascii_case(EXCLUDED) {
+                        EXCLUDED.to_string()
+                    } else {
+                        qualifier.value.clone()
+                    };
+                    Ok(Expression::Column(format!("{}.{}", qualifier, column.value)))
+                },
+                _ => Err(ParseError::UnsupportedFeature(
+                    format!("Unsupported column reference: {}", expr))),
+            },
+            Expr::Value(value) => Ok(Expression::Literal(self.sql_value_to_value(value)?)),
+            Expr::Function(function) => self.parse_function(function),
+            Expr::Nested(inner) => self.parse_expression(inner),
+            Expr::BinaryOp { .. } | Expr::Interval(_) if contains_interval_operation(expr) => {
+                self.parse_operator_chain(expr)
+            },
+            Expr::BinaryOp { left, op, right } => match &**right {
+                Expr::AnyOp(array) | Expr::AllOp(array) => {
+                    self.parse_array_comparison(left, op, array, matches!(**right, Expr::AllOp(_)))
+                },
+                _ => Ok(Expression::BinaryOp {
+                    left: Box::new(self.parse_expression(left)?),
+                    op: binary_operator(op)?,
+                    right: Box::new(self.parse_expression(right)?),
+                }),
+            },
+            Expr::JsonAccess { left, operator, right } if binds_json_operand(right) => {
+                self.parse_expression(&rebind_json_access(left, operator, right))
+            },
+            Expr::JsonAccess { left, operator, right } => Ok(Expression::BinaryOp {
+                left: Box::new(self.parse_expression(left)?),
+                op: json_operator(operator)?,
+                right: Box::new(self.parse_expression(right)?),
+            }),
+            Expr::UnaryOp { op: UnaryOperator::Not, expr } => {
+                Ok(Expression::Not(Box::new(self.parse_expression(expr)?)))
+            },
+            Expr::UnaryOp { op: UnaryOperator::Plus, expr } => self.parse_expression(expr),
+            Expr::UnaryOp { op: UnaryOperator::Minus, expr } => match self.parse_expression(expr)? {
+                // 負の数値リテラルは1つの値として扱う
+                Expression::Literal(Value::Integer(i)) => Ok(Expression::Literal(Value::Integer(-i))),
+                Expression::Literal(Value::Float(f)) => Ok(Expression::Literal(Value::Float(-f))),
+                inner => Ok(Expression::Negate(Box::new(inner))),
+            },
+            Expr::Case { operand, conditions, results, else_result } => Ok(Expression::Case {
+                operand: operand.as_ref().map(|operand| self.parse_expression(operand).map(Box::new)).transpose()?,
+                branches: conditions.iter().zip(results)
+                    .map(|(condition, result)| Ok((self.parse_expression(condition)?, self.parse_expression(result)?)))
+                    .collect::<Result<_, ParseError>>()?,
+                else_result: else_result.as_ref().map(|e| self.parse_expression(e).map(Box::new)).transpose()?,
+            }),
+            Expr::Interval(interval) => self.parse_interval(interval),
+            Expr::Cast { expr, data_type } => Ok(Expression::Cast {
+                expr: Box::new(self.parse_expression(expr)?),
+                data_type: self.parse_data_type(data_type)?,
+                time_zone: Utc.fix(),
+            }),
+            // TIMESTAMP '...' のような型付きリテラルは解析時に値を求める
+            // （タイムゾーン付きの日時はセッションのタイムゾーンによるので実行時に変換する）
+            Expr::TypedString { data_type, value } => match self.parse_data_type(data_type)? {
+                DataType::TimestampTz => Ok(Expression::Cast {
+                    expr: Box::new(Expression::Literal(Value::Text(value.clone()))),
+                    data_type: DataType::TimestampTz,
+                    time_zone: Utc.fix(),
+                }),
+                data_type => Value::Text(value.clone())
+                    .cast_to(&data_type)
+                    .map(Expression::Literal)
+                    .map_err(|e| ParseError::InvalidValue(e.to_string())),
+            },
+            // 特別な構文を持つ関数は通常の関数呼び出しとして扱う
+            Expr::Substring { expr, substring_from, substring_for } => {
+                let from = match substring_from {
+                    Some(from) => self.parse_expression(from)?,
+                    None => Expression::Literal(Value::Integer(1)),
+                };
+                let mut args = vec![self.parse_expression(expr)?, from];
+                if let Some(length) = substring_for {
+                    args.push(self.parse_expression(length)?);
+                }
+                Ok(scalar_function("SUBSTRING", args))
+            },
+            Expr::Trim { expr, trim_where, trim_what } => {
+                let name = match trim_where {
+                    Some(TrimWhereField::Leading) => "LTRIM",
+                    Some(TrimWhereField::Trailing) => "RTRIM",
+                    Some(TrimWhereField::Both) | None => "TRIM",
+                };
+                let mut args = vec![self.parse_expression(expr)?];
+                if let Some(characters) = trim_what {
+                    args.push(self.parse_expression(characters)?);
+                }
+                Ok(scalar_function(name, args))
+            },
+            Expr::Extract { field, expr } => Ok(scalar_function("EXTRACT", vec![
+                Expression::Literal(Value::Text(field.to_string())),
+                self.parse_expression(expr)?,
+            ])),
+            Expr::Ceil { expr, field: DateTimeField::NoDateTime } => {
+                Ok(scalar_function("CEIL", vec![self.parse_expression(expr)?]))
+            },
+            Expr::Floor { expr, field: DateTimeField::NoDateTime } => {
+                Ok(scalar_function("FLOOR", vec![self.parse_expression(expr)?]))
+            },
+            Expr::IsNull(inner) | Expr::IsNotNull(inner) => Ok(Expression::IsNull {
+                expr: Box::new(self.parse_expression(inner)?),
+                negated: matches!(expr, Expr::IsNotNull(_)),
+            }),
+            Expr::InList { expr, list, negated } => Ok(Expression::InList {
+                expr: Box::new(self.parse_expression(expr)?),
+                list: list.iter().map(|item| self.parse_expression(item)).collect::<Result<_, _>>()?,
+                negated: *negated,
+            }),
+            Expr::Like { negated, expr: inner, pattern, escape_char } |
+            Expr::ILike { negated, expr: inner, pattern, escape_char } => Ok(Expression::Like {
+                expr: Box::new(self.parse_expression(inner)?),
+                pattern: Box::new(self.parse_expression(pattern)?),
+                negated: *negated,
+                case_insensitive: matches!(expr, Expr::ILike { .. }),
+                escape: *escape_char,
+            }),
+            Expr::Collate { expr, collation } => Ok(Expression::Collate {
+                expr: Box::new(self.parse_expression(expr)?),
+                collation: self.parse_collation(collation)?,
+                implicit: false,
+            }),
+            // サブクエリはビューと同様にSQLテキストとして保持し、実行時に解析する
+            Expr::Array(array) => Ok(Expression::Array(
+                array.elem.iter().map(|item| self.parse_expression(item)).collect::<Result<_, _>>()?)),
+            // a[1][2] は1つずつ取り出す
+            Expr::ArrayIndex { obj, indexes } => indexes.iter().try_fold(self.parse_expression(obj)?, |expr, index| {
+                Ok(Expression::Subscript {
+                    expr: Box::new(expr),
+                    index: Box::new(self.parse_expression(index)?),
+                })
+            }),
+            Expr::Subquery(query) => Ok(Expression::ScalarSubquery(query.to_string())),
+            Expr::InSubquery { expr, subquery, negated } => Ok(Expression::InSubquery {
+                expr: Box::new(self.parse_expression(expr)?),
+                query: subquery.to_string(),
+                negated: *negated,
+            }),
+            Expr::Exists { subquery, negated } => Ok(Expression::Exists {
+                query: subquery.to_string(),
+                negated: *negated,
+            }),
+            _ => Err(ParseError::UnsupportedFeature(
+                format!("Unsupported expression: {}", expr))),
+        }
+    }
+    
+    /// expr op ANY (配列) / expr op ALL (配列) を解析する
+    fn parse_array_comparison(
+        &self,
+        left: &Expr,
+        op: &sqlparser::ast::BinaryOperator,
+        array: &Expr,
+        all: bool
+    ) -> Result<Expression, ParseError> {
+        let quantifier = if all { "ALL" } else { "ANY" };
+        let op = binary_operator(op)?;
+        if !matches!(op, BinaryOperator::Eq | BinaryOperator::NotEq | BinaryOperator::Lt |
+            BinaryOperator::LtEq | BinaryOperator::Gt | BinaryOperator::GtEq) {
+            return Err(ParseError::UnsupportedFeature(
+                format!("{} requires a comparison operator, got {}", quantifier, op)));
         }
+        if matches!(array, Expr::Subquery(_)) {
+            return Err(ParseError::UnsupportedFeature(
+                format!("{} with a subquery is not supported", quantifier)));
+        }
+
+        Ok(Expression::ArrayComparison {
+            left: Box::new(self.parse_expression(left)?),
+            op,
+            right: Box::new(self.parse_expression(array)?),
+            all,
+        })
+    }
+
+    /// 関数呼び出しを解析する
+    fn parse_function(&self, function: &Function) -> Result<Expression, ParseError> {
+        let name = self.object_name_to_string(&function.name)?;
+        
+        // MySQL の VALUES(col) は挿入しようとした値を参照する
+        if name.eq_ignore_ascii_case("VALUES") {
+            return match function.args.as_slice() {
+                [FunctionArg::Unnamed(FunctionArgExpr::Expr(Expr::Identifier(ident)))] => {
+                    Ok(Expression::Column(format!("{}.{}", EXCLUDED, ident.value)))
+                },
+                _ => Err(ParseError::InvalidValue("VALUES() takes a column name".to_string())),
+            };
+        }
+        if let Some(over) = &function.over {
+            return self.parse_window_function(&name, function, over);
+        }
+        
+        let Some(aggregate) = AggregateFunction::from_name(&name) else {
+            // スカラー関数は実行時に関数レジストリから名前で解決する
+            if function.distinct || !function.order_by.is_empty() {
+                return Err(ParseError::UnsupportedFeature(
+                    format!("Unsupported function syntax: {}", function)));
+            }
+            let args = function.args.iter().map(|arg| match arg {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(arg)) => self.parse_expression(arg),
+                _ => Err(ParseError::UnsupportedFeature(
+                    format!("Unsupported function argument: {}", arg))),
+            }).collect::<Result<_, _>>()?;
+            return Ok(scalar_function(&name, args));
+        };
+        
+        if function.distinct || !function.order_by.is_empty() {
+            return Err(ParseError::UnsupportedFeature(
+                format!("Unsupported aggregate syntax: {}", function)));
+        }
+        
+        let argument = match function.args.as_slice() {
+            [FunctionArg::Unnamed(FunctionArgExpr::Wildcard)] if aggregate == AggregateFunction::Count => None,
+            [FunctionArg::Unnamed(FunctionArgExpr::Expr(arg))] => {
+                let arg = self.parse_expression(arg)?;
+                if arg.contains_aggregate() {
+                    return Err(ParseError::UnsupportedFeature(
+                        "Nested aggregate functions are not supported".to_string()));
+                }
+                if arg.contains_window() {
+                    return Err(ParseError::UnsupportedFeature(
+                        "Window functions cannot be used inside aggregate functions".to_string()));
+                }
+                Some(Box::new(arg))
+            },
+            _ => return Err(ParseError::InvalidValue(
+                format!("{} takes exactly one argument", aggregate))),
+        };
+        
+        Ok(Expression::Aggregate {
+            function: aggregate,
+            argument,
+        })
+    }
+    
+    /// OVER句を伴うウィンドウ関数の呼び出しを解析する
+    fn parse_window_function(&self, name: &str, function: &Function, over: &WindowType) -> Result<Expression, ParseError> {
+        let WindowType::WindowSpec(spec) = over else {
+            return Err(ParseError::UnsupportedFeature("Named windows are not supported".to_string()));
+        };
+        let window_function = WindowFunction::from_name(name)
+            .ok_or_else(|| ParseError::UnsupportedFeature(format!("Unknown window function: {}", name)))?;
+        if function.distinct || !function.order_by.is_empty() {
+            return Err(ParseError::UnsupportedFeature(
+                format!("Unsupported window function syntax: {}", function)));
+        }
+        
+        let mut args = Vec::new();
+        for arg in &function.args {
+            match arg {
+                FunctionArg::Unnamed(FunctionArgExpr::Wildcard)
+                    if window_function == WindowFunction::Aggregate(AggregateFunction::Count) && function.args.len() == 1 => {},
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(arg)) => {
+                    let arg = self.parse_expression(arg)?;
+                    if arg.contains_window() {
+                        return Err(ParseError::UnsupportedFeature(
+                            "Window functions cannot be nested".to_string()));
+                    }
+                    args.push(arg);
+                },
+                _ => return Err(ParseError::InvalidValue(
+                    format!("Unsupported argument to {}: {}", window_function, arg))),
+            }
+        }
+        
+        let arity = match window_function {
+            WindowFunction::RowNumber | WindowFunction::Rank | WindowFunction::DenseRank => 0..=0,
+            WindowFunction::Lag | WindowFunction::Lead => 1..=3,
+            WindowFunction::FirstValue | WindowFunction::LastValue => 1..=1,
+            WindowFunction::Aggregate(AggregateFunction::Count) => 0..=1,
+            WindowFunction::Aggregate(_) => 1..=1,
+        };
+        if !arity.contains(&args.len()) {
+            return Err(ParseError::InvalidValue(format!(
+                "Wrong number of arguments to {}: {}", window_function, args.len())));
+        }
+        
+        let partition_by = spec.partition_by.iter()
+            .map(|expr| self.parse_expression(expr))
+            .collect::<Result<Vec<_>, _>>()?;
+        let order_by = self.parse_order_by(&spec.order_by)?;
+        let frame = match &spec.window_frame {
+            Some(frame) => Some(self.parse_window_frame(frame)?),
+            None => None,
+        };
+        
+        Ok(Expression::Window {
+            function: window_function,
+            args,
+            spec: Box::new(WindowSpec { partition_by, order_by, frame }),
+        })
+    }
+    
+    /// ROWS / RANGE BETWEEN ... AND ... を解析する
+    fn parse_window_frame(&self, frame: &SqlWindowFrame) -> Result<WindowFrame, ParseError> {
+        let units = match frame.units {
+            WindowFrameUnits::Rows => FrameUnits::Rows,
+            WindowFrameUnits::Range => FrameUnits::Range,
+            WindowFrameUnits::Groups => return Err(ParseError::UnsupportedFeature(
+                "GROUPS window frames are not supported".to_string())),
+        };
+        
+        let parse_bound = |bound: &WindowFrameBound| -> Result<FrameBound, ParseError> {
+            let offset = |expr: &Expr| match expr {
+                Expr::Value(SqlValue::Number(n, _)) => n.parse::<usize>().map_err(|_| ParseError::InvalidValue(
+                    format!("Window frame offset must be a non-negative integer: {}", n))),
+                _ => Err(ParseError::UnsupportedFeature(
+                    format!("Window frame offset must be a non-negative integer: {}", expr))),
+            };
+            let bound = match bound {
+                WindowFrameBound::CurrentRow => FrameBound::CurrentRow,
+                WindowFrameBound::Preceding(None) => FrameBound::UnboundedPreceding,
+                WindowFrameBound::Preceding(Some(expr)) => FrameBound::Preceding(offset(expr)?),
+                WindowFrameBound::Following(None) => FrameBound::UnboundedFollowing,
+                WindowFrameBound::Following(Some(expr)) => FrameBound::Following(offset(expr)?),
+            };
+            // RANGE は値の差によるフレームをサポートしない
+            if units == FrameUnits::Range && matches!(bound, FrameBound::Preceding(_) | FrameBound::Following(_)) {
+                return Err(ParseError::UnsupportedFeature(
+                    "RANGE window frames with an offset are not supported".to_string()));
+            }
+            Ok(bound)
+        };
+        
+        let start = parse_bound(&frame.start_bound)?;
+        let end = match &frame.end_bound {
+            Some(bound) => parse_bound(bound)?,
+            None => FrameBound::CurrentRow,
+        };
+        let frame = WindowFrame { units, start, end };
+        if start == FrameBound::UnboundedFollowing || end == FrameBound::UnboundedPreceding {
+            return Err(ParseError::InvalidValue(format!("Invalid window frame: {}", frame)));
+        }
+        
+        Ok(frame)
+    }
+    
+    /// INTERVAL '1 day' や INTERVAL '2' HOUR を時間間隔のリテラルとして解析する
+    fn parse_interval(&self, interval: &SqlInterval) -> Result<Expression, ParseError> {
+        let text = match interval.value.as_ref() {
+            Expr::Value(SqlValue::SingleQuotedString(s)) => s.clone(),
+            Expr::Value(SqlValue::Number(n, _)) => n.clone(),
+            other => return Err(ParseError::UnsupportedFeature(
+                format!("Unsupported interval value: {}", other))),
+        };
+        if interval.last_field.is_some() {
+            return Err(ParseError::UnsupportedFeature(
+                format!("Unsupported interval syntax: {}", interval)));
+        }
+
+        // 単位が別に指定された場合は数値に付け加える
+        let text = match interval.leading_field {
+            Some(field) => format!("{} {}", text, field),
+            None => text,
+        };
+        Interval::parse(&text)
+            .map(|i| Expression::Literal(Value::Interval(i)))
+            .ok_or_else(|| ParseError::InvalidValue(format!("Invalid interval value: {}", text)))
+    }
+
+    /// INTERVAL を含む演算を、演算子の優先順位に従って組み立て直す
+    ///
+    /// sqlparser は INTERVAL の後に続く演算（INTERVAL '1 day' + x < y など）をまとめて
+    /// 時間間隔の値として解析するため、演算子と被演算子の列に戻してから解析し直す。
+    fn parse_operator_chain(&self, expr: &Expr) -> Result<Expression, ParseError> {
+        let mut operands = Vec::new();
+        let mut operators = Vec::new();
+        self.flatten_operators(expr, None, &mut operands, &mut operators)?;
+
+        let mut operands = operands.into_iter();
+        let mut output = operands.next().into_iter().collect::<Vec<_>>();
+        let mut pending: Vec<BinaryOperator> = Vec::new();
+        let reduce = |output: &mut Vec<Expression>, op: BinaryOperator| {
+            if let (Some(right), Some(left)) = (output.pop(), output.pop()) {
+                output.push(Expression::BinaryOp { left: Box::new(left), op, right: Box::new(right) });
+            }
+        };
+        for (op, operand) in operators.into_iter().zip(operands) {
+            // 左結合なので、同じ強さの演算子は先に組み立てる
+            while let Some(top) = pending.pop_if(|top| top.precedence() >= op.precedence()) {
+                reduce(&mut output, top);
+            }
+            pending.push(op);
+            output.push(operand);
+        }
+        while let Some(op) = pending.pop() {
+            reduce(&mut output, op);
+        }
+
+        output.pop().ok_or_else(|| ParseError::InvalidValue(format!("Invalid expression: {}", expr)))
+    }
+
+    /// 二項演算の木を、被演算子と演算子の列に展開する（interval は左端の被演算子に付ける INTERVAL）
+    fn flatten_operators(
+        &self,
+        expr: &Expr,
+        interval: Option<&SqlInterval>,
+        operands: &mut Vec<Expression>,
+        operators: &mut Vec<BinaryOperator>
+    ) -> Result<(), ParseError> {
+        match expr {
+            Expr::BinaryOp { left, op, right } => {
+                self.flatten_operators(left, interval, operands, operators)?;
+                operators.push(binary_operator(op)?);
+                self.flatten_operators(right, None, operands, operators)
+            },
+            Expr::Interval(inner) if !matches!(inner.value.as_ref(), Expr::Value(_)) => {
+                self.flatten_operators(&inner.value, Some(inner), operands, operators)
+            },
+            _ => {
+                operands.push(match interval {
+                    Some(interval) => self.parse_interval(&SqlInterval {
+                        value: Box::new(expr.clone()),
+                        ..interval.clone()
+                    })?,
+                    None => self.parse_expression(expr)?,
+                });
+                Ok(())
+            },
+        }
+    }
+
+    /// ORDER BY の項目を解析する
+    fn parse_order_by(&self, order_by: &[SqlOrderByExpr]) -> Result<Vec<OrderByExpr>, ParseError> {
+        order_by.iter().map(|item| Ok(OrderByExpr {
+            expr: self.parse_expression(&item.expr)?,
+            descending: item.asc == Some(false),
+            nulls_first: item.nulls_first,
+        })).collect()
     }
     
     /// INSERT文を解析する
@@ -266,31 +1315,78 @@ impl SqlParser {
         &self,
         table_name: ObjectName,
         columns: Vec<Ident>,
-        values: Values
+        source: InsertSource,
+        on_conflict: Option<OnConflict>,
+        returning: Option<Vec<SelectItem>>
     ) -> Result<ParsedStatement, ParseError> {
         let table = self.object_name_to_string(&table_name)?;
         let column_names: Vec<String> = columns.into_iter().map(|ident| ident.value).collect();
         
+        Ok(ParsedStatement::Insert(InsertStatement {
+            table_name: table,
+            columns: column_names,
+            source,
+            on_conflict,
+            returning,
+        }))
+    }
+    
+    /// ON CONFLICT（PostgreSQL）と ON DUPLICATE KEY UPDATE（MySQL）を解析する
+    fn parse_on_insert(&self, on: OnInsert) -> Result<OnConflict, ParseError> {
+        match on {
+            OnInsert::OnConflict(on_conflict) => {
+                let columns = match on_conflict.conflict_target {
+                    None => Vec::new(),
+                    Some(ConflictTarget::Columns(idents)) => idents.into_iter().map(|ident| ident.value).collect(),
+                    Some(ConflictTarget::OnConstraint(name)) => return Err(ParseError::UnsupportedFeature(
+                        format!("ON CONFLICT ON CONSTRAINT {} is not supported", name))),
+                };
+                
+                let action = match on_conflict.action {
+                    OnConflictAction::DoNothing => ConflictAction::DoNothing,
+                    OnConflictAction::DoUpdate(do_update) => {
+                        if do_update.selection.is_some() {
+                            return Err(ParseError::UnsupportedFeature(
+                                "WHERE clause in ON CONFLICT DO UPDATE is not supported".to_string()));
+                        }
+                        ConflictAction::DoUpdate(self.parse_conflict_assignments(do_update.assignments)?)
+                    },
+                };
+                
+                Ok(OnConflict { columns, action })
+            },
+            OnInsert::DuplicateKeyUpdate(assignments) => Ok(OnConflict {
+                columns: Vec::new(),
+                action: ConflictAction::DoUpdate(self.parse_conflict_assignments(assignments)?),
+            }),
+            _ => Err(ParseError::UnsupportedFeature("Unsupported ON clause in INSERT".to_string())),
+        }
+    }
+    
+    /// 競合時に適用する代入式を解析する
+    fn parse_conflict_assignments(&self, assignments: Vec<Assignment>) -> Result<Vec<(String, Expression)>, ParseError> {
+        assignments.into_iter()
+            .map(|assignment| {
+                let column = assignment.id.last()
+                    .ok_or_else(|| ParseError::SyntaxError("Missing column name in assignment".to_string()))?
+                    .value.clone();
+                Ok((column, self.parse_expression(&assignment.value)?))
+            })
+            .collect()
+    }
+    
+    /// INSERTのVALUES句を解析する
+    fn parse_insert_values(&self, values: Values) -> Result<InsertSource, ParseError> {
         let mut parsed_values = Vec::new();
         for row in values.rows {
             let mut row_values = Vec::new();
             for expr in row {
-                if let Expr::Value(val) = expr {
-                    let value = self.sql_value_to_value(&val)?;
-                    row_values.push(value);
-                } else {
-                    return Err(ParseError::UnsupportedFeature(
-                        "Complex INSERT expressions not supported".to_string()));
-                }
+                row_values.push(self.parse_insert_expression(&expr)?);
             }
             parsed_values.push(row_values);
         }
         
-        Ok(ParsedStatement::Insert(InsertStatement {
-            table_name: table,
-            columns: column_names,
-            values: parsed_values,
-        }))
+        Ok(InsertSource::Values(parsed_values))
     }
     
     /// UPDATE文を解析する
@@ -299,9 +1395,11 @@ fn parse_update(
     &self,
     table: TableWithJoins,
     assignments: Vec<sqlparser::ast::Assignment>,
-    selection: Option<Expr>
+    selection: Option<Expr>,
+    returning: Option<Vec<SelectItem>>
 ) -> Result<ParsedStatement, ParseError> {
     let table_name = self.get_table_name(&table)?;
+    let table_alias = self.get_table_alias(&table);
     
     let mut updates = Vec::new();
     for assignment in assignments {
@@ -313,10 +1411,16 @@ fn parse_update(
         
         let column_name = assignment.id[0].value.clone();
         
-        let value = if let Expr::Value(val) = &assignment.value {
-            self.sql_value_to_value(val)?
+        // 定数式は値に畳み込み、カラムを参照する式は行ごとに評価する
+        let value = self.parse_expression(&assignment.value)?;
+        if value.contains_aggregate() || value.contains_subquery() || value.contains_window() {
+            return Err(ParseError::UnsupportedFeature(
+                "Aggregates, subqueries and window functions are not supported in UPDATE SET".to_string()));
+        }
+        let value = if value.is_constant() {
+            Expression::Literal(value.evaluate(&Row::new()).map_err(|e| ParseError::InvalidValue(e.to_string()))?)
         } else {
-            return Err(ParseError::UnsupportedFeature("Complex UPDATE expressions not supported".to_string()));
+            value
         };
         
         updates.push((column_name, value));
@@ -329,8 +1433,10 @@ fn parse_update(
     
     Ok(ParsedStatement::Update(UpdateStatement {
         table_name,
+        table_alias,
         updates,
         filter,
+        returning,
     }))
 }
     
@@ -338,7 +1444,9 @@ fn parse_update(
     fn parse_delete(
         &self,
         table_name: String,
-        selection: Option<Expr>
+        table_alias: Option<String>,
+        selection: Option<Expr>,
+        returning: Option<Vec<SelectItem>>
     ) -> Result<ParsedStatement, ParseError> {
         let filter = match selection {
             Some(expr) => Some(self.parse_filter_expression(&expr)?),
@@ -347,7 +1455,9 @@ fn parse_update(
         
         Ok(ParsedStatement::Delete(DeleteStatement {
             table_name,
+            table_alias,
             filter,
+            returning,
         }))
     }
     
@@ -368,9 +1478,90 @@ fn parse_update(
         }
     }
     
+    /// FROM句の unnest(配列) を解析し、テーブルとしての名前とともに返す
+    ///
+    /// 結果カラムの名前は AS t(x) の x、カラム名がなければ別名の t、別名もなければ unnest とする。
+    fn parse_unnest(&self, alias: Option<&TableAlias>, array: &Expr, with_offset: bool) -> Result<(String, TableFunction), ParseError> {
+        if with_offset {
+            return Err(ParseError::UnsupportedFeature("WITH OFFSET is not supported".to_string()));
+        }
+
+        let array = self.parse_expression(array)?;
+        if !array.referenced_columns().is_empty() || array.contains_aggregate() ||
+            array.contains_subquery() || array.contains_window() {
+            return Err(ParseError::UnsupportedFeature(
+                "unnest() argument must not reference columns or contain subqueries".to_string()));
+        }
+
+        let name = alias.map_or("unnest", |alias| alias.name.value.as_str()).to_string();
+        let column = match alias.map(|alias| alias.columns.as_slice()) {
+            None | Some([]) => name.clone(),
+            Some([column]) => column.value.clone(),
+            Some(_) => return Err(ParseError::UnsupportedFeature(
+                "unnest() returns a single column".to_string())),
+        };
+        Ok((name, TableFunction::Unnest { array, column }))
+    }
+
+    /// テーブルの別名を取得する
+    fn get_table_alias(&self, table: &TableWithJoins) -> Option<String> {
+        match &table.relation {
+            TableFactor::Table { alias: Some(alias), .. } => Some(alias.name.value.clone()),
+            _ => None,
+        }
+    }
+    
+    /// カラム定義のデータ型を変換する
+    ///
+    /// 組み込みの型でない名前は列挙型とみなし、実行時に登録済みの型の定義で置き換える。
+    fn parse_column_type(&self, data_type: &sqlparser::ast::DataType) -> Result<DataType, ParseError> {
+        match data_type {
+            sqlparser::ast::DataType::Array(Some(element)) => {
+                Ok(DataType::Array(Box::new(self.parse_column_type(element)?)))
+            },
+            sqlparser::ast::DataType::Custom(name, modifiers)
+                if modifiers.is_empty() && !name.to_string().eq_ignore_ascii_case("JSONB") => {
+                Ok(DataType::Enum(Arc::new(EnumType::named(self.object_name_to_string(name)?))))
+            },
+            _ => self.parse_data_type(data_type),
+        }
+    }
+    
+    /// 照合順序の名前を解析する
+    fn parse_collation(&self, name: &ObjectName) -> Result<Collation, ParseError> {
+        let name = self.object_name_to_string(name)?;
+        Collation::from_name(&name).ok_or_else(|| ParseError::InvalidValue(
+            format!("Collation {} does not exist", name)))
+    }
+
+    /// カラム定義のデータ型で指定された長さや整数の幅を取り出す
+    ///
+    /// 長さを省略した CHAR や VARCHAR は長さを制限しない。
+    fn parse_type_limit(&self, data_type: &sqlparser::ast::DataType) -> Result<Option<TypeLimit>, ParseError> {
+        let length = |length: &sqlparser::ast::CharacterLength| match usize::try_from(length.length) {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(ParseError::InvalidDataType(
+                format!("Length for type {} must be at least 1", data_type))),
+        };
+        
+        Ok(match data_type {
+            sqlparser::ast::DataType::Varchar(Some(n)) |
+            sqlparser::ast::DataType::CharacterVarying(Some(n)) |
+            sqlparser::ast::DataType::CharVarying(Some(n)) => Some(TypeLimit::VarChar(length(n)?)),
+            sqlparser::ast::DataType::Char(Some(n)) |
+            sqlparser::ast::DataType::Character(Some(n)) => Some(TypeLimit::Char(length(n)?)),
+            sqlparser::ast::DataType::SmallInt(_) => Some(TypeLimit::SmallInt),
+            sqlparser::ast::DataType::Int(_) |
+            sqlparser::ast::DataType::Integer(_) => Some(TypeLimit::Int),
+            sqlparser::ast::DataType::BigInt(_) => Some(TypeLimit::BigInt),
+            _ => None,
+        })
+    }
+    
     /// SQL文のデータ型をドメインデータ型に変換する
     fn parse_data_type(&self, data_type: &sqlparser::ast::DataType) -> Result<DataType, ParseError> {
         match data_type {
+            sqlparser::ast::DataType::SmallInt(_) |
             sqlparser::ast::DataType::Int(_) | 
             sqlparser::ast::DataType::Integer(_) |
             sqlparser::ast::DataType::BigInt(_) => Ok(DataType::Integer),
@@ -378,29 +1569,85 @@ fn parse_update(
             sqlparser::ast::DataType::Float(_) |
             sqlparser::ast::DataType::Double |
             sqlparser::ast::DataType::Real => Ok(DataType::Float),
+
+            sqlparser::ast::DataType::Numeric(info) |
+            sqlparser::ast::DataType::Decimal(info) |
+            sqlparser::ast::DataType::Dec(info) => self.parse_decimal_type(info),
             
             sqlparser::ast::DataType::Char(_) |
+            sqlparser::ast::DataType::Character(_) |
             sqlparser::ast::DataType::Varchar(_) |
+            sqlparser::ast::DataType::CharacterVarying(_) |
+            sqlparser::ast::DataType::CharVarying(_) |
             sqlparser::ast::DataType::Text => Ok(DataType::Text),
             
             sqlparser::ast::DataType::Boolean => Ok(DataType::Boolean),
             
+            sqlparser::ast::DataType::Timestamp(_, TimezoneInfo::WithTimeZone | TimezoneInfo::Tz) => Ok(DataType::TimestampTz),
             sqlparser::ast::DataType::Timestamp(_, _) |  // 2つの引数を持つバージョン
-            sqlparser::ast::DataType::Date => Ok(DataType::Timestamp),
+            sqlparser::ast::DataType::Datetime(_) => Ok(DataType::Timestamp),
+
+            sqlparser::ast::DataType::Date => Ok(DataType::Date),
+
+            sqlparser::ast::DataType::Time(_, _) => Ok(DataType::Time),
+
+            sqlparser::ast::DataType::Interval => Ok(DataType::Interval),
+
+            sqlparser::ast::DataType::Blob(_) |
+            sqlparser::ast::DataType::Bytea |
+            sqlparser::ast::DataType::Binary(_) |
+            sqlparser::ast::DataType::Varbinary(_) => Ok(DataType::Blob),
+
+            sqlparser::ast::DataType::Uuid => Ok(DataType::Uuid),
+
+            // INTEGER[] や ARRAY<INTEGER>
+            sqlparser::ast::DataType::Array(Some(element)) => {
+                Ok(DataType::Array(Box::new(self.parse_data_type(element)?)))
+            },
+
+            sqlparser::ast::DataType::JSON => Ok(DataType::Json),
+            sqlparser::ast::DataType::Custom(name, modifiers)
+                if modifiers.is_empty() && name.to_string().eq_ignore_ascii_case("JSONB") => Ok(DataType::Json),
             
             _ => Err(ParseError::InvalidDataType(format!("Unsupported data type: {:?}", data_type)))
         }
     }
     
+    /// NUMERIC(p, s) の精度と位取りを検証する（省略時は最大精度・位取り0）
+    fn parse_decimal_type(&self, info: &sqlparser::ast::ExactNumberInfo) -> Result<DataType, ParseError> {
+        let (precision, scale) = match info {
+            sqlparser::ast::ExactNumberInfo::None => (DataType::MAX_DECIMAL_PRECISION as u64, 0),
+            sqlparser::ast::ExactNumberInfo::Precision(p) => (*p, 0),
+            sqlparser::ast::ExactNumberInfo::PrecisionAndScale(p, s) => (*p, *s),
+        };
+
+        if precision == 0 || precision > DataType::MAX_DECIMAL_PRECISION as u64 {
+            return Err(ParseError::InvalidDataType(format!(
+                "DECIMAL precision must be between 1 and {}, got {}", DataType::MAX_DECIMAL_PRECISION, precision)));
+        }
+        if scale > precision {
+            return Err(ParseError::InvalidDataType(format!(
+                "DECIMAL scale {} must not exceed precision {}", scale, precision)));
+        }
+        Ok(DataType::Decimal(precision as u8, scale as u8))
+    }
+
     /// SQL値をドメイン値に変換する
     fn sql_value_to_value(&self, value: &SqlValue) -> Result<Value, ParseError> {
         match value {
             SqlValue::Number(n, _) => {
-                if n.contains('.') {
+                if n.contains(['e', 'E']) {
                     match n.parse::<f64>() {
                         Ok(f) => Ok(Value::Float(f)),
                         Err(_) => Err(ParseError::InvalidValue(format!("Invalid float value: {}", n)))
                     }
+                } else if n.contains('.') {
+                    // 小数点を含む定数は10進数（桁が多すぎて表せない場合は浮動小数点数）
+                    match (n.parse::<Decimal>(), n.parse::<f64>()) {
+                        (Ok(d), _) => Ok(Value::Decimal(d)),
+                        (_, Ok(f)) => Ok(Value::Float(f)),
+                        _ => Err(ParseError::InvalidValue(format!("Invalid numeric value: {}", n)))
+                    }
                 } else {
                     match n.parse::<i64>() {
                         Ok(i) => Ok(Value::Integer(i)),
@@ -413,10 +1660,35 @@ fn parse_update(
             },
             SqlValue::Boolean(b) => Ok(Value::Boolean(*b)),
             SqlValue::Null => Ok(Value::Null),
+            // X'CAFE' はバイト列
+            SqlValue::HexStringLiteral(hex) => Value::parse_hex(hex)
+                .map(Value::Bytes)
+                .ok_or_else(|| ParseError::InvalidValue(format!("Invalid hex string: X'{}'", hex))),
             _ => Err(ParseError::InvalidValue(format!("Unsupported value type: {:?}", value)))
         }
     }
     
+    /// INSERTのVALUES句の式を解析する
+    ///
+    /// 定数式は値に畳み込む。gen_random_uuid() のような関数呼び出しは行ごとに評価するため式のまま残すが、
+    /// カラムの参照や集約関数、サブクエリ、ウィンドウ関数は受け付けない。
+    fn parse_insert_expression(&self, expr: &Expr) -> Result<Expression, ParseError> {
+        if let Expr::Value(value) = expr {
+            return Ok(Expression::Literal(self.sql_value_to_value(value)?));
+        }
+
+        let expr = self.parse_expression(expr)?;
+        if expr.is_constant() {
+            let value = expr.evaluate(&Row::new()).map_err(|e| ParseError::InvalidValue(e.to_string()))?;
+            return Ok(Expression::Literal(value));
+        }
+        if !expr.referenced_columns().is_empty() || expr.contains_aggregate() ||
+            expr.contains_subquery() || expr.contains_window() {
+            return Err(ParseError::UnsupportedFeature("Complex INSERT expressions not supported".to_string()));
+        }
+        Ok(expr)
+    }
+
     /// SQL値を文字列表現に変換する
     fn sql_value_to_string(&self, value: &SqlValue) -> Result<String, ParseError> {
         match value {
@@ -495,8 +1767,7 @@ fn parse_filter_expression(&self, expr: &Expr) -> Result<FilterCondition, ParseE
                     sqlparser::ast::BinaryOperator::LtEq => FilterOperator::LessOrEqual,
                     // Like演算子がBinaryOperatorにない場合は別の方法で処理する必要があります
                     // 現在のバージョンではサポートしていないようです
-                    _ => return Err(ParseError::UnsupportedFeature(
-                        format!("Unsupported operator: {:?}", op)))
+                    _ => return self.parse_condition_expression(expr),
                 };
                 let value = self.sql_value_to_value(value)?;
                 
@@ -515,8 +1786,7 @@ fn parse_filter_expression(&self, expr: &Expr) -> Result<FilterCondition, ParseE
                     sqlparser::ast::BinaryOperator::LtEq => FilterOperator::GreaterOrEqual, // 左右反転
                     sqlparser::ast::BinaryOperator::Gt => FilterOperator::Less, // 左右反転
                     sqlparser::ast::BinaryOperator::GtEq => FilterOperator::LessOrEqual, // 左右反転
-                    _ => return Err(ParseError::UnsupportedFeature(
-                        format!("Unsupported operator: {:?}", op)))
+                    _ => return self.parse_condition_expression(expr),
                 };
                 let value = self.sql_value_to_value(value)?;
                 
@@ -526,18 +1796,208 @@ fn parse_filter_expression(&self, expr: &Expr) -> Result<FilterCondition, ParseE
                     value,
                 })
             } else {
-                Err(ParseError::UnsupportedFeature("Complex conditions not supported".to_string()))
+                self.parse_condition_expression(expr)
             }
         },
         
-        // その他の式はサポートしない
-        _ => Err(ParseError::UnsupportedFeature("Unsupported WHERE expression".to_string()))
+        // カラムと値の比較で表せない条件は一般的な式として扱う
+        _ => self.parse_condition_expression(expr),
+    }
+}
+
+/// WHERE句の条件を一般的な式として解析する
+fn parse_condition_expression(&self, expr: &Expr) -> Result<FilterCondition, ParseError> {
+    let expr = self.parse_expression(expr)?;
+    if expr.contains_window() {
+        return Err(ParseError::UnsupportedFeature(
+            "Window functions are not allowed in WHERE".to_string()));
     }
+    Ok(FilterCondition::Expression(expr))
 }
 }
 
+/// sqlparser の二項演算子を変換する
+fn binary_operator(op: &sqlparser::ast::BinaryOperator) -> Result<BinaryOperator, ParseError> {
+    match op {
+        sqlparser::ast::BinaryOperator::Eq => Ok(BinaryOperator::Eq),
+        sqlparser::ast::BinaryOperator::NotEq => Ok(BinaryOperator::NotEq),
+        sqlparser::ast::BinaryOperator::Lt => Ok(BinaryOperator::Lt),
+        sqlparser::ast::BinaryOperator::LtEq => Ok(BinaryOperator::LtEq),
+        sqlparser::ast::BinaryOperator::Gt => Ok(BinaryOperator::Gt),
+        sqlparser::ast::BinaryOperator::GtEq => Ok(BinaryOperator::GtEq),
+        sqlparser::ast::BinaryOperator::And => Ok(BinaryOperator::And),
+        sqlparser::ast::BinaryOperator::Or => Ok(BinaryOperator::Or),
+        sqlparser::ast::BinaryOperator::Plus => Ok(BinaryOperator::Plus),
+        sqlparser::ast::BinaryOperator::Minus => Ok(BinaryOperator::Minus),
+        sqlparser::ast::BinaryOperator::Multiply => Ok(BinaryOperator::Multiply),
+        sqlparser::ast::BinaryOperator::Divide => Ok(BinaryOperator::Divide),
+        sqlparser::ast::BinaryOperator::Modulo => Ok(BinaryOperator::Modulo),
+        sqlparser::ast::BinaryOperator::StringConcat => Ok(BinaryOperator::Concat),
+        _ => Err(ParseError::UnsupportedFeature(format!("Unsupported operator: {}", op))),
+    }
+}
+
+/// 識別子と比較演算子に分かれてしまった #> #>> @> を、JSON演算子のトークンに戻す
+///
+/// GenericDialect では '#' と '@' を識別子の先頭に使えるため、`data #> '{a}'` の `#` は識別子になる。
+fn merge_json_operators(tokens: Vec<Token>) -> Vec<Token> {
+    let mut merged = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let operator = match (&token, tokens.peek()) {
+            (Token::Word(word), Some(next)) if word.quote_style.is_none() => match (word.value.as_str(), next) {
+                ("#", Token::Gt) => Some(Token::HashArrow),
+                ("#", Token::ShiftRight) => Some(Token::HashLongArrow),
+                ("@", Token::Gt) => Some(Token::AtArrow),
+                _ => None,
+            },
+            _ => None,
+        };
+        match operator {
+            Some(operator) => {
+                tokens.next();
+                merged.push(operator);
+            },
+            None => merged.push(token),
+        }
+    }
+    merged
+}
+
+/// sqlparser のJSON演算子を変換する
+fn json_operator(op: &JsonOperator) -> Result<BinaryOperator, ParseError> {
+    match op {
+        JsonOperator::Arrow => Ok(BinaryOperator::JsonGet),
+        JsonOperator::LongArrow => Ok(BinaryOperator::JsonGetText),
+        JsonOperator::HashArrow => Ok(BinaryOperator::JsonPath),
+        JsonOperator::HashLongArrow => Ok(BinaryOperator::JsonPathText),
+        JsonOperator::AtArrow => Ok(BinaryOperator::JsonContains),
+        JsonOperator::ArrowAt => Ok(BinaryOperator::JsonContainedBy),
+        _ => Err(ParseError::UnsupportedFeature(format!("Unsupported operator: {}", op))),
+    }
+}
+
+/// JSON演算子の右側が、後続の演算まで取り込んだ式になっているかどうか
+fn binds_json_operand(right: &Expr) -> bool {
+    matches!(right,
+        Expr::BinaryOp { .. } | Expr::JsonAccess { .. } | Expr::IsNull(_) | Expr::IsNotNull(_) |
+        Expr::InList { .. } | Expr::Between { .. } | Expr::Like { .. } | Expr::ILike { .. })
+}
+
+/// JSON演算子を、右側の式の左端の被演算子だけに結び付け直す
+///
+/// sqlparser はJSON演算子の右側を式全体として解析するため、data ->> 'a' = 'b' は
+/// data ->> ('a' = 'b') になる。これを (data ->> 'a') = 'b' に組み替える。
+fn rebind_json_access(left: &Expr, operator: &JsonOperator, right: &Expr) -> Expr {
+    let rebind = |operand: &Expr| Box::new(rebind_json_access(left, operator, operand));
+    match right {
+        Expr::BinaryOp { left: operand, op, right } => Expr::BinaryOp {
+            left: rebind(operand),
+            op: op.clone(),
+            right: right.clone(),
+        },
+        Expr::JsonAccess { left: operand, operator: op, right } => Expr::JsonAccess {
+            left: rebind(operand),
+            operator: *op,
+            right: right.clone(),
+        },
+        Expr::IsNull(operand) => Expr::IsNull(rebind(operand)),
+        Expr::IsNotNull(operand) => Expr::IsNotNull(rebind(operand)),
+        Expr::InList { expr, list, negated } => Expr::InList {
+            expr: rebind(expr),
+            list: list.clone(),
+            negated: *negated,
+        },
+        Expr::Between { expr, negated, low, high } => Expr::Between {
+            expr: rebind(expr),
+            negated: *negated,
+            low: low.clone(),
+            high: high.clone(),
+        },
+        Expr::Like { negated, expr, pattern, escape_char } => Expr::Like {
+            negated: *negated,
+            expr: rebind(expr),
+            pattern: pattern.clone(),
+            escape_char: *escape_char,
+        },
+        Expr::ILike { negated, expr, pattern, escape_char } => Expr::ILike {
+            negated: *negated,
+            expr: rebind(expr),
+            pattern: pattern.clone(),
+            escape_char: *escape_char,
+        },
+        operand => Expr::JsonAccess {
+            left: Box::new(left.clone()),
+            operator: *operator,
+            right: Box::new(operand.clone()),
+        },
+    }
+}
+
+/// 後続の演算を値に取り込んだ INTERVAL を含むかどうか
+fn contains_interval_operation(expr: &Expr) -> bool {
+    match expr {
+        Expr::BinaryOp { left, right, .. } => contains_interval_operation(left) || contains_interval_operation(right),
+        Expr::Interval(interval) => !matches!(interval.value.as_ref(), Expr::Value(_)),
+        _ => false,
+    }
+}
+
+/// 未解決のスカラー関数呼び出しを作る
+fn scalar_function(name: &str, args: Vec<Expression>) -> Expression {
+    Expression::Function {
+        name: name.to_uppercase(),
+        args,
+        function: None,
+    }
+}
+
 impl Default for SqlParser {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_without_from_reads_a_single_row() {
+        let statements = SqlParser::new().parse("SELECT 1 AS n").unwrap();
+        let [ParsedStatement::Select(select)] = &statements[..] else {
+            panic!("expected a single SELECT");
+        };
+        assert!(matches!(select.table_function, Some(TableFunction::SingleRow)));
+        assert_eq!(select.projection.len(), 1);
+    }
+
+    #[test]
+    fn select_without_from_rejects_wildcard_and_joins_are_reported() {
+        let parser = SqlParser::new();
+        assert!(matches!(parser.parse("SELECT *"), Err(ParseError::SyntaxError(_))));
+        let Err(error) = parser.parse("SELECT a FROM t, u") else {
+            panic!("expected an error for a join");
+        };
+        assert!(error.to_string().contains("more than one table"), "{}", error);
+    }
+
+    fn number(n: &str) -> Result<Value, ParseError> {
+        SqlParser::new().sql_value_to_value(&SqlValue::Number(n.to_string(), false))
+    }
+
+    #[test]
+    fn numeric_literals() {
+        assert_eq!(number("12").unwrap(), Value::Integer(12));
+        assert_eq!(number("0.2").unwrap(), Value::Decimal("0.2".parse().unwrap()));
+        assert_eq!(number("1.50").unwrap(), Value::Decimal("1.50".parse().unwrap()));
+        assert_eq!(number("1e3").unwrap(), Value::Float(1000.0));
+        assert_eq!(number("2.5E-1").unwrap(), Value::Float(0.25));
+    }
+
+    #[test]
+    fn numeric_literals_beyond_decimal_range_become_float() {
+        assert_eq!(number("123456789012345678901234567890.5").unwrap(), Value::Float(123456789012345678901234567890.5));
+        assert!(number("99999999999999999999").is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use std::sync::Arc;
 use async_trait::async_trait;
 
-use crate::domain::entity::{Table, Row, Value, ResultSet};
-use crate::domain::repository::{TableRepository, RepositoryError, FilterCondition};
+use crate::domain::entity::{Table, Row, ResultSet, View, EnumType, LabelPosition};
+use crate::domain::expression::Expression;
+use crate::domain::repository::{TableRepository, RepositoryError, FilterCondition, OnConflict, UpsertOutcome};
 use crate::infrastructure::storage::{MemoryStorage, StorageError};
 
 /// インメモリリポジトリの実装
@@ -41,7 +42,62 @@ impl TableRepository for MemoryTableRepository {
         Ok(self.storage.get_table_names())
     }
     
-    async fn insert(&self, table_name: &str, row: &Row) -> Result<(), RepositoryError> {
+    async fn create_view(&self, view: &View, or_replace: bool) -> Result<(), RepositoryError> {
+        self.storage.create_view(view.clone(), or_replace)
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn create_materialized_view(
+        &self,
+        view: &View,
+        table: &Table,
+        rows: &[Row],
+        or_replace: bool
+    ) -> Result<(), RepositoryError> {
+        self.storage.create_materialized_view(view.clone(), table.clone(), rows.to_vec(), or_replace)
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn view_exists(&self, view_name: &str) -> Result<bool, RepositoryError> {
+        Ok(self.storage.view_exists(view_name))
+    }
+    
+    async fn drop_view(&self, view_name: &str, if_exists: bool) -> Result<(), RepositoryError> {
+        self.storage.drop_view(view_name, if_exists)
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn get_view(&self, view_name: &str) -> Result<View, RepositoryError> {
+        self.storage.get_view(view_name)
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn get_view_names(&self) -> Result<Vec<String>, RepositoryError> {
+        Ok(self.storage.get_view_names())
+    }
+    
+    async fn create_type(&self, enum_type: &EnumType) -> Result<(), RepositoryError> {
+        self.storage.create_type(enum_type.clone())
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn get_type(&self, type_name: &str) -> Result<Arc<EnumType>, RepositoryError> {
+        self.storage.get_type(type_name)
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn add_enum_label(
+        &self,
+        type_name: &str,
+        label: &str,
+        position: Option<&LabelPosition>,
+        if_not_exists: bool
+    ) -> Result<(), RepositoryError> {
+        self.storage.add_enum_label(type_name, label, position, if_not_exists)
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn insert(&self, table_name: &str, row: &Row) -> Result<Row, RepositoryError> {
         self.storage.insert_row(table_name, row.clone())
             .map_err(|e: StorageError| RepositoryError::from(e))
     }
@@ -74,9 +130,9 @@ impl TableRepository for MemoryTableRepository {
     async fn update(
         &self,
         table_name: &str,
-        updates: &[(String, Value)],
+        updates: &[(String, Expression)],
         filter: Option<&FilterCondition>
-    ) -> Result<usize, RepositoryError> {
+    ) -> Result<Vec<(Row, Row)>, RepositoryError> {
         self.storage.update_rows(table_name, updates, filter)
             .map_err(|e: StorageError| RepositoryError::from(e))
     }
@@ -85,10 +141,45 @@ impl TableRepository for MemoryTableRepository {
         &self,
         table_name: &str,
         filter: Option<&FilterCondition>
-    ) -> Result<usize, RepositoryError> {
+    ) -> Result<Vec<Row>, RepositoryError> {
         self.storage.delete_rows(table_name, filter)
             .map_err(|e: StorageError| RepositoryError::from(e))
     }
+    
+    async fn upsert(
+        &self,
+        table_name: &str,
+        row: &Row,
+        on_conflict: &OnConflict
+    ) -> Result<UpsertOutcome, RepositoryError> {
+        self.storage.upsert_row(table_name, row.clone(), on_conflict)
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn begin_transaction(&self) -> Result<(), RepositoryError> {
+        self.storage.begin_transaction()
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn commit_transaction(&self) -> Result<(), RepositoryError> {
+        self.storage.commit_transaction()
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn rollback_transaction(&self) -> Result<(), RepositoryError> {
+        self.storage.rollback_transaction()
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn replace_rows(&self, table_name: &str, rows: &[Row]) -> Result<(), RepositoryError> {
+        self.storage.replace_rows(table_name, rows.to_vec())
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
+    
+    async fn apply_view_delta(&self, view_name: &str, removed: &[Row], added: &[Row]) -> Result<(), RepositoryError> {
+        self.storage.apply_view_delta(view_name, removed, added.to_vec())
+            .map_err(|e: StorageError| RepositoryError::from(e))
+    }
 }
 
 impl From<StorageError> for RepositoryError {
@@ -97,6 +188,17 @@ impl From<StorageError> for RepositoryError {
             StorageError::TableNotFound(name) => RepositoryError::TableNotFound(name),
             StorageError::TableAlreadyExists(name) => RepositoryError::TableAlreadyExists(name),
             StorageError::ColumnNotFound(col, table) => RepositoryError::ColumnNotFound(col, table),
+            StorageError::ViewNotFound(name) => RepositoryError::ViewNotFound(name),
+            StorageError::ViewAlreadyExists(name) => RepositoryError::ViewAlreadyExists(name),
+            StorageError::MaterializedViewModification(_) |
+            StorageError::MaterializedViewDrop(_) => RepositoryError::DataError(error.to_string()),
+            StorageError::TypeNotFound(name) => RepositoryError::TypeNotFound(name),
+            StorageError::TypeAlreadyExists(name) => RepositoryError::TypeAlreadyExists(name),
+            StorageError::ValueTooLong(..) |
+            StorageError::OutOfRange(..) |
+            StorageError::InvalidEnumValue(..) |
+            StorageError::EnumLabelAlreadyExists(..) |
+            StorageError::EnumLabelNotFound(..) => RepositoryError::DataError(error.to_string()),
             StorageError::TypeMismatch { expected, actual } => 
                 RepositoryError::DataError(format!("Type mismatch: expected {:?}, got {:?}", expected, actual)),
             StorageError::NotNullViolation(col) => 
@@ -105,6 +207,12 @@ impl From<StorageError> for RepositoryError {
                 RepositoryError::DataError(format!("UNIQUE constraint violation for column {}", col)),
             StorageError::PrimaryKeyViolation => 
                 RepositoryError::DataError("PRIMARY KEY constraint violation".to_string()),
+            StorageError::NoUniqueConstraint(col) => 
+                RepositoryError::DataError(format!("There is no unique or primary key constraint on column {}", col)),
+            StorageError::Expression(e) => RepositoryError::DataError(e.to_string()),
+            StorageError::InvalidValue(e) => RepositoryError::DataError(e.to_string()),
+            StorageError::TransactionAlreadyActive | StorageError::NoActiveTransaction =>
+                RepositoryError::TransactionError(error.to_string()),
             StorageError::Internal(msg) => RepositoryError::InternalError(msg),
         }
     }
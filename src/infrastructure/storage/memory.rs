@@ -1,8 +1,9 @@
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, Mutex, RwLock};
 
-use crate::domain::entity::{Table, Column, Row, Value, DataType};
-use crate::domain::repository::{FilterCondition, FilterOperator};
+use crate::domain::entity::{Table, Column, Row, Value, ValueError, DataType, TypeLimit, View, EnumType, EnumValue, LabelPosition};
+use crate::domain::expression::{Expression, ExpressionError, EXCLUDED};
+use crate::domain::repository::{FilterCondition, OnConflict, ConflictAction, UpsertOutcome};
 use thiserror::Error;
 
 /// ストレージエラー
@@ -17,6 +18,39 @@ pub enum StorageError {
     #[error("Column {0} not found in table {1}")]
     ColumnNotFound(String, String),
     
+    #[error("View {0} not found")]
+    ViewNotFound(String),
+    
+    #[error("View {0} already exists")]
+    ViewAlreadyExists(String),
+    
+    #[error("Cannot modify materialized view {0}; use REFRESH MATERIALIZED VIEW")]
+    MaterializedViewModification(String),
+    
+    #[error("{0} is a materialized view; use DROP MATERIALIZED VIEW")]
+    MaterializedViewDrop(String),
+    
+    #[error("Type {0} not found")]
+    TypeNotFound(String),
+    
+    #[error("Type {0} already exists")]
+    TypeAlreadyExists(String),
+    
+    #[error("Invalid input value for enum {0}: {1}")]
+    InvalidEnumValue(String, String),
+    
+    #[error("Enum label {1} already exists in type {0}")]
+    EnumLabelAlreadyExists(String, String),
+    
+    #[error("Enum label {1} not found in type {0}")]
+    EnumLabelNotFound(String, String),
+    
+    #[error("Value too long for column {0} (maximum {1} characters)")]
+    ValueTooLong(String, usize),
+    
+    #[error("Value {1} out of range for column {0} ({2})")]
+    OutOfRange(String, i64, TypeLimit),
+    
     #[error("Data type mismatch: expected {expected}, got {actual}")]
     TypeMismatch { expected: DataType, actual: DataType },
     
@@ -29,15 +63,118 @@ pub enum StorageError {
     #[error("Primary key constraint violation")]
     PrimaryKeyViolation,
     
+    #[error("There is no unique or primary key constraint on column {0}")]
+    NoUniqueConstraint(String),
+    
+    #[error("A transaction is already in progress")]
+    TransactionAlreadyActive,
+    
+    #[error("No transaction is in progress")]
+    NoActiveTransaction,
+    
+    #[error("Expression error: {0}")]
+    Expression(#[from] ExpressionError),
+    
+    #[error("Invalid value: {0}")]
+    InvalidValue(#[from] ValueError),
+    
     #[error("Internal storage error: {0}")]
     Internal(String),
 }
 
+/// 値をカラムの型に揃える
+///
+/// 10進数はカラムの精度と位取りに丸め、浮動小数点数のカラムでは浮動小数点数にする。日付はタイムスタンプのカラムではその日の0時とする。
+/// 日時のカラムには '2024-01-01' のような文字列も、時間間隔のカラムには '1 day' のような文字列も、JSONやUUIDのカラムにはその形式として正しい文字列も受け付ける。
+/// バイト列のカラムには文字列をそのバイト列（'\x' で始まる場合は16進数）として格納する。
+/// 配列のカラムには要素をカラムの要素の型に揃えた配列を格納し、'{1,2,3}' の形式の文字列も受け付ける。
+/// CHAR(n) のカラムの文字列は末尾を空白で埋めてn文字にする。
+/// 列挙型のカラムにはラベルの文字列を受け付ける（ラベルでない文字列の検査は validate_row に任せる）。
+/// タイムゾーンを持つ日時と持たない日時はUTCで対応させる（セッションのタイムゾーンは実行時に適用済み）。
+/// それ以外の値はそのまま返し、型の検査は validate_row に任せる。
+fn coerce_value(value: &Value, column: &Column) -> Result<Value, StorageError> {
+    match (value, &column.data_type) {
+        (Value::Null, _) => Ok(Value::Null),
+        (_, DataType::Decimal(..)) |
+        (Value::Decimal(_), DataType::Float) |
+        (Value::Date(_) | Value::TimestampTz(_), DataType::Timestamp) |
+        (Value::Date(_) | Value::Timestamp(_), DataType::TimestampTz) |
+        (Value::Text(_), DataType::Timestamp | DataType::TimestampTz | DataType::Date | DataType::Time | DataType::Interval |
+            DataType::Json | DataType::Blob | DataType::Uuid) |
+        (Value::Text(_) | Value::Array(_), DataType::Array(_)) => {
+            Ok(value.cast_to(&column.data_type)?)
+        },
+        (Value::Text(s), DataType::Text) => Ok(match column.type_limit.and_then(|limit| limit.blank_pad(s)) {
+            Some(padded) => Value::Text(padded),
+            None => value.clone(),
+        }),
+        (Value::Text(_) | Value::Enum(_), DataType::Enum(_)) => {
+            Ok(value.cast_to(&column.data_type).unwrap_or_else(|_| value.clone()))
+        },
+        _ => Ok(value.clone()),
+    }
+}
+
+/// 列挙型のカラムの値が型に定義されたラベルかどうかを検査する
+fn check_enum_label(value: &Value, column: &Column) -> Result<(), StorageError> {
+    if let (DataType::Enum(enum_type), Value::Text(label) | Value::Enum(EnumValue { label, .. })) = (&column.data_type, value) {
+        if enum_type.position(label).is_none() {
+            return Err(StorageError::InvalidEnumValue(enum_type.name.clone(), label.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// カラムに宣言された長さや整数の幅に値が収まるかどうかを検査する
+fn check_type_limit(value: &Value, column: &Column) -> Result<(), StorageError> {
+    let Some(type_limit) = column.type_limit else {
+        return Ok(());
+    };
+    match value {
+        Value::Text(s) => match type_limit.max_length() {
+            Some(max) if s.chars().count() > max => Err(StorageError::ValueTooLong(column.name.clone(), max)),
+            _ => Ok(()),
+        },
+        Value::Integer(i) => match type_limit.integer_range() {
+            Some((min, max)) if *i < min || *i > max => Err(StorageError::OutOfRange(column.name.clone(), *i, type_limit)),
+            _ => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// 型に含まれる列挙型を新しい定義に置き換える（その列挙型を含まなければNone）
+fn replace_enum_type(data_type: &DataType, enum_type: &Arc<EnumType>) -> Option<DataType> {
+    match data_type {
+        DataType::Enum(t) if t.name == enum_type.name => Some(DataType::Enum(Arc::clone(enum_type))),
+        DataType::Array(element) => Some(DataType::Array(Box::new(replace_enum_type(element, enum_type)?))),
+        _ => None,
+    }
+}
+
+/// 利用者が変更するテーブルを取得する（マテリアライズドビューの結果を保持するテーブルは変更できない）
+fn writable_table<'a>(
+    tables: &'a mut HashMap<String, TableData>,
+    table_name: &str
+) -> Result<&'a mut TableData, StorageError> {
+    let table_data = tables.get_mut(table_name)
+        .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
+    if table_data.materialized {
+        return Err(StorageError::MaterializedViewModification(table_name.to_string()));
+    }
+    Ok(table_data)
+}
+
 /// テーブルのデータを保持する構造体
+///
+/// 行はカラム名を持たず、スキーマのカラムの順に値を並べて保持する（値のないカラムはNULL）。
+/// 条件や式のカラム参照は、評価の前に Table::get_column_index で位置に解決する。
 #[derive(Debug, Clone)]
 struct TableData {
     schema: Table,
-    rows: Vec<Row>,
+    rows: Vec<Vec<Value>>,
+    /// マテリアライズドビューの結果を保持するテーブルかどうか（利用者は変更できない）
+    materialized: bool,
     // インデックス（後で実装）：カラム名 -> 値 -> 行インデックスのセット
     // simple_indices: HashMap<String, BTreeMap<Value, Vec<usize>>>,
 }
@@ -47,6 +184,7 @@ impl TableData {
         Self {
             schema,
             rows: Vec::new(),
+            materialized: false,
             // simple_indices: HashMap::new(),
         }
     }
@@ -55,22 +193,36 @@ impl TableData {
         self.schema.get_column_index(column_name)
     }
     
-    fn validate_row(&self, row: &Row) -> Result<(), StorageError> {
+    /// カラム名で値を持つ行を、スキーマの順に並べた値にする（テーブルにないカラムの値があればエラー）
+    fn to_values(&self, row: &Row) -> Result<Vec<Value>, StorageError> {
+        if let Some(name) = row.values.keys().filter(|name| self.get_column_index(name).is_none()).min() {
+            return Err(StorageError::ColumnNotFound(name.clone(), self.schema.name.clone()));
+        }
+        
+        Ok(self.schema.columns.iter()
+            .map(|column| row.get(&column.name).cloned().unwrap_or(Value::Null))
+            .collect())
+    }
+    
+    /// スキーマの順に並べた値を、カラム名で値を持つ行にする
+    fn to_row(&self, values: &[Value]) -> Row {
+        Row::from_values(self.schema.columns.iter()
+            .zip(values)
+            .map(|(column, value)| (column.name.clone(), value.clone()))
+            .collect())
+    }
+    
+    /// 行の値をカラムの型に揃える
+    fn coerce_row(&self, values: &mut [Value]) -> Result<(), StorageError> {
+        for (column, value) in self.schema.columns.iter().zip(values.iter_mut()) {
+            *value = coerce_value(value, column)?;
+        }
+        Ok(())
+    }
+    
+    fn validate_row(&self, values: &[Value]) -> Result<(), StorageError> {
         // 各カラムのデータ型と制約をチェック
-        for column in &self.schema.columns {
-            // カラムが存在するかチェック
-            let value = match row.get(&column.name) {
-                Some(v) => v,
-                None => {
-                    // NOT NULL制約のチェック
-                    if column.is_not_null() {
-                        return Err(StorageError::NotNullViolation(column.name.clone()));
-                    }
-                    // NULL値が許容されるのでスキップ
-                    continue;
-                }
-            };
-            
+        for (column, value) in self.schema.columns.iter().zip(values) {
             // NULL値のチェック
             if value.data_type() == DataType::Null {
                 if column.is_not_null() {
@@ -79,57 +231,59 @@ impl TableData {
                 continue;
             }
             
+            check_enum_label(value, column)?;
+            
             // データ型のチェック
-            if value.data_type() != column.data_type {
+            if !value.data_type().is_same_kind(&column.data_type) {
                 return Err(StorageError::TypeMismatch { 
-                    expected: column.data_type,
+                    expected: column.data_type.clone(),
                     actual: value.data_type(),
                 });
             }
             
-            // プライマリキーと一意制約のチェックは後で実装
+            check_type_limit(value, column)?;
         }
         
         Ok(())
     }
     
-    fn insert_row(&mut self, row: Row) -> Result<(), StorageError> {
+    /// 行を挿入し、カラムの型に揃えて格納した行を返す
+    fn insert_row(&mut self, row: Row) -> Result<Row, StorageError> {
+        let values = self.to_values(&row)?;
+        self.insert_values(values)
+    }
+    
+    fn insert_values(&mut self, mut values: Vec<Value>) -> Result<Row, StorageError> {
         // 行のバリデーション
-        self.validate_row(&row)?;
+        self.coerce_row(&mut values)?;
+        self.validate_row(&values)?;
         
         // プライマリキーと一意制約のチェック
-        self.check_constraints(&row)?;
+        self.check_constraints(&values)?;
         
         // 行を追加
-        self.rows.push(row);
+        let row = self.to_row(&values);
+        self.rows.push(values);
         
         // インデックスの更新は後で実装
         
-        Ok(())
+        Ok(row)
     }
     
-    fn check_constraints(&self, row: &Row) -> Result<(), StorageError> {
-        // プライマリキーと一意制約のチェック
-        for column in &self.schema.columns {
-            if column.is_primary_key() || column.is_unique() {
-                if let Some(value) = row.get(&column.name) {
-                    // NULL値はユニーク制約に違反しない（標準SQLの仕様）
-                    if value.data_type() == DataType::Null {
-                        continue;
-                    }
-                    
-                    // 既存の行に同じ値があるかチェック
-                    for existing_row in &self.rows {
-                        if let Some(existing_value) = existing_row.get(&column.name) {
-                            if existing_value == value {
-                                if column.is_primary_key() {
-                                    return Err(StorageError::PrimaryKeyViolation);
-                                } else {
-                                    return Err(StorageError::UniqueViolation(column.name.clone()));
-                                }
-                            }
-                        }
-                    }
+    fn check_constraints(&self, values: &[Value]) -> Result<(), StorageError> {
+        self.check_constraints_except(values, None)
+    }
+    
+    /// 指定した位置の行を除いてプライマリキーと一意制約をチェックする
+    /// （既存の行を更新する場合に自分自身と比較しないため）
+    fn check_constraints_except(&self, values: &[Value], skip: Option<usize>) -> Result<(), StorageError> {
+        for (index, column) in self.schema.columns.iter().enumerate() {
+            if (column.is_primary_key() || column.is_unique())
+                && self.find_duplicate(index, values, skip).is_some() {
+                if column.is_primary_key() {
+                    return Err(StorageError::PrimaryKeyViolation);
+                } else {
+                    return Err(StorageError::UniqueViolation(column.name.clone()));
                 }
             }
         }
@@ -137,144 +291,266 @@ impl TableData {
         Ok(())
     }
     
-    fn filter_rows(&self, filter: &FilterCondition) -> Vec<&Row> {
+    /// 指定した位置のカラムの値が既存の行と重複している場合、その行の位置を返す
+    fn find_duplicate(&self, index: usize, values: &[Value], skip: Option<usize>) -> Option<usize> {
+        let value = &values[index];
+        
+        // NULL値はユニーク制約に違反しない（標準SQLの仕様）
+        if value.data_type() == DataType::Null {
+            return None;
+        }
+        
+        // 照合順序が指定されたカラムでは、その照合順序で等しい文字列（配列の要素を含む）を重複とみなす
+        let collation = self.schema.columns[index].collation;
+        let normalized = collation.map(|collation| collation.normalize(value));
+        let is_same = |existing: &Value| match (collation, &normalized) {
+            (Some(collation), Some(normalized)) => collation.normalize(existing) == *normalized,
+            _ => existing == value,
+        };
         self.rows.iter()
-            .filter(|row| self.eval_filter(row, filter))
-            .collect()
-    }
-    
-    fn eval_filter(&self, row: &Row, filter: &FilterCondition) -> bool {
-        match filter {
-            FilterCondition::Simple { column, operator, value } => {
-                let row_value = match row.get(column) {
-                    Some(v) => v,
-                    None => return false,
-                };
-                
-                match operator {
-                    FilterOperator::Equal => row_value == value,
-                    FilterOperator::NotEqual => row_value != value,
-                    FilterOperator::Greater => {
-                        match (row_value, value) {
-                            (Value::Integer(a), Value::Integer(b)) => a > b,
-                            (Value::Float(a), Value::Float(b)) => a > b,
-                            (Value::Text(a), Value::Text(b)) => a > b,
-                            _ => false,
-                        }
-                    },
-                    FilterOperator::GreaterOrEqual => {
-                        match (row_value, value) {
-                            (Value::Integer(a), Value::Integer(b)) => a >= b,
-                            (Value::Float(a), Value::Float(b)) => a >= b,
-                            (Value::Text(a), Value::Text(b)) => a >= b,
-                            _ => false,
-                        }
-                    },
-                    FilterOperator::Less => {
-                        match (row_value, value) {
-                            (Value::Integer(a), Value::Integer(b)) => a < b,
-                            (Value::Float(a), Value::Float(b)) => a < b,
-                            (Value::Text(a), Value::Text(b)) => a < b,
-                            _ => false,
-                        }
-                    },
-                    FilterOperator::LessOrEqual => {
-                        match (row_value, value) {
-                            (Value::Integer(a), Value::Integer(b)) => a <= b,
-                            (Value::Float(a), Value::Float(b)) => a <= b,
-                            (Value::Text(a), Value::Text(b)) => a <= b,
-                            _ => false,
-                        }
-                    },
-                    FilterOperator::Like => {
-                        // シンプルなLIKE演算子の実装（%のみサポート）
-                        if let (Value::Text(text), Value::Text(pattern)) = (row_value, value) {
-                            if pattern.starts_with('%') && pattern.ends_with('%') {
-                                let search = &pattern[1..pattern.len()-1];
-                                text.contains(search)
-                            } else if pattern.starts_with('%') {
-                                let search = &pattern[1..];
-                                text.ends_with(search)
-                            } else if pattern.ends_with('%') {
-                                let search = &pattern[..pattern.len()-1];
-                                text.starts_with(search)
-                            } else {
-                                text == pattern
-                            }
-                        } else {
-                            false
-                        }
-                    },
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != skip)
+            .find(|(_, existing)| is_same(&existing[index]))
+            .map(|(i, _)| i)
+    }
+    
+    /// UPSERTで競合する既存行の位置を探す
+    fn find_conflict(&self, values: &[Value], conflict_columns: &[String]) -> Result<Option<usize>, StorageError> {
+        let mut indices = Vec::new();
+        if conflict_columns.is_empty() {
+            indices.extend(self.schema.columns.iter()
+                .enumerate()
+                .filter(|(_, c)| c.is_primary_key() || c.is_unique())
+                .map(|(i, _)| i));
+        } else {
+            for name in conflict_columns {
+                let index = self.get_column_index(name)
+                    .ok_or_else(|| StorageError::ColumnNotFound(name.clone(), self.schema.name.clone()))?;
+                let column = &self.schema.columns[index];
+                if !column.is_primary_key() && !column.is_unique() {
+                    return Err(StorageError::NoUniqueConstraint(name.clone()));
                 }
-            },
-            FilterCondition::And(conditions) => {
-                conditions.iter().all(|c| self.eval_filter(row, c))
-            },
-            FilterCondition::Or(conditions) => {
-                conditions.iter().any(|c| self.eval_filter(row, c))
-            },
+                indices.push(index);
+            }
         }
+        
+        Ok(indices.into_iter().find_map(|index| self.find_duplicate(index, values, None)))
     }
     
-    fn update_rows(&mut self, updates: &[(String, Value)], filter: Option<&FilterCondition>) -> usize {
-        let mut updated_count = 0;
+    fn upsert_row(&mut self, row: Row, on_conflict: &OnConflict) -> Result<UpsertOutcome, StorageError> {
+        let mut values = self.to_values(&row)?;
+        self.coerce_row(&mut values)?;
+        self.validate_row(&values)?;
+        
+        let Some(index) = self.find_conflict(&values, &on_conflict.columns)? else {
+            return Ok(UpsertOutcome::Inserted(self.insert_values(values)?));
+        };
+        
+        let assignments = match &on_conflict.action {
+            ConflictAction::DoNothing => return Ok(UpsertOutcome::Skipped),
+            ConflictAction::DoUpdate(assignments) => assignments,
+        };
+        
+        // 式の評価用に、既存の行へ挿入しようとした値を "excluded.カラム名" として加える
+        let old_row = self.to_row(&self.rows[index]);
+        let mut context = old_row.clone();
+        for (column, value) in self.schema.columns.iter().zip(&values) {
+            context.set(format!("{}.{}", EXCLUDED, column.name), value.clone());
+        }
+        
+        let mut new_values = self.rows[index].clone();
+        for (column, expr) in assignments {
+            let position = self.get_column_index(column)
+                .ok_or_else(|| StorageError::ColumnNotFound(column.clone(), self.schema.name.clone()))?;
+            new_values[position] = expr.evaluate(&context)?;
+        }
+        
+        self.coerce_row(&mut new_values)?;
+        self.validate_row(&new_values)?;
+        self.check_constraints_except(&new_values, Some(index))?;
+        let new_row = self.to_row(&new_values);
+        self.rows[index] = new_values;
+        
+        Ok(UpsertOutcome::Updated(old_row, new_row))
+    }
+    
+    /// カラムの列挙型を新しい定義に置き換え、格納済みの値もその定義の値にする
+    fn replace_enum_type(&mut self, enum_type: &Arc<EnumType>) -> Result<(), StorageError> {
+        for (index, column) in self.schema.columns.iter_mut().enumerate() {
+            let Some(data_type) = replace_enum_type(&column.data_type, enum_type) else {
+                continue;
+            };
+            for row in &mut self.rows {
+                row[index] = row[index].cast_to(&data_type)?;
+            }
+            column.data_type = data_type;
+        }
+        Ok(())
+    }
+    
+    /// 条件のカラムに照合順序を付け、カラムの位置に解決する
+    fn bind_filter(&self, filter: &FilterCondition) -> Result<FilterCondition, StorageError> {
+        filter.with_collations(&self.schema.columns)
+            .bind(&self.schema)
+            .map_err(|e| self.bind_error(e))
+    }
+    
+    /// 解決できなかったカラムをこのテーブルのカラムのエラーにする
+    fn bind_error(&self, error: ExpressionError) -> StorageError {
+        match error {
+            ExpressionError::ColumnNotFound(column) => StorageError::ColumnNotFound(column, self.schema.name.clone()),
+            e => StorageError::Expression(e),
+        }
+    }
+    
+    /// 条件を満たす行を返す（条件のカラムは先に位置に解決しておく）
+    fn filter_rows(&self, filter: &FilterCondition) -> Result<Vec<&[Value]>, StorageError> {
+        let filter = self.bind_filter(filter)?;
+        Ok(self.rows.iter()
+            .map(Vec::as_slice)
+            .filter(|row| filter.matches(*row))
+            .collect())
+    }
+    
+    /// 条件を満たす行を更新し、更新前と更新後の行の組を返す
+    ///
+    /// 代入する式は各行の更新前の値で評価する。すべての行が検証を通過した場合のみ反映する。
+    /// プライマリキーと一意制約は、すべての行を更新した後の状態で検査する。
+    fn update_rows(&mut self, updates: &[(usize, Expression)], filter: Option<&FilterCondition>) -> Result<Vec<(Row, Row)>, StorageError> {
+        let updates: Vec<_> = updates.iter()
+            .map(|(index, expr)| Ok((*index, expr.bind(&self.schema).map_err(|e| self.bind_error(e))?)))
+            .collect::<Result<_, StorageError>>()?;
         
         // 事前にフィルタを通過する行のインデックスを収集
         let indices_to_update = if let Some(f) = filter {
+            let f = self.bind_filter(f)?;
             self.rows.iter()
                 .enumerate()
-                .filter(|(_, row)| self.eval_filter(row, f))
+                .filter(|(_, row)| f.matches(row.as_slice()))
                 .map(|(i, _)| i)
                 .collect::<Vec<_>>()
         } else {
             (0..self.rows.len()).collect()
         };
         
-        // 収集したインデックスの行を更新
+        // 収集したインデックスの行の更新後の値を先にすべて計算する
+        let mut new_rows = Vec::with_capacity(indices_to_update.len());
         for idx in indices_to_update {
-            if let Some(row) = self.rows.get_mut(idx) {
-                for (column, value) in updates {
-                    row.set(column.clone(), value.clone());
-                }
-                updated_count += 1;
+            let old_values = &self.rows[idx];
+            let mut new_values = old_values.clone();
+            for (index, expr) in &updates {
+                new_values[*index] = expr.evaluate(old_values.as_slice())?;
+            }
+            self.coerce_row(&mut new_values)?;
+            self.validate_row(&new_values)?;
+            new_rows.push((idx, new_values));
+        }
+        
+        let mut replaced = Vec::with_capacity(new_rows.len());
+        for (idx, new_values) in new_rows {
+            let old_values = std::mem::replace(&mut self.rows[idx], new_values);
+            replaced.push((idx, old_values));
+        }
+        
+        // 制約に違反する行があれば、すべての行を更新前の値に戻す
+        let checked = replaced.iter()
+            .try_for_each(|(idx, _)| self.check_constraints_except(&self.rows[*idx], Some(*idx)));
+        if let Err(e) = checked {
+            for (idx, old_values) in replaced {
+                self.rows[idx] = old_values;
             }
+            return Err(e);
         }
         
-        updated_count
+        Ok(replaced.iter()
+            .map(|(idx, old_values)| (self.to_row(old_values), self.to_row(&self.rows[*idx])))
+            .collect())
     }
     
-    fn delete_rows(&mut self, filter: Option<&FilterCondition>) -> usize {
-        let initial_len = self.rows.len();
+    /// 条件を満たす行を削除し、削除した行を返す
+    fn delete_rows(&mut self, filter: Option<&FilterCondition>) -> Result<Vec<Row>, StorageError> {
+        let filter = filter.map(|f| self.bind_filter(f)).transpose()?;
+        let rows = std::mem::take(&mut self.rows);
         
-        if let Some(filter) = filter {
-            // 削除する代わりに保持する行を収集
-            let rows_to_keep = self.rows.iter()
-                .filter(|row| !self.eval_filter(row, filter))
-                .cloned()  // クローンを作成
-                .collect::<Vec<_>>();
-            
-            let deleted_count = initial_len - rows_to_keep.len();
-            self.rows = rows_to_keep;  // 保持する行で置き換え
-            deleted_count
-        } else {
-            let deleted_count = self.rows.len();
-            self.rows.clear();
-            deleted_count
-        }
+        let (removed, kept): (Vec<_>, Vec<_>) = match filter {
+            Some(filter) => rows.into_iter().partition(|row| filter.matches(row.as_slice())),
+            None => (rows, Vec::new()),
+        };
+        
+        self.rows = kept;
+        Ok(removed.iter().map(|values| self.to_row(values)).collect())
     }
 }
 
+/// トランザクション開始時点のテーブルとビューと型の状態
+#[derive(Debug)]
+struct Snapshot {
+    tables: HashMap<String, TableData>,
+    views: HashMap<String, View>,
+    types: HashMap<String, Arc<EnumType>>,
+}
+
 /// インメモリストレージの実装
 #[derive(Debug, Default)]
 pub struct MemoryStorage {
     tables: RwLock<HashMap<String, TableData>>,
+    views: RwLock<HashMap<String, View>>,
+    /// CREATE TYPE で定義された列挙型
+    types: RwLock<HashMap<String, Arc<EnumType>>>,
+    snapshot: Mutex<Option<Snapshot>>,
 }
 
 impl MemoryStorage {
     pub fn new() -> Self {
         Self {
             tables: RwLock::new(HashMap::new()),
+            views: RwLock::new(HashMap::new()),
+            types: RwLock::new(HashMap::new()),
+            snapshot: Mutex::new(None),
+        }
+    }
+    
+    /// トランザクションを開始し、現在の状態を保存する
+    pub fn begin_transaction(&self) -> Result<(), StorageError> {
+        let mut snapshot = self.snapshot.lock().unwrap();
+        if snapshot.is_some() {
+            return Err(StorageError::TransactionAlreadyActive);
         }
+        
+        let tables = self.tables.read().unwrap();
+        let views = self.views.read().unwrap();
+        let types = self.types.read().unwrap();
+        *snapshot = Some(Snapshot {
+            tables: tables.clone(),
+            views: views.clone(),
+            types: types.clone(),
+        });
+        
+        Ok(())
+    }
+    
+    /// トランザクションを確定し、保存した状態を破棄する
+    pub fn commit_transaction(&self) -> Result<(), StorageError> {
+        self.snapshot.lock().unwrap()
+            .take()
+            .map(|_| ())
+            .ok_or(StorageError::NoActiveTransaction)
+    }
+    
+    /// トランザクション開始時点の状態に戻す
+    pub fn rollback_transaction(&self) -> Result<(), StorageError> {
+        let snapshot = self.snapshot.lock().unwrap()
+            .take()
+            .ok_or(StorageError::NoActiveTransaction)?;
+        
+        let mut tables = self.tables.write().unwrap();
+        let mut views = self.views.write().unwrap();
+        let mut types = self.types.write().unwrap();
+        *tables = snapshot.tables;
+        *views = snapshot.views;
+        *types = snapshot.types;
+        
+        Ok(())
     }
     
     /// テーブルを作成する
@@ -288,6 +564,11 @@ impl MemoryStorage {
             return Err(StorageError::TableAlreadyExists(table.name));
         }
         
+        // ビューと同じ名前のテーブルは作成できない
+        if self.views.read().unwrap().contains_key(&table.name) {
+            return Err(StorageError::ViewAlreadyExists(table.name));
+        }
+        
         tables.insert(table.name.clone(), TableData::new(table));
         Ok(())
     }
@@ -296,11 +577,13 @@ impl MemoryStorage {
     pub fn drop_table(&self, table_name: &str, if_exists: bool) -> Result<(), StorageError> {
         let mut tables = self.tables.write().unwrap();
         
-        if !tables.contains_key(table_name) {
-            if if_exists {
-                return Ok(());
-            }
-            return Err(StorageError::TableNotFound(table_name.to_string()));
+        match tables.get(table_name) {
+            Some(table_data) if table_data.materialized => {
+                return Err(StorageError::MaterializedViewDrop(table_name.to_string()));
+            },
+            Some(_) => {},
+            None if if_exists => return Ok(()),
+            None => return Err(StorageError::TableNotFound(table_name.to_string())),
         }
         
         tables.remove(table_name);
@@ -329,12 +612,181 @@ impl MemoryStorage {
         tables.keys().cloned().collect()
     }
     
-    /// 行を挿入する
-    pub fn insert_row(&self, table_name: &str, row: Row) -> Result<(), StorageError> {
+    /// ビューを作成する
+    pub fn create_view(&self, view: View, or_replace: bool) -> Result<(), StorageError> {
+        // テーブルと同じ名前のビューは作成できない
+        if self.tables.read().unwrap().contains_key(&view.name) {
+            return Err(StorageError::TableAlreadyExists(view.name));
+        }
+        
+        let mut views = self.views.write().unwrap();
+        
+        if views.contains_key(&view.name) && !or_replace {
+            return Err(StorageError::ViewAlreadyExists(view.name));
+        }
+        
+        views.insert(view.name.clone(), view);
+        Ok(())
+    }
+    
+    /// マテリアライズドビューを作成し、その結果を同名のテーブルとして保持する
+    pub fn create_materialized_view(
+        &self,
+        view: View,
+        table: Table,
+        rows: Vec<Row>,
+        or_replace: bool
+    ) -> Result<(), StorageError> {
         let mut tables = self.tables.write().unwrap();
+        let mut views = self.views.write().unwrap();
         
-        let table_data = tables.get_mut(table_name)
-            .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
+        match views.get(&view.name) {
+            Some(existing) if or_replace && existing.materialized => {},
+            Some(_) => return Err(StorageError::ViewAlreadyExists(view.name)),
+            None => {
+                if tables.contains_key(&view.name) {
+                    return Err(StorageError::TableAlreadyExists(view.name));
+                }
+            }
+        }
+        
+        let mut table_data = TableData::new(table);
+        for row in rows {
+            table_data.insert_row(row)?;
+        }
+        table_data.materialized = true;
+        
+        tables.insert(view.name.clone(), table_data);
+        views.insert(view.name.clone(), view);
+        Ok(())
+    }
+    
+    /// ビューを削除する（マテリアライズドビューの場合は保持しているテーブルも削除する）
+    pub fn drop_view(&self, view_name: &str, if_exists: bool) -> Result<(), StorageError> {
+        let mut tables = self.tables.write().unwrap();
+        let mut views = self.views.write().unwrap();
+        
+        match views.remove(view_name) {
+            Some(view) => {
+                if view.materialized {
+                    tables.remove(view_name);
+                }
+            },
+            None => {
+                if !if_exists {
+                    return Err(StorageError::ViewNotFound(view_name.to_string()));
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// ビューが存在するか確認する
+    pub fn view_exists(&self, view_name: &str) -> bool {
+        let views = self.views.read().unwrap();
+        views.contains_key(view_name)
+    }
+    
+    /// ビューの定義を取得する
+    pub fn get_view(&self, view_name: &str) -> Result<View, StorageError> {
+        let views = self.views.read().unwrap();
+        
+        views.get(view_name)
+            .cloned()
+            .ok_or_else(|| StorageError::ViewNotFound(view_name.to_string()))
+    }
+    
+    /// すべてのビュー名を取得する
+    pub fn get_view_names(&self) -> Vec<String> {
+        let views = self.views.read().unwrap();
+        views.keys().cloned().collect()
+    }
+    
+    /// 列挙型を作成する
+    pub fn create_type(&self, enum_type: EnumType) -> Result<(), StorageError> {
+        let mut types = self.types.write().unwrap();
+        
+        if types.contains_key(&enum_type.name) {
+            return Err(StorageError::TypeAlreadyExists(enum_type.name));
+        }
+        
+        for (i, label) in enum_type.labels.iter().enumerate() {
+            if enum_type.labels[..i].contains(label) {
+                return Err(StorageError::EnumLabelAlreadyExists(enum_type.name.clone(), label.clone()));
+            }
+        }
+        
+        types.insert(enum_type.name.clone(), Arc::new(enum_type));
+        Ok(())
+    }
+    
+    /// 列挙型の定義を取得する
+    pub fn get_type(&self, type_name: &str) -> Result<Arc<EnumType>, StorageError> {
+        let types = self.types.read().unwrap();
+        
+        types.get(type_name)
+            .cloned()
+            .ok_or_else(|| StorageError::TypeNotFound(type_name.to_string()))
+    }
+    
+    /// 列挙型にラベルを追加し、その型を使うカラムと格納済みの値を新しい定義に置き換える
+    ///
+    /// 位置を指定しなければ最後に追加する。if_not_exists の場合、既にあるラベルは何もしない。
+    pub fn add_enum_label(
+        &self,
+        type_name: &str,
+        label: &str,
+        position: Option<&LabelPosition>,
+        if_not_exists: bool
+    ) -> Result<(), StorageError> {
+        let mut tables = self.tables.write().unwrap();
+        let mut types = self.types.write().unwrap();
+        
+        let current = types.get(type_name)
+            .ok_or_else(|| StorageError::TypeNotFound(type_name.to_string()))?;
+        
+        if current.position(label).is_some() {
+            if if_not_exists {
+                return Ok(());
+            }
+            return Err(StorageError::EnumLabelAlreadyExists(type_name.to_string(), label.to_string()));
+        }
+        
+        let index = match position {
+            None => current.labels.len(),
+            Some(LabelPosition::Before(neighbor) | LabelPosition::After(neighbor)) => {
+                let index = current.position(neighbor)
+                    .ok_or_else(|| StorageError::EnumLabelNotFound(type_name.to_string(), neighbor.clone()))?;
+                if matches!(position, Some(LabelPosition::After(_))) { index + 1 } else { index }
+            },
+        };
+        
+        let mut enum_type = EnumType::clone(current);
+        enum_type.labels.insert(index, label.to_string());
+        let enum_type = Arc::new(enum_type);
+        
+        // その型を使うテーブルをすべて置き換えられた場合だけ反映する
+        let mut replaced = Vec::new();
+        for (name, table_data) in tables.iter() {
+            if table_data.schema.columns.iter().all(|c| replace_enum_type(&c.data_type, &enum_type).is_none()) {
+                continue;
+            }
+            let mut table_data = table_data.clone();
+            table_data.replace_enum_type(&enum_type)?;
+            replaced.push((name.clone(), table_data));
+        }
+        
+        tables.extend(replaced);
+        types.insert(type_name.to_string(), enum_type);
+        Ok(())
+    }
+    
+    /// 行を挿入し、格納した行を返す
+    pub fn insert_row(&self, table_name: &str, row: Row) -> Result<Row, StorageError> {
+        let mut tables = self.tables.write().unwrap();
+        
+        let table_data = writable_table(&mut tables, table_name)?;
         
         table_data.insert_row(row)
     }
@@ -343,8 +795,7 @@ impl MemoryStorage {
     pub fn insert_rows(&self, table_name: &str, rows: Vec<Row>) -> Result<(), StorageError> {
         let mut tables = self.tables.write().unwrap();
         
-        let table_data = tables.get_mut(table_name)
-            .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
+        let table_data = writable_table(&mut tables, table_name)?;
         
         for row in rows {
             table_data.insert_row(row)?;
@@ -367,9 +818,9 @@ impl MemoryStorage {
         
         // フィルタリング
         let rows: Vec<Row> = if let Some(filter) = filter {
-            table_data.filter_rows(filter).into_iter().cloned().collect()
+            table_data.filter_rows(filter)?.into_iter().map(|values| table_data.to_row(values)).collect()
         } else {
-            table_data.rows.clone()
+            table_data.rows.iter().map(|values| table_data.to_row(values)).collect()
         };
         
         // カラムの選択
@@ -392,41 +843,212 @@ impl MemoryStorage {
         Ok((selected_columns, rows))
     }
     
-    /// 行を更新する
+    /// 行を更新し、更新前と更新後の行の組を返す
     pub fn update_rows(
         &self,
         table_name: &str,
-        updates: &[(String, Value)],
+        updates: &[(String, Expression)],
         filter: Option<&FilterCondition>
-    ) -> Result<usize, StorageError> {
+    ) -> Result<Vec<(Row, Row)>, StorageError> {
+        let mut tables = self.tables.write().unwrap();
+        
+        let table_data = writable_table(&mut tables, table_name)?;
+        
+        // 更新前にカラムの存在確認
+        let mut positions = Vec::with_capacity(updates.len());
+        for (column_name, expr) in updates {
+            let index = table_data.get_column_index(column_name)
+                .ok_or_else(|| StorageError::ColumnNotFound(column_name.clone(), table_name.to_string()))?;
+            positions.push((index, expr.clone()));
+        }
+        
+        table_data.update_rows(&positions, filter)
+    }
+    
+    /// 行を挿入し、競合した場合は指定された方法で解決する
+    pub fn upsert_row(&self, table_name: &str, row: Row, on_conflict: &OnConflict) -> Result<UpsertOutcome, StorageError> {
+        let mut tables = self.tables.write().unwrap();
+        
+        let table_data = writable_table(&mut tables, table_name)?;
+        
+        table_data.upsert_row(row, on_conflict)
+    }
+    
+    /// テーブルの全行を置き換える（すべての行が検証を通過した場合のみ反映する）
+    pub fn replace_rows(&self, table_name: &str, rows: Vec<Row>) -> Result<(), StorageError> {
         let mut tables = self.tables.write().unwrap();
         
         let table_data = tables.get_mut(table_name)
             .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
         
-        // 更新前にカラムの存在確認
-        for (column_name, _) in updates {
-            if table_data.get_column_index(column_name).is_none() {
-                return Err(StorageError::ColumnNotFound(
-                    column_name.clone(), table_name.to_string()
-                ));
+        let mut replacement = TableData::new(table_data.schema.clone());
+        for row in rows {
+            replacement.insert_row(row)?;
+        }
+        
+        table_data.rows = replacement.rows;
+        Ok(())
+    }
+    
+    /// マテリアライズドビューの結果から行を1つずつ削除し、行を追加する
+    ///
+    /// 削除する行と等しい行を1つずつ取り除く（重複行は指定した数だけ削除される）。
+    /// すべての行が検証を通過した場合のみ反映する。
+    pub fn apply_view_delta(&self, view_name: &str, removed: &[Row], added: Vec<Row>) -> Result<(), StorageError> {
+        let mut tables = self.tables.write().unwrap();
+        
+        let table_data = tables.get_mut(view_name)
+            .ok_or_else(|| StorageError::TableNotFound(view_name.to_string()))?;
+        
+        let mut replacement = table_data.clone();
+        for row in removed {
+            let mut values = replacement.to_values(row)?;
+            replacement.coerce_row(&mut values)?;
+            if let Some(pos) = replacement.rows.iter().position(|existing| *existing == values) {
+                replacement.rows.remove(pos);
             }
         }
+        for row in added {
+            replacement.insert_row(row)?;
+        }
         
-        Ok(table_data.update_rows(updates, filter))
+        *table_data = replacement;
+        Ok(())
     }
     
-    /// 行を削除する
+    /// 行を削除し、削除した行を返す
     pub fn delete_rows(
         &self,
         table_name: &str,
         filter: Option<&FilterCondition>
-    ) -> Result<usize, StorageError> {
+    ) -> Result<Vec<Row>, StorageError> {
         let mut tables = self.tables.write().unwrap();
         
-        let table_data = tables.get_mut(table_name)
-            .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
+        let table_data = writable_table(&mut tables, table_name)?;
         
-        Ok(table_data.delete_rows(filter))
+        table_data.delete_rows(filter)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entity::Interval;
+    use crate::domain::repository::FilterOperator;
+
+    fn row(id: i64) -> Row {
+        let mut row = Row::new();
+        row.set("id", Value::Integer(id));
+        row
+    }
+
+    fn storage_with_view() -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        let table = Table::new("mv").with_column(Column::new("id", DataType::Integer)).unwrap();
+        let view = View::new("mv", "SELECT id FROM t").materialized();
+        storage.create_materialized_view(view, table, vec![row(1), row(1), row(2)], false).unwrap();
+        storage
+    }
+
+    #[test]
+    fn materialized_view_table_is_read_only() {
+        let storage = storage_with_view();
+
+        assert!(matches!(storage.insert_row("mv", row(3)), Err(StorageError::MaterializedViewModification(_))));
+        assert!(matches!(storage.insert_rows("mv", vec![row(3)]), Err(StorageError::MaterializedViewModification(_))));
+        assert!(matches!(storage.update_rows("mv", &[], None), Err(StorageError::MaterializedViewModification(_))));
+        assert!(matches!(storage.delete_rows("mv", None), Err(StorageError::MaterializedViewModification(_))));
+        assert!(matches!(storage.drop_table("mv", true), Err(StorageError::MaterializedViewDrop(_))));
+
+        storage.drop_view("mv", false).unwrap();
+        assert!(!storage.table_exists("mv"));
+    }
+
+    #[test]
+    fn apply_view_delta_removes_one_row_per_match() {
+        let storage = storage_with_view();
+
+        storage.apply_view_delta("mv", &[row(1), row(5)], vec![row(3)]).unwrap();
+
+        let (_, rows) = storage.select_rows("mv", None, None).unwrap();
+        let ids: Vec<_> = rows.iter().map(|r| r.get("id").cloned().unwrap()).collect();
+        assert_eq!(ids, vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]);
+    }
+
+    #[test]
+    fn apply_view_delta_is_all_or_nothing() {
+        let storage = storage_with_view();
+        let mut invalid = Row::new();
+        invalid.set("id", Value::Text("x".to_string()));
+
+        assert!(storage.apply_view_delta("mv", &[row(2)], vec![row(3), invalid]).is_err());
+
+        let (_, rows) = storage.select_rows("mv", None, None).unwrap();
+        assert_eq!(rows.len(), 3);
+    }
+
+    #[test]
+    fn rows_with_unknown_columns_are_rejected() {
+        let storage = MemoryStorage::new();
+        storage.create_table(Table::new("t").with_column(Column::new("id", DataType::Integer)).unwrap(), false).unwrap();
+        let mut bogus = row(1);
+        bogus.set("bogus", Value::Text("x".to_string()));
+
+        let error = storage.insert_row("t", bogus).unwrap_err();
+        assert!(matches!(&error, StorageError::ColumnNotFound(column, table) if column == "bogus" && table == "t"), "{}", error);
+        let (_, rows) = storage.select_rows("t", None, None).unwrap();
+        assert!(rows.is_empty());
+
+        // 条件にテーブルにないカラムがあれば、検索も更新も削除もエラーになる
+        storage.insert_row("t", row(1)).unwrap();
+        let filter = FilterCondition::Simple {
+            column: "bogus".to_string(),
+            operator: FilterOperator::Equal,
+            value: Value::Integer(1),
+        };
+        let unknown = |result: Result<_, StorageError>| matches!(result, Err(StorageError::ColumnNotFound(column, _)) if column == "bogus");
+        assert!(unknown(storage.select_rows("t", None, Some(&filter)).map(|_| ())));
+        assert!(unknown(storage.update_rows("t", &[], Some(&filter)).map(|_| ())));
+        assert!(unknown(storage.delete_rows("t", Some(&filter)).map(|_| ())));
+        let (_, rows) = storage.select_rows("t", None, None).unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[test]
+    fn text_is_coerced_to_interval_column() {
+        let storage = MemoryStorage::new();
+        let table = Table::new("t").with_column(Column::new("d", DataType::Interval)).unwrap();
+        storage.create_table(table, false).unwrap();
+
+        let mut row = Row::new();
+        row.set("d", Value::Text("1 day 2 hours".to_string()));
+        let stored = storage.insert_row("t", row).unwrap();
+        assert_eq!(stored.get("d"), Some(&Value::Interval(Interval::parse("1 day 2 hours").unwrap())));
+
+        let mut row = Row::new();
+        row.set("d", Value::Text("soon".to_string()));
+        assert!(storage.insert_row("t", row).is_err());
+    }
+
+    #[test]
+    fn char_values_are_blank_padded() {
+        let storage = MemoryStorage::new();
+        let column = Column::new("code", DataType::Text).with_type_limit(TypeLimit::Char(3));
+        storage.create_table(Table::new("t").with_column(column).unwrap(), false).unwrap();
+        let code = |s: &str| {
+            let mut row = Row::new();
+            row.set("code", Value::Text(s.to_string()));
+            row
+        };
+
+        let stored = storage.insert_row("t", code("ab")).unwrap();
+        assert_eq!(stored.get("code"), Some(&Value::Text("ab ".to_string())));
+        // 長さを超える部分が空白だけなら切り詰める
+        let stored = storage.insert_row("t", code("xyz   ")).unwrap();
+        assert_eq!(stored.get("code"), Some(&Value::Text("xyz".to_string())));
+        assert!(matches!(storage.insert_row("t", code("abcd")), Err(StorageError::ValueTooLong(_, 3))));
+
+        let mut null = Row::new();
+        null.set("code", Value::Null);
+        assert_eq!(storage.insert_row("t", null).unwrap().get("code"), Some(&Value::Null));
+    }
+}
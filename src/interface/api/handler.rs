@@ -3,13 +3,16 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use base64::Engine;
+use chrono::{FixedOffset, SecondsFormat};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use thiserror::Error;
 
+use crate::application::{QueryExecutor, ExecutionResult, ExecutorError, OnError, ScriptOptions};
 use crate::domain::repository::{TableRepository, RepositoryError};
-use crate::domain::entity::{Table, Row, Value};
-use crate::infrastructure::parser::{SqlParser, ParsedStatement};
+use crate::domain::entity::{parse_time_zone, Value};
+use crate::infrastructure::parser::SqlParser;
 
 /// API エラー
 #[derive(Error, Debug)]
@@ -20,6 +23,9 @@ pub enum ApiError {
     #[error("Repository error: {0}")]
     Repository(#[from] RepositoryError),
     
+    #[error("Execution error: {0}")]
+    Execution(String),
+    
     #[error("Unsupported SQL: {0}")]
     UnsupportedSql(String),
     
@@ -32,10 +38,13 @@ impl IntoResponse for ApiError {
         let (status, error_message) = match self {
             ApiError::SqlSyntax(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiError::Repository(e) => match e {
-                RepositoryError::TableNotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
-                RepositoryError::TableAlreadyExists(_) => (StatusCode::CONFLICT, e.to_string()),
+                RepositoryError::TableNotFound(_) |
+                RepositoryError::ViewNotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
+                RepositoryError::TableAlreadyExists(_) |
+                RepositoryError::ViewAlreadyExists(_) => (StatusCode::CONFLICT, e.to_string()),
                 _ => (StatusCode::BAD_REQUEST, e.to_string()),
             },
+            ApiError::Execution(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiError::UnsupportedSql(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
         };
@@ -48,6 +57,17 @@ impl IntoResponse for ApiError {
     }
 }
 
+impl From<ExecutorError> for ApiError {
+    fn from(err: ExecutorError) -> Self {
+        match err {
+            ExecutorError::Repository(e) => ApiError::Repository(e),
+            ExecutorError::Parse(e) => ApiError::SqlSyntax(e.to_string()),
+            ExecutorError::Expression(e) => ApiError::Execution(e.to_string()),
+            ExecutorError::Execution(msg) => ApiError::Execution(msg),
+        }
+    }
+}
+
 /// エラーレスポンス
 #[derive(Serialize)]
 pub struct ErrorResponse {
@@ -58,6 +78,39 @@ pub struct ErrorResponse {
 #[derive(Deserialize)]
 pub struct QueryRequest {
     sql: String,
+    
+    /// 複数の文を実行する場合のエラー時の動作（"stop" または "continue"）
+    #[serde(default)]
+    on_error: OnError,
+    
+    /// すべての文を1つのトランザクションで実行する（on_error の "continue" とは組み合わせられない）
+    #[serde(default)]
+    transaction: bool,
+    
+    /// 結果に含まれるバイト列の表し方（"base64" または "hex"）
+    #[serde(default)]
+    binary_format: BinaryFormat,
+    
+    /// セッションのタイムゾーン（'+09:00' のようなUTCからの時差。省略した場合はUTC）
+    #[serde(default)]
+    time_zone: Option<String>,
+}
+
+/// APIレスポンスでのバイト列の表し方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryFormat {
+    #[default]
+    Base64,
+    Hex,
+}
+
+/// 値をJSONに変換する際の書式
+#[derive(Debug, Clone, Copy)]
+struct OutputFormat {
+    /// タイムゾーン付きの日時を表すタイムゾーン
+    time_zone: FixedOffset,
+    binary_format: BinaryFormat,
 }
 
 /// テーブル情報のレスポンス
@@ -72,6 +125,15 @@ pub struct TableInfoResponse {
 pub struct ColumnInfo {
     name: String,
     data_type: String,
+    /// 宣言された型（VARCHAR(20) や SMALLINT など、長さや幅の指定がある場合）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    declared_type: Option<String>,
+    /// 文字列の最大の文字数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_length: Option<usize>,
+    /// 照合順序（指定がある場合）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    collation: Option<String>,
     constraints: Vec<String>,
 }
 
@@ -87,9 +149,49 @@ pub struct QueryResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     affected_rows: Option<usize>,
     
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inserted_rows: Option<usize>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_rows: Option<usize>,
+    
     statement_type: String,
 }
 
+/// スクリプト中の1文の実行結果
+#[derive(Serialize)]
+pub struct StatementResult {
+    /// スクリプト中の文の位置（0始まり）
+    index: usize,
+    
+    #[serde(flatten)]
+    result: Option<QueryResult>,
+    
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// 複数の文を実行した結果
+#[derive(Serialize)]
+pub struct ScriptResponse {
+    results: Vec<StatementResult>,
+    
+    /// 実行されなかった文の数
+    skipped: usize,
+    
+    /// トランザクションをロールバックしたかどうか
+    rolled_back: bool,
+}
+
+/// SQL実行ハンドラーのレスポンス
+/// （単一の文であれば従来どおりの結果、複数の文であれば文ごとの結果の配列を返す）
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum QueryResponse {
+    Single(QueryResult),
+    Script(ScriptResponse),
+}
+
 /// ヘルスチェックハンドラー
 pub async fn health_check_handler() -> impl IntoResponse {
     StatusCode::OK
@@ -114,6 +216,9 @@ pub async fn get_table_handler(
         ColumnInfo {
             name: col.name.clone(),
             data_type: col.data_type.to_string(),
+            declared_type: col.type_limit.map(|limit| limit.to_string()),
+            max_length: col.type_limit.and_then(|limit| limit.max_length()),
+            collation: col.collation.map(|collation| collation.to_string()),
             constraints: col.constraints.iter().map(|c| c.to_string()).collect(),
         }
     }).collect();
@@ -124,12 +229,22 @@ pub async fn get_table_handler(
     }))
 }
 
+/// ビュー一覧取得ハンドラー
+pub async fn get_views_handler(
+    Extension(repository): Extension<Arc<dyn TableRepository>>,
+) -> Result<Json<Vec<String>>, ApiError> {
+    let views = repository.get_view_names().await?;
+    Ok(Json(views))
+}
+
 /// SQL実行ハンドラー
+///
+/// リクエストごとに新しいセッションで実行するので、SET TIME ZONE はそのリクエストの後続の文だけに適用される。
 pub async fn execute_sql_handler(
-    Extension(repository): Extension<Arc<dyn TableRepository>>,
+    Extension(executor): Extension<Arc<QueryExecutor>>,
     Extension(parser): Extension<Arc<SqlParser>>,
     Json(payload): Json<QueryRequest>,
-) -> Result<Json<QueryResult>, ApiError> {
+) -> Result<Json<QueryResponse>, ApiError> {
     // SQLの解析
     let statements = match parser.parse(&payload.sql) {
         Ok(stmts) => stmts,
@@ -140,130 +255,304 @@ pub async fn execute_sql_handler(
         return Err(ApiError::SqlSyntax("No SQL statement provided".to_string()));
     }
     
-    // 現時点では単一のSQLステートメントのみをサポート
-    let stmt = &statements[0];
+    let executor = executor.session();
+    if let Some(time_zone) = &payload.time_zone {
+        let time_zone = parse_time_zone(time_zone)
+            .ok_or_else(|| ApiError::SqlSyntax(format!("Invalid time zone: {} (use a UTC offset such as '+09:00')", time_zone)))?;
+        executor.set_time_zone(time_zone);
+    }
     
-    // ステートメントのタイプに応じた処理
-    match stmt {
-        ParsedStatement::CreateTable(create_stmt) => {
-            let mut table = Table::new(&create_stmt.table_name);
-            for column in &create_stmt.columns {
-                table.add_column(column.clone())
-                    .map_err(|e| ApiError::Internal(e.to_string()))?;
-            }
-            
-            repository.create_table(&table).await?;
-            
-            Ok(Json(QueryResult {
-                columns: None,
-                rows: None,
-                affected_rows: Some(0),
-                statement_type: "CREATE_TABLE".to_string(),
-            }))
-        },
-        
-        ParsedStatement::Select(select_stmt) => {
-            let columns = select_stmt.columns.as_ref().map_or(Vec::new(), |c| c.clone());
-            let result = repository.select(
-                &select_stmt.table_name, 
-                &columns, 
-                select_stmt.filter.as_ref()
-            ).await?;
-            
-            // 結果を変換
-            let column_names = result.columns.iter().map(|c| c.name.clone()).collect();
+    if statements.len() == 1 && !payload.transaction {
+        let result = executor.execute(&statements[0]).await?;
+        return Ok(Json(QueryResponse::Single(to_query_result(result, payload.binary_format))));
+    }
+    
+    let options = ScriptOptions {
+        on_error: payload.on_error,
+        transaction: payload.transaction,
+    };
+    let script = executor.execute_script(&statements, options).await?;
+    
+    let skipped = statements.len() - script.results.len();
+    let results = script.results.into_iter().enumerate().map(|(index, result)| {
+        match result {
+            Ok(result) => StatementResult {
+                index,
+                result: Some(to_query_result(result, payload.binary_format)),
+                error: None,
+            },
+            Err(e) => StatementResult {
+                index,
+                result: None,
+                error: Some(ApiError::from(e).to_string()),
+            },
+        }
+    }).collect();
+    
+    Ok(Json(QueryResponse::Script(ScriptResponse {
+        results,
+        skipped,
+        rolled_back: script.rolled_back,
+    })))
+}
+
+/// 実行結果をAPIレスポンスの形式に変換する
+///
+/// タイムゾーン付きの日時は、その文を実行した時点のセッションのタイムゾーンで表す。
+fn to_query_result(result: ExecutionResult, binary_format: BinaryFormat) -> QueryResult {
+    let format = OutputFormat { time_zone: result.time_zone, binary_format };
+    let (columns, rows) = match result.result_set {
+        Some(result_set) => {
+            let column_names = result_set.columns.iter().map(|c| c.name.clone()).collect();
             
-            let rows = result.rows.iter().map(|row| {
+            let rows = result_set.rows.iter().map(|row| {
                 let mut obj = serde_json::Map::new();
-                for column in &result.columns {
-                    let value = match row.get(&column.name) {
-                        Some(Value::Integer(i)) => serde_json::Value::Number(serde_json::Number::from(*i)),
-                        Some(Value::Float(f)) => {
-                            if let Some(num) = serde_json::Number::from_f64(*f) {
-                                serde_json::Value::Number(num)
-                            } else {
-                                serde_json::Value::String(f.to_string())
-                            }
-                        },
-                        Some(Value::Text(s)) => serde_json::Value::String(s.clone()),
-                        Some(Value::Boolean(b)) => serde_json::Value::Bool(*b),
-                        Some(Value::Timestamp(dt)) => serde_json::Value::String(dt.to_string()),
-                        Some(Value::Null) => serde_json::Value::Null,
-                        None => serde_json::Value::Null,
-                    };
-                    obj.insert(column.name.clone(), value);
+                for column in &result_set.columns {
+                    obj.insert(column.name.clone(), value_to_json(row.get(&column.name), format));
                 }
                 serde_json::Value::Object(obj)
             }).collect();
             
-            Ok(Json(QueryResult {
-                columns: Some(column_names),
-                rows: Some(rows),
-                affected_rows: None,
-                statement_type: "SELECT".to_string(),
-            }))
+            (Some(column_names), Some(rows))
         },
-        
-        ParsedStatement::Insert(insert_stmt) => {
-            let mut affected_rows = 0;
-            
-            for values in &insert_stmt.values {
-                let mut row = Row::new();
-                for (i, value) in values.iter().enumerate() {
-                    if i < insert_stmt.columns.len() {
-                        row.set(insert_stmt.columns[i].clone(), value.clone());
-                    }
-                }
-                repository.insert(&insert_stmt.table_name, &row).await?;
-                affected_rows += 1;
+        None => (None, None),
+    };
+    
+    QueryResult {
+        columns,
+        rows,
+        affected_rows: result.affected_rows,
+        inserted_rows: result.inserted_rows,
+        updated_rows: result.updated_rows,
+        statement_type: result.statement_type,
+    }
+}
+
+/// 値をJSON表現に変換する
+fn value_to_json(value: Option<&Value>, format: OutputFormat) -> serde_json::Value {
+    match value {
+        Some(Value::Integer(i)) => serde_json::Value::Number(serde_json::Number::from(*i)),
+        Some(Value::Float(f)) => {
+            if let Some(num) = serde_json::Number::from_f64(*f) {
+                serde_json::Value::Number(num)
+            } else {
+                serde_json::Value::String(f.to_string())
             }
-            
-            Ok(Json(QueryResult {
-                columns: None,
-                rows: None,
-                affected_rows: Some(affected_rows),
-                statement_type: "INSERT".to_string(),
-            }))
-        },
-        
-        ParsedStatement::Update(update_stmt) => {
-            let affected = repository.update(
-                &update_stmt.table_name,
-                &update_stmt.updates,
-                update_stmt.filter.as_ref()
-            ).await?;
-            
-            Ok(Json(QueryResult {
-                columns: None,
-                rows: None,
-                affected_rows: Some(affected),
-                statement_type: "UPDATE".to_string(),
-            }))
-        },
-        
-        ParsedStatement::Delete(delete_stmt) => {
-            let affected = repository.delete(
-                &delete_stmt.table_name,
-                delete_stmt.filter.as_ref()
-            ).await?;
-            
-            Ok(Json(QueryResult {
-                columns: None,
-                rows: None,
-                affected_rows: Some(affected),
-                statement_type: "DELETE".to_string(),
-            }))
-        },
-        
-        ParsedStatement::DropTable(drop_stmt) => {
-            repository.drop_table(&drop_stmt.table_name).await?;
-            
-            Ok(Json(QueryResult {
-                columns: None,
-                rows: None,
-                affected_rows: None,
-                statement_type: "DROP_TABLE".to_string(),
-            }))
         },
+        Some(Value::Text(s)) => serde_json::Value::String(s.clone()),
+        Some(Value::Boolean(b)) => serde_json::Value::Bool(*b),
+        // 日時や時間間隔は ISO 8601 形式の文字列として返す（タイムゾーン付きの日時は RFC 3339 形式）
+        Some(Value::Timestamp(dt)) => serde_json::Value::String(dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()),
+        Some(Value::TimestampTz(dt)) => serde_json::Value::String(
+            dt.with_timezone(&format.time_zone).to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        Some(Value::Date(d)) => serde_json::Value::String(d.format("%Y-%m-%d").to_string()),
+        Some(Value::Time(t)) => serde_json::Value::String(t.format("%H:%M:%S%.f").to_string()),
+        Some(Value::Interval(i)) => serde_json::Value::String(i.to_iso8601()),
+        // 10進数は精度を失わないよう文字列として返す
+        Some(Value::Decimal(d)) => serde_json::Value::String(d.to_string()),
+        // JSONの値は文字列にせずそのまま埋め込む
+        Some(Value::Json(json)) => json.clone(),
+        Some(Value::Uuid(u)) => serde_json::Value::String(u.to_string()),
+        Some(Value::Enum(e)) => serde_json::Value::String(e.label.clone()),
+        // バイト列は指定に応じて Base64 または16進数の文字列として返す
+        Some(Value::Bytes(b)) => serde_json::Value::String(match format.binary_format {
+            BinaryFormat::Base64 => base64::engine::general_purpose::STANDARD.encode(b),
+            BinaryFormat::Hex => b.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        }),
+        // 配列は要素を同じ規則で変換したJSONの配列として返す
+        Some(Value::Array(items)) => serde_json::Value::Array(
+            items.iter().map(|item| value_to_json(Some(item), format)).collect()),
+        Some(Value::Null) => serde_json::Value::Null,
+        None => serde_json::Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::executor::testing::{exec, executor, query};
+    use crate::infrastructure::repository::MemoryTableRepository;
+    use crate::infrastructure::storage::MemoryStorage;
+
+    async fn post(executor: &Arc<QueryExecutor>, request: serde_json::Value) -> Result<Json<QueryResponse>, ApiError> {
+        let request: QueryRequest = serde_json::from_value(request).unwrap();
+        execute_sql_handler(Extension(Arc::clone(executor)), Extension(Arc::new(SqlParser::new())), Json(request)).await
+    }
+
+    /// 単一の文を実行し、レスポンスの rows をJSONとして返す
+    async fn rows(executor: &Arc<QueryExecutor>, request: serde_json::Value) -> serde_json::Value {
+        let Ok(Json(response)) = post(executor, request).await else {
+            panic!("expected a response");
+        };
+        serde_json::to_value(response).unwrap()["rows"].clone()
+    }
+
+    #[tokio::test]
+    async fn transaction_with_continue_on_error_is_a_bad_request() {
+        let executor = Arc::new(executor());
+        exec(&executor, "CREATE TABLE t (id INTEGER)").await;
+
+        let response = post(&executor, serde_json::json!({
+            "sql": "INSERT INTO t VALUES (1); INSERT INTO t VALUES ('x')",
+            "transaction": true,
+            "on_error": "continue",
+        })).await;
+        let Err(error) = response else {
+            panic!("expected an error");
+        };
+        assert!(error.to_string().contains("on_error=continue"), "{}", error);
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+
+        // どの文も実行しない
+        assert!(query(&executor, "SELECT id FROM t").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn decimals_are_returned_as_exact_strings() {
+        let executor = Arc::new(executor());
+
+        let rows = rows(&executor, serde_json::json!({"sql": "SELECT 1.50 AS d, 0.1 + 0.2 AS s, CAST(NULL AS DECIMAL(4,2)) AS n"})).await;
+        assert_eq!(rows, serde_json::json!([{"d": "1.50", "s": "0.3", "n": null}]));
+    }
+
+    #[tokio::test]
+    async fn dates_times_and_intervals_are_returned_as_iso_8601() {
+        let executor = Arc::new(executor());
+
+        let rows = rows(&executor, serde_json::json!({
+            "sql": "SELECT DATE '2024-02-29' AS d, TIME '13:05:09.25' AS t, TIME '00:00:00' AS midnight, \
+                TIMESTAMP '2024-02-29 13:05:09' AS ts, INTERVAL '1 year 2 months 3 days 04:05:06' AS i, \
+                INTERVAL '0 seconds' AS zero, CAST(NULL AS DATE) AS n",
+        })).await;
+        assert_eq!(rows, serde_json::json!([{
+            "d": "2024-02-29",
+            "t": "13:05:09.250",
+            "midnight": "00:00:00",
+            "ts": "2024-02-29T13:05:09",
+            "i": "P1Y2M3DT4H5M6S",
+            "zero": "PT0S",
+            "n": null,
+        }]));
+    }
+
+    #[tokio::test]
+    async fn timestamptz_is_returned_in_the_session_offset() {
+        let executor = Arc::new(executor());
+        let sql = "SELECT CAST('2024-03-01 00:30:00+00' AS TIMESTAMPTZ) AS ts";
+
+        let utc = rows(&executor, serde_json::json!({"sql": sql})).await;
+        assert_eq!(utc, serde_json::json!([{"ts": "2024-03-01T00:30:00Z"}]));
+        let tokyo = rows(&executor, serde_json::json!({"sql": sql, "time_zone": "+09:00"})).await;
+        assert_eq!(tokyo, serde_json::json!([{"ts": "2024-03-01T09:30:00+09:00"}]));
+        let india = rows(&executor, serde_json::json!({"sql": sql, "time_zone": "-0530"})).await;
+        assert_eq!(india, serde_json::json!([{"ts": "2024-02-29T19:00:00-05:30"}]));
+    }
+
+    #[tokio::test]
+    async fn script_results_use_the_time_zone_of_each_statement() {
+        let executor = Arc::new(executor());
+        exec(&executor, "CREATE TABLE e (ts TIMESTAMPTZ); INSERT INTO e VALUES ('2024-03-01 00:30:00+00')").await;
+
+        let Ok(Json(response)) = post(&executor, serde_json::json!({
+            "sql": "SELECT ts FROM e; SET TIME ZONE '+09:00'; SELECT ts FROM e",
+        })).await else {
+            panic!("expected a response");
+        };
+        let results = serde_json::to_value(response).unwrap()["results"].clone();
+        assert_eq!(results[0]["rows"], serde_json::json!([{"ts": "2024-03-01T00:30:00Z"}]));
+        assert_eq!(results[2]["rows"], serde_json::json!([{"ts": "2024-03-01T09:30:00+09:00"}]));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn time_zones_must_be_utc_offsets() {
+        let executor = Arc::new(executor());
+
+        for time_zone in ["+09:00", "-0530", "UTC"] {
+            let response = post(&executor, serde_json::json!({"sql": "SHOW TIME ZONE", "time_zone": time_zone})).await;
+            assert!(response.is_ok(), "{}", time_zone);
+        }
+
+        // 地域名のタイムゾーンは扱わない
+        let Err(error) = post(&executor, serde_json::json!({"sql": "SHOW TIME ZONE", "time_zone": "Asia/Tokyo"})).await else {
+            panic!("expected an error");
+        };
+        assert!(error.to_string().contains("Invalid time zone: Asia/Tokyo"), "{}", error);
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+        let Err(error) = post(&executor, serde_json::json!({"sql": "SET TIME ZONE 'Europe/Paris'"})).await else {
+            panic!("expected an error");
+        };
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn json_values_are_embedded_as_json() {
+        let executor = Arc::new(executor());
+        exec(&executor, r#"
+            CREATE TABLE docs (doc JSONB);
+            INSERT INTO docs VALUES ('{"name": "a", "tags": ["x", 1.5], "extra": null}')
+        "#).await;
+
+        let rows = rows(&executor, serde_json::json!({
+            "sql": "SELECT doc, doc -> 'tags' AS tags, doc ->> 'name' AS name, CAST('null' AS JSON) AS j, \
+                CAST(NULL AS JSON) AS n FROM docs",
+        })).await;
+        // 文字列に変換せず、JSONのnullとSQLのNULLはどちらも null になる
+        assert_eq!(rows, serde_json::json!([{
+            "doc": {"name": "a", "tags": ["x", 1.5], "extra": null},
+            "tags": ["x", 1.5],
+            "name": "a",
+            "j": null,
+            "n": null,
+        }]));
+    }
+
+    #[tokio::test]
+    async fn bytes_are_returned_as_base64_or_hex() {
+        let executor = Arc::new(executor());
+        let sql = "SELECT X'CAFE' AS b, CAST(NULL AS BYTEA) AS n";
+
+        let base64 = rows(&executor, serde_json::json!({"sql": sql})).await;
+        assert_eq!(base64, serde_json::json!([{"b": "yv4=", "n": null}]));
+        let hex = rows(&executor, serde_json::json!({"sql": sql, "binary_format": "hex"})).await;
+        assert_eq!(hex, serde_json::json!([{"b": "cafe", "n": null}]));
+
+        let request = serde_json::from_value::<QueryRequest>(serde_json::json!({"sql": sql, "binary_format": "octal"}));
+        assert!(request.is_err());
+    }
+
+    #[tokio::test]
+    async fn arrays_are_returned_as_json_arrays() {
+        let executor = Arc::new(executor());
+
+        let rows = rows(&executor, serde_json::json!({
+            "sql": "SELECT ARRAY[1, NULL] AS a, ARRAY[1.50] AS d, CAST('{}' AS TEXT[]) AS e, CAST(NULL AS INTEGER[]) AS n",
+        })).await;
+        // 要素も同じ規則で変換する（10進数は文字列）
+        assert_eq!(rows, serde_json::json!([{"a": [1, null], "d": ["1.50"], "e": [], "n": null}]));
+    }
+
+    #[tokio::test]
+    async fn table_info_shows_declared_lengths_and_widths() {
+        let storage = Arc::new(MemoryStorage::new());
+        let repository: Arc<dyn TableRepository> = Arc::new(MemoryTableRepository::new(storage));
+        let executor = QueryExecutor::new(Arc::clone(&repository));
+        exec(&executor, "CREATE TABLE w (s SMALLINT, v VARCHAR(3), c CHAR(2), t TEXT)").await;
+
+        let Ok(Json(info)) = get_table_handler(Path("w".to_string()), Extension(Arc::clone(&repository))).await else {
+            panic!("expected table info");
+        };
+        let columns = serde_json::to_value(info).unwrap()["columns"].clone();
+        let declared: Vec<_> = columns.as_array().unwrap().iter()
+            .map(|c| (c["declared_type"].clone(), c["max_length"].clone()))
+            .collect();
+        assert_eq!(declared, vec![
+            (serde_json::json!("SMALLINT"), serde_json::Value::Null),
+            (serde_json::json!("VARCHAR(3)"), serde_json::json!(3)),
+            (serde_json::json!("CHAR(2)"), serde_json::json!(2)),
+            (serde_json::Value::Null, serde_json::Value::Null),
+        ]);
+
+        let missing = get_table_handler(Path("nope".to_string()), Extension(repository)).await;
+        assert!(matches!(missing, Err(ApiError::Repository(_))));
+    }
+}
@@ -8,6 +8,7 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing::info;
 
+use crate::application::QueryExecutor;
 use crate::domain::repository::TableRepository;
 use crate::infrastructure::storage::MemoryStorage;
 use crate::infrastructure::repository::MemoryTableRepository;
@@ -16,6 +17,7 @@ use crate::interface::api::handler::{
     health_check_handler, 
     get_tables_handler, 
     get_table_handler, 
+    get_views_handler,
     execute_sql_handler
 };
 
@@ -37,17 +39,20 @@ pub async fn start_server(config: ServerConfig) -> Result<(), Box<dyn std::error
     let storage = Arc::new(MemoryStorage::new());
     let repository: Arc<dyn TableRepository> = Arc::new(MemoryTableRepository::new(storage.clone()));
     
-    // SQLパーサーの初期化
+    // SQLパーサーとエグゼキューターの初期化
     let parser = Arc::new(SqlParser::new());
+    let executor = Arc::new(QueryExecutor::new(repository.clone()));
 
     // ルーターの設定
     let app = Router::new()
         .route("/health", get(health_check_handler))
         .route("/api/tables", get(get_tables_handler))
         .route("/api/tables/:table_name", get(get_table_handler))
+        .route("/api/views", get(get_views_handler))
         .route("/api/query", post(execute_sql_handler))
         .layer(Extension(repository))  // リポジトリの拡張
-        .layer(Extension(parser));     // パーサーの拡張
+        .layer(Extension(parser))      // パーサーの拡張
+        .layer(Extension(executor));   // エグゼキューターの拡張
 
     // サーバーのアドレス設定
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));